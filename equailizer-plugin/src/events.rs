@@ -4,6 +4,7 @@ use crate::protocol::{BatchReconcileError, Transaction};
 pub struct BatchCreated {
     pub batch_id: String,
     pub total: String,
+    pub currency: String,
     pub transactions: Vec<Transaction>,
     pub warnings: Vec<String>,
 }
@@ -12,6 +13,7 @@ pub struct BatchCreated {
 pub struct BatchReconciled {
     pub batch_id: String,
     pub amount: String,
+    pub currency: String,
     pub settlement_credit_id: u32,
     pub settlement_debit_id: u32,
 }
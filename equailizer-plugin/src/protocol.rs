@@ -14,12 +14,17 @@ pub enum PluginMessage {
     BatchCreated {
         batch_id: String,
         total: String,
+        /// ISO currency code of every amount in the batch, in lower case.
+        #[serde(default = "default_currency")]
+        currency: String,
         transactions: Vec<Transaction>,
         warnings: Vec<String>,
     },
     BatchReconciled {
         batch_id: String,
         amount: String,
+        #[serde(default = "default_currency")]
+        currency: String,
         settlement_credit_id: u32,
         settlement_debit_id: u32,
     },
@@ -35,6 +40,11 @@ pub enum PluginMessage {
     Shutdown,
 }
 
+/// Hosts before currency support only sent dollar amounts.
+fn default_currency() -> String {
+    "usd".to_string()
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transaction {
     pub payee: String,
@@ -85,6 +95,7 @@ mod tests {
         let msg = PluginMessage::BatchCreated {
             batch_id: "abc-123".to_string(),
             total: "40.00".to_string(),
+            currency: "usd".to_string(),
             transactions: vec![Transaction {
                 payee: "Store A".to_string(),
                 amount: "15.00".to_string(),
@@ -109,6 +120,7 @@ mod tests {
         let msg = PluginMessage::BatchReconciled {
             batch_id: "abc-123".to_string(),
             amount: "40.00".to_string(),
+            currency: "usd".to_string(),
             settlement_credit_id: 50,
             settlement_debit_id: 60,
         };
@@ -210,6 +222,7 @@ mod tests {
         let msg = PluginMessage::BatchCreated {
             batch_id: "abc-123".to_string(),
             total: "40.00".to_string(),
+            currency: "usd".to_string(),
             transactions: vec![
                 Transaction {
                     payee: "Store A".to_string(),
@@ -236,6 +249,7 @@ mod tests {
         let msg = PluginMessage::BatchReconciled {
             batch_id: "abc-123".to_string(),
             amount: "40.00".to_string(),
+            currency: "usd".to_string(),
             settlement_credit_id: 50,
             settlement_debit_id: 60,
         };
@@ -297,11 +311,13 @@ mod tests {
             PluginMessage::BatchCreated {
                 batch_id,
                 total,
+                currency,
                 transactions,
                 warnings,
             } => {
                 assert_eq!(batch_id, "abc-123");
                 assert_eq!(total, "40.00");
+                assert_eq!(currency, "usd");
                 assert_eq!(transactions.len(), 2);
                 assert_eq!(transactions[0].payee, "Store A");
                 assert_eq!(transactions[1].notes, None);
@@ -325,11 +341,13 @@ mod tests {
             PluginMessage::BatchReconciled {
                 batch_id,
                 amount,
+                currency,
                 settlement_credit_id,
                 settlement_debit_id,
             } => {
                 assert_eq!(batch_id, "abc-123");
                 assert_eq!(amount, "40.00");
+                assert_eq!(currency, "usd");
                 assert_eq!(settlement_credit_id, 50);
                 assert_eq!(settlement_debit_id, 60);
             }
@@ -98,6 +98,7 @@ fn dispatch_event(
         PluginMessage::BatchCreated {
             batch_id,
             total,
+            currency,
             transactions,
             warnings,
         } => plugin.on_batch_created(
@@ -105,6 +106,7 @@ fn dispatch_event(
             &BatchCreated {
                 batch_id,
                 total,
+                currency,
                 transactions,
                 warnings,
             },
@@ -112,6 +114,7 @@ fn dispatch_event(
         PluginMessage::BatchReconciled {
             batch_id,
             amount,
+            currency,
             settlement_credit_id,
             settlement_debit_id,
         } => plugin.on_batch_reconciled(
@@ -119,6 +122,7 @@ fn dispatch_event(
             &BatchReconciled {
                 batch_id,
                 amount,
+                currency,
                 settlement_credit_id,
                 settlement_debit_id,
             },
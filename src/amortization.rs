@@ -0,0 +1,167 @@
+use crate::lunch_money::model::transaction::{Transaction, TransactionId};
+use crate::money::Money;
+use chrono::{Months, NaiveDate};
+use display_json::DebugAsJson;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A large expense billed to the debtor in monthly installments instead of all
+/// at once. Installment `n` falls due `n - 1` months after the transaction date,
+/// and each `create-batch` run bills the installments that are due.
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AmortizationSchedule {
+    pub id: String,
+    pub transaction_id: TransactionId,
+    pub payee: String,
+    pub date: NaiveDate,
+    pub notes: Option<String>,
+    /// The transaction's category before it was moved to the proxy category.
+    #[serde(default)]
+    pub category_id: Option<u32>,
+    pub total: Money,
+    pub installments: Vec<Installment>,
+}
+
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Installment {
+    pub number: u32,
+    pub due: NaiveDate,
+    pub amount: Money,
+    /// The batch this installment was billed in, once it has been.
+    pub batch_id: Option<String>,
+}
+
+impl AmortizationSchedule {
+    pub fn new(txn: &Transaction, months: u32) -> Self {
+        let months = months.max(1);
+        // Every installment but the last is rounded down to the cent; the last
+        // takes the remainder so the schedule always sums to the total.
+        let currency = txn.amount.currency();
+        let base = Money::new(
+            (txn.amount.value() / Decimal::from(months))
+                .round_dp_with_strategy(currency.minor_units(), rust_decimal::RoundingStrategy::ToZero),
+            currency,
+        );
+        let last = txn.amount - base * Decimal::from(months - 1);
+
+        let installments = (1..=months)
+            .map(|number| Installment {
+                number,
+                due: txn
+                    .date
+                    .checked_add_months(Months::new(number - 1))
+                    .unwrap_or(NaiveDate::MAX),
+                amount: if number == months { last } else { base },
+                batch_id: None,
+            })
+            .collect();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            transaction_id: txn.id,
+            payee: txn.payee.clone(),
+            date: txn.date,
+            notes: txn.notes.clone(),
+            category_id: txn.category_id,
+            total: txn.amount,
+            installments,
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.installments.len() as u32
+    }
+
+    pub fn billed_count(&self) -> u32 {
+        self.installments
+            .iter()
+            .filter(|i| i.batch_id.is_some())
+            .count() as u32
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.billed_count() == self.count()
+    }
+
+    /// Unbilled installments due on or before `as_of`.
+    pub fn due_installments(&self, as_of: NaiveDate) -> Vec<&Installment> {
+        self.installments
+            .iter()
+            .filter(|i| i.batch_id.is_none() && i.due <= as_of)
+            .collect()
+    }
+
+    pub fn mark_billed(&mut self, number: u32, batch_id: &str) {
+        if let Some(installment) = self.installments.iter_mut().find(|i| i.number == number) {
+            installment.batch_id = Some(batch_id.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lunch_money::model::transaction::TransactionStatus;
+    use crate::money::Currency;
+
+    fn txn(cents: i64) -> Transaction {
+        Transaction {
+            id: 7,
+            date: NaiveDate::from_ymd_opt(2025, 1, 31).expect("NaiveDate creation failed"),
+            payee: "Lemonade Insurance".to_string(),
+            amount: Money::new_from_minor(cents, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: None,
+            category_name: None,
+            tags: vec![],
+            notes: Some("annual premium".to_string()),
+            status: TransactionStatus::Cleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        }
+    }
+
+    #[test]
+    fn installments_sum_to_total() {
+        let schedule = AmortizationSchedule::new(&txn(100000), 12);
+        assert_eq!(schedule.count(), 12);
+        assert_eq!(schedule.installments[0].amount, Money::new_from_minor(8333, Currency::USD));
+        assert_eq!(schedule.installments[11].amount, Money::new_from_minor(8337, Currency::USD));
+        let sum = schedule
+            .installments
+            .iter()
+            .fold(Money::new_from_minor(0, Currency::USD), |acc, i| acc + i.amount);
+        assert_eq!(sum, Money::new_from_minor(100000, Currency::USD));
+    }
+
+    #[test]
+    fn due_dates_are_monthly_and_clamped_to_month_end() {
+        let schedule = AmortizationSchedule::new(&txn(30000), 3);
+        let dues: Vec<NaiveDate> = schedule.installments.iter().map(|i| i.due).collect();
+        assert_eq!(
+            dues,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn due_installments_skip_billed_and_future() {
+        let mut schedule = AmortizationSchedule::new(&txn(30000), 3);
+        schedule.mark_billed(1, "batch-1");
+
+        let due = schedule.due_installments(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap());
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].number, 2);
+
+        schedule.mark_billed(2, "batch-2");
+        schedule.mark_billed(3, "batch-3");
+        assert!(schedule.is_complete());
+    }
+}
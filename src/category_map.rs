@@ -0,0 +1,117 @@
+use crate::lunch_money::model::category::{Category, CategoryId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Which of the debtor's categories each of the creditor's categories becomes
+/// when the debtor's settlement is split.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CategoryMap {
+    /// Creditor category id to debtor category id.
+    #[serde(default)]
+    pub categories: BTreeMap<CategoryId, CategoryId>,
+    /// The debtor category for transactions whose category isn't mapped.
+    #[serde(default)]
+    pub fallback: Option<CategoryId>,
+}
+
+impl CategoryMap {
+    /// The debtor category for a transaction the creditor had in `creditor_category`.
+    pub fn debtor_category(&self, creditor_category: Option<CategoryId>) -> Option<CategoryId> {
+        creditor_category
+            .and_then(|id| self.categories.get(&id).copied())
+            .or(self.fallback)
+    }
+}
+
+/// Match creditor categories to debtor categories with the same name, ignoring
+/// case and surrounding whitespace. Groups, archived categories and anything
+/// in `skip` are left out. Returns the matches and the creditor categories
+/// with no match.
+pub fn suggest(
+    creditor: &[Category],
+    debtor: &[Category],
+    skip: &[CategoryId],
+) -> (BTreeMap<CategoryId, CategoryId>, Vec<Category>) {
+    let assignable = |c: &&Category| !c.is_group && !c.archived;
+    let by_name: BTreeMap<String, CategoryId> = debtor
+        .iter()
+        .filter(assignable)
+        .map(|c| (normalize(&c.name), c.id))
+        .collect();
+
+    let mut matched = BTreeMap::new();
+    let mut unmatched = vec![];
+    for category in creditor.iter().filter(assignable).filter(|c| !skip.contains(&c.id)) {
+        match by_name.get(&normalize(&category.name)) {
+            Some(debtor_id) => {
+                matched.insert(category.id, *debtor_id);
+            }
+            None => unmatched.push(category.clone()),
+        }
+    }
+    (matched, unmatched)
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: CategoryId, name: &str) -> Category {
+        Category {
+            id,
+            name: name.to_string(),
+            is_group: false,
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn debtor_category_falls_back() {
+        let map = CategoryMap {
+            categories: BTreeMap::from([(1, 10)]),
+            fallback: Some(99),
+        };
+        assert_eq!(map.debtor_category(Some(1)), Some(10));
+        assert_eq!(map.debtor_category(Some(2)), Some(99));
+        assert_eq!(map.debtor_category(None), Some(99));
+        assert_eq!(CategoryMap::default().debtor_category(Some(1)), None);
+    }
+
+    #[test]
+    fn suggest_matches_names() {
+        let creditor = vec![
+            category(1, "Groceries"),
+            category(2, "Dining Out "),
+            category(3, "Hobbies"),
+            category(4, "Proxy"),
+            Category {
+                is_group: true,
+                ..category(5, "Food")
+            },
+        ];
+        let debtor = vec![
+            category(10, "groceries"),
+            category(11, "dining out"),
+            Category {
+                archived: true,
+                ..category(12, "Hobbies")
+            },
+            category(13, "Food"),
+        ];
+
+        let (matched, unmatched) = suggest(&creditor, &debtor, &[4]);
+        assert_eq!(matched, BTreeMap::from([(1, 10), (2, 11)]));
+        assert_eq!(unmatched, vec![category(3, "Hobbies")]);
+    }
+
+    #[test]
+    fn map_reads_string_keys() {
+        let map: CategoryMap =
+            serde_json::from_str(r#"{"categories": {"41": 7}, "fallback": 8}"#).unwrap();
+        assert_eq!(map.debtor_category(Some(41)), Some(7));
+    }
+}
@@ -1,8 +1,8 @@
 use equailizer::date_helpers;
-#[cfg(debug_assertions)]
 use equailizer::lunch_money::model::transaction::TransactionId;
 use chrono::NaiveDate;
-use clap::{ArgAction, Args, Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "equailizer")]
@@ -21,6 +21,40 @@ pub struct StartArgs {
     pub start_days_ago: Option<u16>,
 }
 
+/// How to print a command's run report to stdout.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Table,
+}
+
+/// File format for `export`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Ofx,
+    Ledger,
+    Beancount,
+}
+
+/// Which way an `adjust` moves the balance.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AdjustKind {
+    /// The debtor paid some back outside a settlement
+    Payment,
+    /// The creditor let the debtor off some of what they owe
+    Forgiven,
+    /// The debtor owes something that was never batched
+    Charge,
+}
+
+/// Which batches `export` includes by reconciliation.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReconciledStatus {
+    Reconciled,
+    Unreconciled,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     CreateBatch {
@@ -37,6 +71,56 @@ pub enum Commands {
         profile: String,
         #[arg(short, long, action = ArgAction::SetTrue)]
         dry_run: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
+        /// Save what the batch would do to this file instead of doing it; run it with `apply`
+        #[arg(long = "plan", value_name = "file", conflicts_with = "output")]
+        plan: Option<PathBuf>,
+        /// Confirm each transaction on the terminal before anything is changed
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        interactive: bool,
+        /// If any transaction can't be processed, undo every change and save no batch
+        #[arg(long = "all-or-nothing", action = ArgAction::SetTrue)]
+        all_or_nothing: bool,
+    },
+    /// Carry out a plan saved by `create-batch --plan`
+    Apply {
+        #[arg(required = true, value_name = "plan file")]
+        plan: PathBuf,
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+        /// If any transaction can't be processed, undo every change and save no batch
+        #[arg(long = "all-or-nothing", action = ArgAction::SetTrue)]
+        all_or_nothing: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Finish, or with --rollback undo, a create-batch run that stopped partway
+    Resume {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        /// Put back every transaction the run changed instead of finishing it
+        #[arg(long = "rollback", action = ArgAction::SetTrue)]
+        rollback: bool,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Put back splits left undone by a re-split that failed partway
+    Repair {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
     },
     Reconcile {
         #[arg(
@@ -50,18 +134,148 @@ pub enum Commands {
         profile: String,
         #[arg(short, long, action = ArgAction::SetTrue)]
         dry_run: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
     },
     ReconcileAll {
         #[arg(required = true, long = "profile", short = 'p')]
         profile: String,
         #[arg(short, long, action = ArgAction::SetTrue)]
         dry_run: bool,
+        /// Print a report of what was done to stdout; logs go to stderr instead
+        #[arg(long = "output", short = 'o', value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Remind the debtor about unreconciled batches that are overdue
+    Remind {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    /// Run create-batch and reconcile-all on the profile's schedule until stopped
+    Daemon {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    /// Print every batched transaction to stdout in a portable format
+    Export {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(required = true, long = "format", short = 'f', value_enum)]
+        format: ExportFormat,
+        /// Only transactions dated on or after this day
+        #[arg(long = "start-date", short = 's', value_name = "yyyy-mm-dd")]
+        start_date: Option<NaiveDate>,
+        /// Only transactions dated on or before this day
+        #[arg(long = "end-date", short = 'e', value_name = "yyyy-mm-dd")]
+        end_date: Option<NaiveDate>,
+        /// Only batches that are, or aren't yet, reconciled
+        #[arg(long = "status", value_enum)]
+        status: Option<ReconciledStatus>,
+    },
+    /// Print what was shared by month, category and payee, and how long settling took
+    Report {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        /// Only transactions dated on or after this day
+        #[arg(long = "from", value_name = "yyyy-mm-dd")]
+        from: Option<NaiveDate>,
+        /// Only transactions dated on or before this day
+        #[arg(long = "to", value_name = "yyyy-mm-dd")]
+        to: Option<NaiveDate>,
+        #[arg(long = "output", short = 'o', value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Print what the debtor owes, by batch age, with the running ledger
+    Balance {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(long = "output", short = 'o', value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Record money that changed hands outside a batch settlement
+    Adjust {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(value_enum)]
+        kind: AdjustKind,
+        /// A positive amount, e.g. 20.00
+        #[arg(required = true, long = "amount", short = 'm')]
+        amount: String,
+        /// Defaults to today
+        #[arg(long = "date", value_name = "yyyy-mm-dd")]
+        date: Option<NaiveDate>,
+        #[arg(long = "note", short = 'n')]
+        note: Option<String>,
+        /// The batch a payment or forgiveness goes toward; once it's paid in
+        /// full it's closed so it's no longer reconciled or reminded about
+        #[arg(long = "batch-name", short = 'b', value_name = "batch name")]
+        batch_name: Option<String>,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
     },
+    #[command(subcommand)]
+    Rules(RulesSubcommand),
+    #[command(subcommand)]
+    Amortize(AmortizeSubcommand),
+    #[command(subcommand)]
+    Categories(CategoriesSubcommand),
     #[cfg(debug_assertions)]
     #[command(subcommand)]
     Dev(DevSubcommand),
 }
 
+#[derive(Debug, Subcommand)]
+pub enum RulesSubcommand {
+    /// Show which transactions each rule would match, without changing anything
+    Test {
+        #[command(flatten)]
+        start: StartArgs,
+        #[arg(
+            required = false,
+            long = "end-date",
+            short = 'e',
+            value_name = "yyyy-mm-dd"
+        )]
+        end_date: Option<NaiveDate>,
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AmortizeSubcommand {
+    /// Bill a transaction to the debtor in monthly installments
+    Add {
+        #[arg(required = true, long = "identifier", short = 'i')]
+        id: TransactionId,
+        #[arg(required = true, long = "months", short = 'm', value_parser = clap::value_parser!(u32).range(1..))]
+        months: u32,
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    /// Show amortization schedules and how far along they are
+    List {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CategoriesSubcommand {
+    /// Suggest a category map by matching category names across both accounts
+    Suggest {
+        #[arg(required = true, long = "profile", short = 'p')]
+        profile: String,
+    },
+}
+
 #[cfg(debug_assertions)]
 #[derive(Debug, Subcommand)]
 pub enum DevSubcommand {
@@ -90,6 +304,27 @@ pub enum DevSubcommand {
     },
 }
 
+impl Commands {
+    /// The requested report format, for commands that print one.
+    pub fn output(&self) -> Option<OutputFormat> {
+        match self {
+            Commands::CreateBatch { output, .. }
+            | Commands::Apply { output, .. }
+            | Commands::Resume { output, .. }
+            | Commands::Repair { output, .. }
+            | Commands::Reconcile { output, .. }
+            | Commands::ReconcileAll { output, .. } => *output,
+            Commands::Report { output, .. } | Commands::Balance { output, .. } => Some(*output),
+            _ => None,
+        }
+    }
+
+    /// Whether stdout is reserved for what the command prints, so logs go to stderr.
+    pub fn prints_to_stdout(&self) -> bool {
+        self.output().is_some() || matches!(self, Commands::Categories(_) | Commands::Export { .. })
+    }
+}
+
 pub fn start_date_from_args(args: StartArgs) -> NaiveDate {
     match (args.start_date, args.start_days_ago) {
         (Some(date), None) => date,
@@ -0,0 +1,68 @@
+use crate::{
+    amortization::AmortizationSchedule,
+    config::Config,
+    error::{Error, Result},
+    lunch_money::{
+        api::{update_transaction::TransactionUpdateItem, LunchMoney},
+        model::transaction::{TransactionId, TransactionStatus},
+    },
+    persist::Persistence,
+};
+
+/// Start amortizing a transaction over `months` without tagging it. The
+/// transaction moves to the proxy category like an add, and its installments
+/// are billed by later `create-batch` runs as they fall due.
+pub async fn amortize_transaction(
+    txn_id: TransactionId,
+    months: u32,
+    config: &Config,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<AmortizationSchedule> {
+    let not_amortizable = |reason: &str| Err(Error::NotAmortizable(txn_id, reason.to_string()));
+
+    if months == 0 {
+        return not_amortizable("months must be greater than 0");
+    }
+
+    let txn = api.get_transaction(txn_id).await?;
+    if txn.is_pending {
+        return not_amortizable("transaction is pending");
+    }
+    if txn.has_children {
+        return not_amortizable("transaction has been split");
+    }
+    if txn.category_id == Some(config.creditor.proxy_category_id) {
+        return not_amortizable("transaction is already in the proxy category");
+    }
+    if persistence
+        .all_schedules()?
+        .iter()
+        .any(|s| s.transaction_id == txn_id)
+    {
+        return not_amortizable("transaction already has an amortization schedule");
+    }
+
+    api.update_transaction((
+        txn.id,
+        TransactionUpdateItem {
+            payee: None,
+            category_id: Some(config.creditor.proxy_category_id),
+            notes: None,
+            tags: None,
+            status: Some(TransactionStatus::Cleared),
+        },
+    ))
+    .await?;
+
+    let schedule = AmortizationSchedule::new(&txn, months);
+    persistence.save_schedule(&schedule)?;
+
+    tracing::info!(
+        txn_id,
+        schedule_id = %schedule.id,
+        months,
+        "Amortization schedule started"
+    );
+    Ok(schedule)
+}
@@ -0,0 +1,71 @@
+use crate::commands::create_batch::execute_plan;
+use crate::commands::create_batch::plan::BatchPlan;
+use crate::email::BatchNotifier;
+use crate::error::{Error, Result};
+use crate::lunch_money::api::LunchMoney;
+use crate::lunch_money::model::transaction::TransactionId;
+use crate::persist::Persistence;
+use crate::plugin::PluginManager;
+use crate::report::BatchReport;
+
+/// Carry out a plan saved by `create-batch --plan`, exactly as written. Nothing
+/// is changed unless every transaction and installment in the plan is still in
+/// the state it was planned against.
+pub async fn apply_plan(
+    plan: BatchPlan,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<BatchReport> {
+    check_plan(&plan, api, persistence).await?;
+    execute_plan(plan, api, persistence, notifier, plugins).await
+}
+
+/// Check that the plan still matches Lunch Money and the saved schedules.
+pub async fn check_plan(
+    plan: &BatchPlan,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<()> {
+    let mut changed: Vec<TransactionId> = vec![];
+
+    for snapshot in plan.snapshots() {
+        match api.get_transaction(snapshot.id).await {
+            Ok(current) if current == *snapshot => {}
+            Ok(_) => {
+                tracing::warn!(txn_id = snapshot.id, "Transaction changed since the plan was made");
+                changed.push(snapshot.id);
+            }
+            Err(e) => {
+                tracing::warn!(txn_id = snapshot.id, error = %e, "Could not fetch planned transaction");
+                changed.push(snapshot.id);
+            }
+        }
+    }
+
+    let schedules = persistence.all_schedules()?;
+    for installment in &plan.installments {
+        let still_due = schedules
+            .iter()
+            .find(|s| s.id == installment.schedule_id)
+            .is_some_and(|s| {
+                s.due_installments(plan.end_date)
+                    .iter()
+                    .any(|i| i.number == installment.number)
+            });
+        if !still_due {
+            tracing::warn!(
+                schedule_id = %installment.schedule_id,
+                number = installment.number,
+                "Installment is no longer due"
+            );
+            changed.push(installment.transaction_id);
+        }
+    }
+
+    if !changed.is_empty() {
+        return Err(Error::StalePlan(changed));
+    }
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    money::{Currency, Money},
+    persist::{Adjustment, AdjustmentKind, Batch, Persistence},
+    report::{AgeBucket, BalanceReport, LedgerEntry, OpenBatch},
+};
+use chrono::NaiveDate;
+
+/// What the debtor owes as of `today`: every batch billed, less the ones
+/// settled, plus the adjustment history. Only amounts in the profile's
+/// currency are counted.
+pub fn balance(
+    today: NaiveDate,
+    config: &Config,
+    persistence: &impl Persistence,
+) -> Result<BalanceReport> {
+    let currency = config.creditor.currency;
+
+    // Everything that moved the balance, oldest first. The sort is stable, so
+    // entries on the same day stay in the order they're added here.
+    let mut changes: Vec<(Option<NaiveDate>, String, Money)> = vec![];
+    let mut open_batches: Vec<OpenBatch> = vec![];
+    for batch in persistence.all_batches()? {
+        if batch.amount.currency() != currency {
+            tracing::warn!(batch_id = %batch.id, currency = %batch.amount.currency(), "Leaving out batch in another currency");
+            continue;
+        }
+        changes.push((batch.created_on, format!("batch {}", batch.id), batch.amount));
+        // A batch closed by an adjustment is paid off by that adjustment.
+        if let Some(settlement) = &batch.reconciliation {
+            changes.push((
+                settlement.settled_on.or(batch.created_on),
+                format!("batch {} settled", batch.id),
+                -batch.amount,
+            ));
+        }
+        if batch.is_open() {
+            open_batches.push(OpenBatch {
+                age_days: batch.created_on.map(|d| (today - d).num_days().max(0)),
+                created_on: batch.created_on,
+                batch_id: batch.id,
+                amount: batch.amount,
+            });
+        }
+    }
+    open_batches.sort_by(|a, b| (a.created_on, &a.batch_id).cmp(&(b.created_on, &b.batch_id)));
+
+    for adjustment in persistence.adjustments()? {
+        if adjustment.amount.currency() != currency {
+            tracing::warn!(adjustment_id = %adjustment.id, currency = %adjustment.amount.currency(), "Leaving out adjustment in another currency");
+            continue;
+        }
+        let kind = match &adjustment.batch_id {
+            Some(batch_id) => format!("{} for batch {}", adjustment.kind.as_str(), batch_id),
+            None => adjustment.kind.as_str().to_string(),
+        };
+        let description = match &adjustment.note {
+            Some(note) => format!("{}: {}", kind, note),
+            None => kind,
+        };
+        changes.push((Some(adjustment.date), description, adjustment.signed_amount()));
+    }
+    changes.sort_by_key(|(date, _, _)| *date);
+
+    let mut running = Money::zero(currency);
+    let ledger = changes
+        .into_iter()
+        .map(|(date, description, change)| {
+            running = running + change;
+            LedgerEntry {
+                date,
+                description,
+                change,
+                balance: running,
+            }
+        })
+        .collect();
+
+    Ok(BalanceReport {
+        as_of: today,
+        balance: running,
+        by_age: by_age(&open_batches, currency),
+        open_batches,
+        ledger,
+    })
+}
+
+/// Open batches grouped by how many days old they are. Empty groups are left out.
+fn by_age(open_batches: &[OpenBatch], currency: Currency) -> Vec<AgeBucket> {
+    const BUCKETS: [(&str, i64, i64); 4] = [
+        ("0-30 days", 0, 30),
+        ("31-60 days", 31, 60),
+        ("61-90 days", 61, 90),
+        ("over 90 days", 91, i64::MAX),
+    ];
+    let bucket = |label: &str, batches: Vec<&OpenBatch>| AgeBucket {
+        label: label.to_string(),
+        count: batches.len(),
+        total: batches
+            .iter()
+            .fold(Money::zero(currency), |total, b| total + b.amount),
+    };
+
+    let mut buckets: Vec<AgeBucket> = BUCKETS
+        .iter()
+        .map(|(label, min, max)| {
+            let batches = open_batches
+                .iter()
+                .filter(|b| b.age_days.is_some_and(|age| age >= *min && age <= *max))
+                .collect();
+            bucket(label, batches)
+        })
+        .collect();
+    buckets.push(bucket(
+        "unknown",
+        open_batches.iter().filter(|b| b.age_days.is_none()).collect(),
+    ));
+    buckets.retain(|b| b.count > 0);
+    buckets
+}
+
+/// Add an adjustment to the history. `amount` must be positive and in the
+/// profile's currency. A payment or forgiveness with a `batch_id` counts
+/// toward that batch; once the batch is paid in full it's closed, so it's no
+/// longer reconciled or reminded about.
+pub fn record_adjustment(
+    kind: AdjustmentKind,
+    amount: Money,
+    date: NaiveDate,
+    note: Option<String>,
+    batch_id: Option<String>,
+    config: &Config,
+    persistence: &impl Persistence,
+) -> Result<Adjustment> {
+    if amount.currency() != config.creditor.currency {
+        return Err(Error::InvalidAdjustment(format!(
+            "amount is in {}, but the profile uses {}",
+            amount.currency(),
+            config.creditor.currency
+        )));
+    }
+    if amount.value() <= rust_decimal::Decimal::ZERO {
+        return Err(Error::InvalidAdjustment("amount must be greater than 0".to_string()));
+    }
+
+    let batch = match &batch_id {
+        Some(batch_id) => Some(closable_batch(kind, batch_id, persistence)?),
+        None => None,
+    };
+    let paid_in_full = match &batch {
+        Some(batch) => {
+            let owed = owed_on_batch(batch, persistence)?;
+            if amount.currency() != owed.currency() || amount > owed {
+                return Err(Error::InvalidAdjustment(format!(
+                    "{amount} is more than the {owed} still owed on batch '{}'",
+                    batch.id
+                )));
+            }
+            amount == owed
+        }
+        None => false,
+    };
+
+    let adjustment = Adjustment {
+        id: uuid::Uuid::new_v4().to_string(),
+        date,
+        kind,
+        amount,
+        note,
+        batch_id,
+    };
+    persistence.append_adjustment(&adjustment)?;
+    tracing::info!(adjustment_id = %adjustment.id, kind = kind.as_str(), amount = %amount, "Adjustment recorded");
+
+    if let Some(batch) = batch.filter(|_| paid_in_full) {
+        tracing::info!(batch_id = %batch.id, adjustment_id = %adjustment.id, "Closing batch");
+        persistence.save_batch(&Batch {
+            closed_by: Some(adjustment.id.clone()),
+            ..batch
+        })?;
+    }
+    Ok(adjustment)
+}
+
+/// The batch an adjustment of `kind` would close, if it can be closed.
+fn closable_batch(
+    kind: AdjustmentKind,
+    batch_id: &str,
+    persistence: &impl Persistence,
+) -> Result<Batch> {
+    if kind == AdjustmentKind::Charge {
+        return Err(Error::InvalidAdjustment(
+            "only a payment or forgiveness can close a batch".to_string(),
+        ));
+    }
+    let batch = persistence.get_batch(batch_id)?;
+    if batch.reconciliation.is_some() {
+        return Err(Error::BatchAlreadyReconciled(batch.id));
+    }
+    if let Some(adjustment_id) = batch.closed_by {
+        return Err(Error::BatchClosed(batch.id, adjustment_id));
+    }
+    Ok(batch)
+}
+
+/// What's left of `batch` after the payments and forgiveness already recorded
+/// against it.
+fn owed_on_batch(batch: &Batch, persistence: &impl Persistence) -> Result<Money> {
+    Ok(persistence
+        .adjustments()?
+        .into_iter()
+        .filter(|a| a.batch_id.as_deref() == Some(batch.id.as_str()))
+        .filter(|a| a.amount.currency() == batch.amount.currency())
+        .fold(batch.amount, |owed, a| owed - a.amount))
+}
@@ -0,0 +1,49 @@
+use crate::{
+    category_map::{self, CategoryMap},
+    config::Config,
+    error::Result,
+    lunch_money::{api::LunchMoney, model::category::Category},
+};
+
+pub struct CategorySuggestion {
+    /// The profile's map with every new name match added.
+    pub category_map: CategoryMap,
+    /// Creditor categories with no mapping and no debtor category of the same name.
+    pub unmatched: Vec<Category>,
+}
+
+/// Suggest a category map by matching category names across the creditor's
+/// and debtor's accounts. Mappings already in the profile's config are kept,
+/// and the proxy category is never mapped.
+pub async fn suggest_category_map(
+    config: &Config,
+    creditor_api: &(impl LunchMoney + Sync),
+    debtor_api: &(impl LunchMoney + Sync),
+) -> Result<CategorySuggestion> {
+    let creditor = creditor_api.get_categories().await?;
+    let debtor = debtor_api.get_categories().await?;
+
+    let mut category_map = config.category_map.clone();
+    let skip: Vec<u32> = category_map
+        .categories
+        .keys()
+        .copied()
+        .chain(std::iter::once(config.creditor.proxy_category_id))
+        .collect();
+    let (matched, unmatched) = category_map::suggest(&creditor, &debtor, &skip);
+
+    tracing::info!(
+        creditor_categories = creditor.len(),
+        debtor_categories = debtor.len(),
+        already_mapped = category_map.categories.len(),
+        matched = matched.len(),
+        unmatched = unmatched.len(),
+        "Suggested category map"
+    );
+    category_map.categories.extend(matched);
+
+    Ok(CategorySuggestion {
+        category_map,
+        unmatched,
+    })
+}
@@ -1,23 +1,35 @@
 mod create_updates;
+pub mod journal;
+pub mod plan;
 mod process_tags;
 
-use crate::commands::create_batch::create_updates::{create_resplit_items, create_updates};
+use crate::amortization::AmortizationSchedule;
+use crate::commands::create_batch::create_updates::{
+    create_amortize_updates, create_resplit_items, create_updates,
+};
+use crate::commands::create_batch::journal::{Journal, Step, StepResult, StepState};
+use crate::commands::create_batch::plan::{
+    BatchPlan, PlannedAmortization, PlannedResplit, PlannedSplit, PlannedUpdate,
+};
 use crate::commands::create_batch::process_tags::process_tags;
+use crate::commands::repair::restore_split;
+use crate::commands::resume::rollback_journal;
 use crate::config;
-use crate::email::{BatchNotifier, Txn};
+use crate::date_helpers;
+use crate::email::{BatchNotifier, InstallmentInfo, Txn};
 use crate::error::{Error, Result};
 use crate::issue::Issue;
-use crate::lunch_money::api::update_transaction::{
-    TransactionAndSplitUpdate, TransactionUpdate, TransactionUpdateItem,
-};
+use crate::lunch_money::api::update_transaction::TransactionUpdateItem;
 use crate::lunch_money::api::LunchMoney;
 use crate::lunch_money::model::transaction::{Transaction, TransactionId, TransactionStatus};
-use crate::persist::{Batch, Persistence};
+use crate::money::Money;
+use crate::persist::{Batch, BatchInstallment, Persistence, SplitSnapshot};
 use crate::plugin::PluginManager;
-use crate::usd::USD;
+use crate::report::{BatchReport, BatchedTransaction, Handling};
+use crate::review::Reviewer;
+use crate::rules;
 use chrono::NaiveDate;
-use std::collections::HashMap;
-use uuid::Uuid;
+use std::collections::{BTreeMap, HashMap};
 
 pub async fn create_batch(
     start_date: NaiveDate,
@@ -27,8 +39,24 @@ pub async fn create_batch(
     persistence: &(impl Persistence + Sync),
     notifier: &(impl BatchNotifier + Sync),
     plugins: &mut PluginManager,
-) -> Result<()> {
-    let span = tracing::info_span!("Create Batch");
+) -> Result<BatchReport> {
+    let plan = plan_batch(start_date, end_date, config, api, persistence, None).await?;
+    execute_plan(plan, api, persistence, notifier, plugins).await
+}
+
+/// Work out everything a batch run would do without changing anything: which
+/// transactions get batched, the exact updates and split amounts for each, and
+/// which amortization installments fall due. With a reviewer, each transaction
+/// found is confirmed with it first.
+pub async fn plan_batch(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    config: &config::Config,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    reviewer: Option<&mut dyn Reviewer>,
+) -> Result<BatchPlan> {
+    let span = tracing::info_span!("Plan Batch");
     let _enter = span.enter();
 
     if start_date.cmp(&end_date) == std::cmp::Ordering::Greater {
@@ -41,79 +69,249 @@ pub async fn create_batch(
         "Fetching transactions"
     );
 
-    // Get all transactions in provided date range.
-    let txns = api.get_transactions(start_date, end_date).await?;
+    // Get all transactions in provided date range, plus any tagged
+    // transactions that were still pending on an earlier run.
+    let mut txns = api.get_transactions(start_date, end_date).await?;
+    txns.append(&mut carried_pending_transactions(&txns, api, persistence).await?);
     let all_txns = txns.clone(); // Keep for sibling lookup during resplits
 
+    // Match profile rules first, since tag processing consumes the transactions.
+    let rule_matches = rules::apply_rules(
+        &txns,
+        &config.rules,
+        &config.tags,
+        config.creditor.proxy_category_id,
+    );
+
     // Process tags on the retrieved transactions to see what should be
     // added to the batch.
-    let mut processed = process_tags(
-        txns,
-        &config::TAG_BATCH_ADD.to_string(),
-        &config::TAG_BATCH_SPLIT.to_string(),
-    );
+    let mut processed = process_tags(txns, &config.tags);
+    processed.add_rule_matches(rule_matches);
+    processed.retain_currency(config.creditor.currency);
+    let batched = batched_transaction_ids(persistence)?;
+    processed.retain_unbatched(&batched, config.creditor.proxy_category_id, &config.tags);
+    processed.forget_batched_parents(&batched, &all_txns, &config.tags);
+    if let Some(reviewer) = reviewer {
+        processed.review(reviewer);
+    }
 
-    let add_count = processed.txns_to_add.len();
-    let split_count = processed.txns_to_split.len();
-    let resplit_count = processed.txns_to_resplit.len();
+    // Installments of existing amortization schedules falling due in this batch.
+    let installments: Vec<BatchInstallment> = persistence
+        .all_schedules()?
+        .iter()
+        .filter(|s| s.total.currency() == config.creditor.currency)
+        .flat_map(|s| due_batch_installments(s, end_date))
+        .collect();
 
-    // Check that we found at least 1 valid transaction.
-    if add_count + split_count + resplit_count == 0 {
-        tracing::info!("No tagged transactions found — nothing to batch");
-        return Ok(());
-    }
+    let issues: Vec<String> = processed.issues.drain(..).map(|i| i.to_string()).collect();
+    let pending_ids = std::mem::take(&mut processed.pending_ids);
+
+    // Extract resplit transactions before create_updates consumes the rest.
+    let txns_to_resplit: Vec<Transaction> = std::mem::take(&mut processed.txns_to_resplit);
+    let txns_to_amortize = std::mem::take(&mut processed.txns_to_amortize);
+
+    // Create actionable updates for the processed results.
+    let (add_updates, split_updates) = create_updates(processed, config.creditor.proxy_category_id, &config.tags);
+
+    let plan = BatchPlan {
+        start_date,
+        end_date,
+        currency: config.creditor.currency,
+        adds: add_updates
+            .into_iter()
+            .map(|(transaction, (_, update))| PlannedUpdate { transaction, update })
+            .collect(),
+        splits: split_updates
+            .into_iter()
+            .map(|(transaction, (_, update, splits))| PlannedSplit {
+                transaction,
+                update,
+                splits,
+            })
+            .collect(),
+        resplits: plan_resplits(txns_to_resplit, &all_txns, config.creditor.proxy_category_id),
+        amortizations: create_amortize_updates(
+            txns_to_amortize,
+            config.creditor.proxy_category_id,
+            &config.tags,
+        )
+        .into_iter()
+        .map(|(transaction, (_, update), months)| PlannedAmortization {
+            transaction,
+            update,
+            months,
+        })
+        .collect(),
+        installments,
+        pending_ids,
+        issues,
+        all_or_nothing: false,
+        batch_marker: config.batch_marker.clone(),
+    };
 
     tracing::info!(
-        to_add = add_count,
-        to_split = split_count,
-        to_resplit = resplit_count,
-        "Tagged transactions found"
+        to_add = plan.adds.len(),
+        to_split = plan.splits.len(),
+        to_resplit = plan.resplits.iter().map(|r| r.children.len()).sum::<usize>(),
+        to_amortize = plan.amortizations.len(),
+        installments_due = plan.installments.len(),
+        "Batch planned"
     );
 
-    // Capture tag-processing issues before consuming the processed data.
-    let mut issues: Vec<Issue> = processed.issues.drain(..).collect();
-    for issue in &issues {
+    Ok(plan)
+}
+
+/// Carry out a batch plan: update and split the planned transactions, bill
+/// due installments, save the batch and send notifications. Refuses to start
+/// while an earlier run is unfinished, since its batch isn't saved yet.
+pub async fn execute_plan(
+    plan: BatchPlan,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<BatchReport> {
+    if let Some(journal) = persistence.unfinished_journal()? {
+        return Err(Error::UnfinishedRun(journal.batch_id));
+    }
+    run_journal(Journal::new(plan), api, persistence, notifier, plugins).await
+}
+
+/// Run the steps of a journaled plan that haven't finished yet, then save the
+/// batch from the results of all of them. Each step is journaled as started
+/// before its API calls and as finished after, and the journal is removed once
+/// the batch is saved and sent.
+pub async fn run_journal(
+    mut journal: Journal,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<BatchReport> {
+    let span = tracing::info_span!("Create Batch");
+    let _enter = span.enter();
+
+    let start_date = journal.plan.start_date;
+    let end_date = journal.plan.end_date;
+    let currency = journal.plan.currency;
+    let planned_issues = journal.plan.issues.clone();
+
+    // Tag-processing issues found while planning.
+    for issue in &planned_issues {
         tracing::warn!("{}", issue);
     }
 
-    // Extract resplit transactions before create_updates consumes the rest.
-    let txns_to_resplit: Vec<Transaction> = std::mem::take(&mut processed.txns_to_resplit);
+    // Check that we found at least 1 valid transaction or due installment.
+    if journal.plan.is_empty() {
+        tracing::info!("No tagged or rule-matched transactions found — nothing to batch");
+        persistence.save_pending_transaction_ids(&journal.plan.pending_ids)?;
+        return Ok(BatchReport {
+            batch_id: None,
+            start_date,
+            end_date,
+            total: None,
+            transactions: vec![],
+            issues: planned_issues,
+            rollback: None,
+        });
+    }
 
-    // Create actionable updates for the processed results.
-    let (add_updates, split_updates) = create_updates(processed, config.creditor.proxy_category_id);
+    // Nothing is changed until the journal is on disk.
+    persistence.save_journal(&journal)?;
 
-    // Prepare final output data.
-    let mut batched_txn_info: Vec<(TransactionId, Txn)> = vec![];
+    let mut results: Vec<(Step, StepResult)> = vec![];
+    for step in journal.planned_steps() {
+        let result = match journal.state(step) {
+            Some(StepState::Finished(result)) => {
+                results.push((step, (**result).clone()));
+                continue;
+            }
+            Some(StepState::Started) => {
+                tracing::info!(?step, "Recovering step interrupted on an earlier run");
+                recover_step(&journal.plan, step, api, persistence).await
+            }
+            None => {
+                journal.record(step, StepState::Started);
+                persistence.save_journal(&journal)?;
+                execute_step(&journal.plan, step, api, persistence).await
+            }
+        };
+        journal.record(step, StepState::Finished(Box::new(result.clone())));
+        persistence.save_journal(&journal)?;
+        results.push((step, result));
+    }
 
-    // Execute adds and append results to output.
-    {
-        let (mut added_ids_and_email_txns, mut added_issues) =
-            execute_adds(add_updates, api).await;
-        batched_txn_info.append(&mut added_ids_and_email_txns);
-        issues.append(&mut added_issues);
+    if journal.plan.all_or_nothing && results.iter().any(|(_, r)| !r.issues.is_empty()) {
+        return roll_back_run(&journal, results, api, persistence).await;
     }
 
-    // Execute splits and append results to output.
-    {
-        let (mut added_ids_and_email_txns, mut added_issues) =
-            execute_splits(split_updates, api).await;
-        batched_txn_info.append(&mut added_ids_and_email_txns);
-        issues.append(&mut added_issues);
+    let mut issues: Vec<Issue> = vec![];
+    let mut categories = original_categories(&journal.plan, &results);
+
+    // Prepare final output data.
+    let mut batched_txn_info: Vec<(TransactionId, Txn)> = vec![];
+    let mut split_ids: Vec<TransactionId> = vec![];
+    let mut handling: Vec<Handling> = vec![];
+    let mut new_schedules: Vec<AmortizationSchedule> = vec![];
+
+    for (step, mut result) in results {
+        let how = match step {
+            Step::Add(_) => Handling::Added,
+            Step::Split(_) => Handling::Split,
+            Step::Resplit(_) => Handling::Resplit,
+            Step::Amortize(_) => {
+                new_schedules.extend(result.schedule.take());
+                issues.append(&mut result.issues);
+                continue;
+            }
+        };
+        handling.extend(result.batched.iter().map(|_| how));
+        batched_txn_info.append(&mut result.batched);
+        split_ids.append(&mut result.split_ids);
+        issues.append(&mut result.issues);
     }
 
-    // Execute resplits: re-split parent transactions to split tagged children.
-    {
-        let (mut added_ids_and_email_txns, mut added_issues) =
-            execute_resplits(txns_to_resplit, &all_txns, config.creditor.proxy_category_id, api)
-                .await;
-        batched_txn_info.append(&mut added_ids_and_email_txns);
-        issues.append(&mut added_issues);
+    // Amortization schedules with installments falling due in this batch.
+    let planned_installments = journal.plan.installments.clone();
+    let mut schedules: Vec<AmortizationSchedule> = persistence
+        .all_schedules()?
+        .into_iter()
+        .filter(|s| planned_installments.iter().any(|i| i.schedule_id == s.id))
+        .collect();
+    let mut installments = planned_installments;
+
+    // New schedules from amortized transactions. The first installment is due right away.
+    for schedule in new_schedules {
+        installments.extend(due_batch_installments(&schedule, end_date));
+        schedules.push(schedule);
     }
 
+    // Bill every installment that is due by the end of this batch's range.
+    let installments: Vec<(BatchInstallment, Txn)> = installments
+        .into_iter()
+        .filter_map(|i| {
+            let schedule = schedules.iter().find(|s| s.id == i.schedule_id)?;
+            if let Some(category) = schedule.category_id {
+                categories.insert(i.transaction_id, category);
+            }
+            let txn = Txn {
+                payee: schedule.payee.clone(),
+                amount: i.amount,
+                date: i.due,
+                notes: schedule.notes.clone(),
+                installment: Some(InstallmentInfo {
+                    number: i.number,
+                    count: i.count,
+                }),
+            };
+            Some((i, txn))
+        })
+        .collect();
+
     // Scoop up all the data from the batched transactions into the relevant formats for output
-    let (batched_ids, email_txns, total_amount): (Vec<TransactionId>, Vec<Txn>, USD) =
+    let (batched_ids, email_txns, total_amount): (Vec<TransactionId>, Vec<Txn>, Money) =
         batched_txn_info.into_iter().fold(
-            (vec![], vec![], USD::new_from_cents(0)),
+            (vec![], vec![], Money::zero(currency)),
             |(mut ids, mut txns, amt), x| {
                 let tot = amt + x.1.amount;
                 ids.push(x.0);
@@ -121,23 +319,82 @@ pub async fn create_batch(
                 (ids, txns, tot)
             },
         );
+    let (batch_installments, email_txns, total_amount): (Vec<BatchInstallment>, Vec<Txn>, Money) =
+        installments.into_iter().fold(
+            (vec![], email_txns, total_amount),
+            |(mut installments, mut txns, amt), x| {
+                let tot = amt + x.1.amount;
+                installments.push(x.0);
+                txns.push(x.1);
+                (installments, txns, tot)
+            },
+        );
 
-    // Create batch id and save to local data. The same id must flow into the
+    // Save the batch under the journal's id. The same id must flow into the
     // notification and plugins — it's what `reconcile --batch-name` looks up.
-    let batch_id = Uuid::new_v4().to_string();
+    let batch_id = journal.batch_id.clone();
     let batch: Batch = Batch {
         id: batch_id.clone(),
         amount: total_amount,
         transaction_ids: batched_ids.clone(),
         reconciliation: None,
+        installments: batch_installments,
+        categories,
+        created_on: Some(date_helpers::now_date_naive_eastern()),
+        reminders: vec![],
+        closed_by: None,
+        split_ids,
     };
     persistence.save_batch(&batch)?;
 
+    // Only now replace the remembered pending transactions: a run that's rolled
+    // back before this point keeps carrying forward the ones it started with.
+    persistence.save_pending_transaction_ids(&journal.plan.pending_ids)?;
+
+    // Mark the batched transactions, so the batch can be found from Lunch Money.
+    if let Some(marker) = &journal.plan.batch_marker {
+        for &id in &batched_ids {
+            if let Err(e) = marker.mark(id, &batch_id, api).await {
+                tracing::warn!(txn_id = id, error = %e, "Failed to mark transaction with its batch");
+                issues.push(Issue::TransactionUpdateError(id, e.to_string()));
+            }
+        }
+    }
+
+    // Record the billed installments. New schedules are saved even when nothing
+    // was billed from them, so their later installments aren't lost.
+    for schedule in &mut schedules {
+        let schedule_id = schedule.id.clone();
+        for installment in batch.installments.iter().filter(|i| i.schedule_id == schedule_id) {
+            schedule.mark_billed(installment.number, &batch_id);
+        }
+        persistence.save_schedule(schedule)?;
+    }
+
     // Send the batch notification.
-    let email_warnings: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
-    notifier
+    for issue in &issues {
+        tracing::warn!("{}", issue);
+    }
+    let email_warnings: Vec<String> = planned_issues
+        .into_iter()
+        .chain(issues.iter().map(|i| i.to_string()))
+        .collect();
+    // The batch is already saved, so a notifier that fails is reported rather
+    // than failing the run.
+    let mut report_issues = email_warnings.clone();
+    if let Err(e) = notifier
         .send_batch_notification(&batch_id, &total_amount, &email_txns, email_warnings.clone())
-        .await?;
+        .await
+    {
+        let failures = match e {
+            Error::NotifiersFailed(failures) => failures,
+            other => vec![other.to_string()],
+        };
+        for failure in failures {
+            tracing::warn!(batch_id, error = %failure, "Batch notification failed");
+            report_issues.push(format!("Batch notification failed: {}", failure));
+        }
+    }
 
     // Dispatch to plugins.
     plugins
@@ -149,211 +406,601 @@ pub async fn create_batch(
         ))
         .await;
 
+    persistence.remove_journal(&batch_id)?;
+
     tracing::info!(
         batch_id,
         amount = %total_amount,
         transaction_count = batched_ids.len(),
-        warnings = issues.len(),
+        installment_count = batch.installments.len(),
+        warnings = email_warnings.len(),
         "Batch created"
     );
-    Ok(())
+
+    // Batched transactions come first in the email list, then installments.
+    let handling = handling.into_iter().chain(
+        batch
+            .installments
+            .iter()
+            .map(|i| Handling::Installment { number: i.number, count: i.count }),
+    );
+    let ids = batched_ids
+        .iter()
+        .chain(batch.installments.iter().map(|i| &i.transaction_id));
+    let transactions = email_txns
+        .iter()
+        .zip(ids)
+        .zip(handling)
+        .map(|((txn, id), handling)| BatchedTransaction {
+            id: *id,
+            date: txn.date,
+            payee: txn.payee.clone(),
+            amount: txn.amount,
+            handling,
+        })
+        .collect();
+
+    Ok(BatchReport {
+        batch_id: Some(batch_id),
+        start_date,
+        end_date,
+        total: Some(total_amount),
+        transactions,
+        issues: report_issues,
+        rollback: None,
+    })
 }
 
-// Execute adding these transactions to the batch with their associated pre-prepared update.
-// Return info about the added transactions and any issues encountered during the operation.
-async fn execute_adds(
-    txns_and_updates: Vec<(crate::lunch_money::model::transaction::Transaction, TransactionUpdate)>,
+/// The category each batched transaction had when it was planned, keyed by the
+/// id it was batched under (the debtor's half, for splits).
+fn original_categories(
+    plan: &BatchPlan,
+    results: &[(Step, StepResult)],
+) -> BTreeMap<TransactionId, u32> {
+    let mut categories = BTreeMap::new();
+    for (step, result) in results {
+        let originals: Vec<Option<u32>> = match *step {
+            Step::Add(id) => vec![planned_add(plan, id).transaction.category_id],
+            Step::Split(id) => vec![planned_split(plan, id).transaction.category_id],
+            Step::Resplit(parent_id) => planned_resplit(plan, parent_id)
+                .children
+                .iter()
+                .map(|c| c.category_id)
+                .collect(),
+            Step::Amortize(_) => continue,
+        };
+        for ((id, _), category) in result.batched.iter().zip(originals) {
+            if let Some(category) = category {
+                categories.insert(*id, category);
+            }
+        }
+    }
+    categories
+}
+
+/// Undo an all-or-nothing run after a failure: revert every change it made,
+/// and save and send no batch. The report lists what failed.
+async fn roll_back_run(
+    journal: &Journal,
+    results: Vec<(Step, StepResult)>,
     api: &(impl LunchMoney + Sync),
-) -> (Vec<(TransactionId, Txn)>, Vec<Issue>) {
-    let mut batched_txn_info: Vec<(TransactionId, Txn)> = vec![];
-    let mut issues: Vec<Issue> = vec![];
+    persistence: &(impl Persistence + Sync),
+) -> Result<BatchReport> {
+    let failures: Vec<String> = results
+        .into_iter()
+        .flat_map(|(_, r)| r.issues)
+        .map(|i| i.to_string())
+        .collect();
+    for failure in &failures {
+        tracing::warn!("{}", failure);
+    }
+    tracing::warn!(
+        failed = failures.len(),
+        "All-or-nothing run had failures — rolling back every change"
+    );
 
-    for (txn, update) in txns_and_updates {
-        let result = api.update_transaction(update).await;
-        match result {
-            Ok(_) => {
-                batched_txn_info.push((
-                    txn.id,
-                    Txn {
-                        payee: txn.payee,
-                        amount: txn.amount,
-                        date: txn.date,
-                        notes: txn.notes,
-                    },
-                ));
-            }
+    let rollback = rollback_journal(journal, api, persistence).await?;
+    if !rollback.errors.is_empty() {
+        tracing::error!(
+            failed = rollback.errors.len(),
+            "Some changes couldn't be reverted; run `equailizer resume --rollback` to retry"
+        );
+    }
+
+    Ok(BatchReport {
+        batch_id: None,
+        start_date: journal.plan.start_date,
+        end_date: journal.plan.end_date,
+        total: None,
+        transactions: vec![],
+        issues: journal.plan.issues.iter().cloned().chain(failures).collect(),
+        rollback: Some(rollback),
+    })
+}
+
+/// The installments of `schedule` that are due by `as_of`, as batch line items.
+fn due_batch_installments(schedule: &AmortizationSchedule, as_of: NaiveDate) -> Vec<BatchInstallment> {
+    schedule
+        .due_installments(as_of)
+        .into_iter()
+        .map(|i| BatchInstallment {
+            schedule_id: schedule.id.clone(),
+            transaction_id: schedule.transaction_id,
+            number: i.number,
+            count: schedule.count(),
+            due: i.due,
+            amount: i.amount,
+        })
+        .collect()
+}
+
+/// Every transaction id already billed in a saved batch, including amortized
+/// transactions with an installment billed and the creditor's halves of the
+/// batch's splits, mapped to its batch id.
+fn batched_transaction_ids(
+    persistence: &(impl Persistence + Sync),
+) -> Result<HashMap<TransactionId, String>> {
+    let mut ids = HashMap::new();
+    for batch in persistence.all_batches()? {
+        let installment_ids = batch.installments.iter().map(|i| i.transaction_id);
+        let billed = batch.transaction_ids.iter().chain(&batch.split_ids).copied();
+        for id in billed.chain(installment_ids) {
+            ids.insert(id, batch.id.clone());
+        }
+    }
+    Ok(ids)
+}
+
+/// Fetch the tagged transactions remembered as pending on earlier runs that
+/// aren't already in `txns`. Ones that no longer exist are dropped.
+async fn carried_pending_transactions(
+    txns: &[Transaction],
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<Vec<Transaction>> {
+    let mut carried = vec![];
+    for id in persistence.pending_transaction_ids()? {
+        if txns.iter().any(|t| t.id == id) {
+            continue;
+        }
+        match api.get_transaction(id).await {
+            Ok(txn) => carried.push(txn),
             Err(e) => {
-                tracing::warn!(txn_id = txn.id, error = %e, "Failed to update transaction");
-                issues.push(Issue::TransactionUpdateError(txn.id, e.to_string()));
+                tracing::warn!(txn_id = id, error = %e, "Could not fetch previously pending transaction; forgetting it");
             }
         }
     }
+    if !carried.is_empty() {
+        tracing::info!(count = carried.len(), "Carrying forward previously pending transactions");
+    }
+    Ok(carried)
+}
 
-    (batched_txn_info, issues)
+/// Carry out one step of `plan`.
+async fn execute_step(
+    plan: &BatchPlan,
+    step: Step,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> StepResult {
+    match step {
+        Step::Add(id) => execute_add(planned_add(plan, id), api).await,
+        Step::Split(id) => execute_split(planned_split(plan, id), api).await,
+        Step::Resplit(id) => execute_resplit(planned_resplit(plan, id), api, persistence).await,
+        Step::Amortize(id) => execute_amortization(planned_amortization(plan, id), api).await,
+    }
 }
 
-// Execute splitting and adding these transactions to the batch with their associated pre-prepared update.
-// Returns info about the transactions added to the batch - i.e. after splitting,
-// return the debtor's split txn info
-async fn execute_splits(
-    txns_and_updates: Vec<(
-        crate::lunch_money::model::transaction::Transaction,
-        TransactionAndSplitUpdate,
-    )>,
+/// Finish a step that an earlier run started but never recorded finishing, so
+/// its API calls may or may not have gone through. Looks at the transaction as
+/// it is now to decide what's left to do.
+async fn recover_step(
+    plan: &BatchPlan,
+    step: Step,
     api: &(impl LunchMoney + Sync),
-) -> (Vec<(TransactionId, Txn)>, Vec<Issue>) {
-    let mut batched_txn_info: Vec<(TransactionId, Txn)> = vec![];
-    let mut issues: Vec<Issue> = vec![];
+    persistence: &(impl Persistence + Sync),
+) -> StepResult {
+    match step {
+        // Updates set fixed values, so sending one again is harmless.
+        Step::Add(_) | Step::Amortize(_) => execute_step(plan, step, api, persistence).await,
+        Step::Split(id) => {
+            let planned = planned_split(plan, id);
+            let txn = match api.get_transaction(id).await {
+                Ok(txn) => txn,
+                Err(e) => return StepResult::failed(vec![Issue::TransactionUpdateError(id, e.to_string())]),
+            };
+            if !txn.has_children {
+                return execute_split(planned, api).await;
+            }
 
-    for (txn, update) in txns_and_updates {
-        // Grab amount out of update before consuming it during execution
-        let split_amount = update
-            .2
-            .get(1)
-            .expect("split update contained fewer than 2 split items")
-            .amount;
-
-        let result = api.update_transaction_and_split(update).await;
-
-        match result {
-            Ok(split_response) => {
-                match split_response.split_ids.get(1).ok_or("no item in position 1 of split ids in transaction update response - expected debtor proxy split id") {
-                    Ok(batched_id) => {
-                        batched_txn_info.push((
-                            *batched_id,
-                            Txn {
-                                payee: txn.payee,
-                                amount: split_amount,
-                                date: txn.date,
-                                notes: txn.notes
-                            },
-                        ));
-                    }
-                    Err(e) => {
-                        tracing::warn!(txn_id = txn.id, error = %e, "Split response missing expected ID");
-                        issues.push(Issue::TransactionUpdateError(txn.id, e.to_string()));
-                    }
+            // The split went through; find the debtor's half.
+            let debtor_split = planned
+                .splits
+                .get(1)
+                .expect("split update contained fewer than 2 split items");
+            let children = match split_children(&txn, api).await {
+                Ok(children) => children,
+                Err(e) => return StepResult::failed(vec![Issue::TransactionUpdateError(id, e.to_string())]),
+            };
+            match children
+                .iter()
+                .find(|c| c.category_id == debtor_split.category_id && c.amount == debtor_split.amount)
+            {
+                Some(debtor_child) => StepResult {
+                    batched: vec![(debtor_child.id, batched_txn(&planned.transaction, debtor_split.amount))],
+                    split_ids: children.iter().map(|c| c.id).filter(|&id| id != debtor_child.id).collect(),
+                    ..StepResult::default()
+                },
+                None => StepResult::failed(vec![Issue::TransactionUpdateError(
+                    id,
+                    "split went through on an interrupted run, but the debtor's half couldn't be found".to_string(),
+                )]),
+            }
+        }
+        Step::Resplit(parent_id) => {
+            let planned = planned_resplit(plan, parent_id);
+            let failed = |msg: String| {
+                StepResult::failed(
+                    planned
+                        .children
+                        .iter()
+                        .map(|c| Issue::TransactionUpdateError(c.id, msg.clone()))
+                        .collect(),
+                )
+            };
+            let parent = match api.get_transaction(parent_id).await {
+                Ok(parent) => parent,
+                Err(e) => return failed(e.to_string()),
+            };
+            if !parent.has_children {
+                // Unsplit went through, the split didn't.
+                return split_resplit_parent(planned, api, persistence).await;
+            }
+
+            let original_ids: Vec<TransactionId> = planned
+                .children
+                .iter()
+                .chain(&planned.siblings)
+                .map(|t| t.id)
+                .collect();
+            match split_children(&parent, api).await {
+                Ok(children) if children.iter().all(|c| original_ids.contains(&c.id)) => {
+                    execute_resplit(planned, api, persistence).await
                 }
+                Ok(_) => failed(format!(
+                    "re-split of parent {} went through on an interrupted run; check its splits by hand",
+                    parent_id
+                )),
+                Err(e) => failed(e.to_string()),
             }
-            Err(e) => {
-                tracing::warn!(txn_id = txn.id, error = %e, "Failed to split transaction");
-                issues.push(Issue::TransactionUpdateError(txn.id, e.to_string()));
+        }
+    }
+}
+
+fn planned_add(plan: &BatchPlan, id: TransactionId) -> &PlannedUpdate {
+    plan.adds
+        .iter()
+        .find(|a| a.transaction.id == id)
+        .expect("journal step is not in its plan")
+}
+
+fn planned_split(plan: &BatchPlan, id: TransactionId) -> &PlannedSplit {
+    plan.splits
+        .iter()
+        .find(|s| s.transaction.id == id)
+        .expect("journal step is not in its plan")
+}
+
+fn planned_resplit(plan: &BatchPlan, parent_id: TransactionId) -> &PlannedResplit {
+    plan.resplits
+        .iter()
+        .find(|r| r.parent_id == parent_id)
+        .expect("journal step is not in its plan")
+}
+
+fn planned_amortization(plan: &BatchPlan, id: TransactionId) -> &PlannedAmortization {
+    plan.amortizations
+        .iter()
+        .find(|a| a.transaction.id == id)
+        .expect("journal step is not in its plan")
+}
+
+/// The split children of `parent`. Lunch Money has no direct lookup, so this
+/// searches a window around the parent's date.
+pub(crate) async fn split_children(
+    parent: &Transaction,
+    api: &(impl LunchMoney + Sync),
+) -> Result<Vec<Transaction>> {
+    let search_start = parent.date - chrono::Days::new(7);
+    let search_end = parent.date + chrono::Days::new(30);
+    Ok(api
+        .get_transactions(search_start, search_end)
+        .await?
+        .into_iter()
+        .filter(|t| t.parent_id == Some(parent.id))
+        .collect())
+}
+
+/// The email line item for `amount` of `txn`.
+fn batched_txn(txn: &Transaction, amount: Money) -> Txn {
+    Txn {
+        payee: txn.payee.clone(),
+        amount,
+        date: txn.date,
+        notes: txn.notes.clone(),
+        installment: None,
+    }
+}
+
+// Execute adding this transaction to the batch with its associated pre-prepared update.
+async fn execute_add(add: &PlannedUpdate, api: &(impl LunchMoney + Sync)) -> StepResult {
+    let txn = &add.transaction;
+    match api.update_transaction((txn.id, add.update.clone())).await {
+        Ok(_) => StepResult {
+            batched: vec![(txn.id, batched_txn(txn, txn.amount))],
+            ..StepResult::default()
+        },
+        Err(e) => {
+            tracing::warn!(txn_id = txn.id, error = %e, "Failed to update transaction");
+            StepResult::failed(vec![Issue::TransactionUpdateError(txn.id, e.to_string())])
+        }
+    }
+}
+
+// Execute amortizing this transaction with its associated pre-prepared update.
+// Returns a new schedule if the transaction was updated.
+async fn execute_amortization(
+    amortization: &PlannedAmortization,
+    api: &(impl LunchMoney + Sync),
+) -> StepResult {
+    let PlannedAmortization { transaction: txn, update, months } = amortization;
+    match api.update_transaction((txn.id, update.clone())).await {
+        Ok(_) => {
+            let schedule = AmortizationSchedule::new(txn, *months);
+            tracing::info!(
+                txn_id = txn.id,
+                schedule_id = %schedule.id,
+                months,
+                "Amortization schedule started"
+            );
+            StepResult {
+                schedule: Some(schedule),
+                ..StepResult::default()
             }
         }
+        Err(e) => {
+            tracing::warn!(txn_id = txn.id, error = %e, "Failed to update transaction");
+            StepResult::failed(vec![Issue::TransactionUpdateError(txn.id, e.to_string())])
+        }
     }
+}
 
-    (batched_txn_info, issues)
+// Execute splitting and adding this transaction to the batch with its associated pre-prepared update.
+// Returns info about the transaction added to the batch - i.e. after splitting,
+// the debtor's split txn info
+async fn execute_split(split: &PlannedSplit, api: &(impl LunchMoney + Sync)) -> StepResult {
+    let PlannedSplit { transaction: txn, update, splits } = split;
+
+    let split_amount = splits
+        .get(1)
+        .expect("split update contained fewer than 2 split items")
+        .amount;
+
+    let result = api
+        .update_transaction_and_split((txn.id, update.clone(), splits.clone()))
+        .await;
+
+    match result {
+        Ok(split_response) => {
+            match split_response.split_ids.get(1).ok_or("no item in position 1 of split ids in transaction update response - expected debtor proxy split id") {
+                Ok(batched_id) => StepResult {
+                    batched: vec![(*batched_id, batched_txn(txn, split_amount))],
+                    split_ids: split_response.split_ids.first().copied().into_iter().collect(),
+                    ..StepResult::default()
+                },
+                Err(e) => {
+                    tracing::warn!(txn_id = txn.id, error = %e, "Split response missing expected ID");
+                    StepResult::failed(vec![Issue::TransactionUpdateError(txn.id, e.to_string())])
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(txn_id = txn.id, error = %e, "Failed to split transaction");
+            StepResult::failed(vec![Issue::TransactionUpdateError(txn.id, e.to_string())])
+        }
+    }
 }
 
-/// Re-split parent transactions to split tagged child transactions.
+/// Plan re-splits of parent transactions to split tagged child transactions.
 ///
 /// For each tagged child, we replace it in its parent's split list with two new
 /// children (creditor half + debtor half), preserving all other siblings.
-async fn execute_resplits(
+fn plan_resplits(
     txns_to_resplit: Vec<Transaction>,
     all_txns: &[Transaction],
     proxy_category_id: u32,
-    api: &(impl LunchMoney + Sync),
-) -> (Vec<(TransactionId, Txn)>, Vec<Issue>) {
-    let mut batched_txn_info: Vec<(TransactionId, Txn)> = vec![];
-    let mut issues: Vec<Issue> = vec![];
-
+) -> Vec<PlannedResplit> {
     // Group tagged children by parent_id.
-    let mut by_parent: HashMap<TransactionId, Vec<Transaction>> = HashMap::new();
+    let mut by_parent: BTreeMap<TransactionId, Vec<Transaction>> = BTreeMap::new();
     for child in txns_to_resplit {
         let parent_id = child.parent_id.expect("resplit txn must have parent_id");
         by_parent.entry(parent_id).or_default().push(child);
     }
 
-    for (parent_id, tagged_children) in by_parent {
-        let tagged_ids: Vec<TransactionId> =
-            tagged_children.iter().map(|t| t.id).collect();
+    by_parent
+        .into_iter()
+        .map(|(parent_id, tagged_children)| {
+            let tagged_ids: Vec<TransactionId> =
+                tagged_children.iter().map(|t| t.id).collect();
+
+            // Find siblings (other children of the same parent) in the fetched transactions.
+            let siblings: Vec<Transaction> = all_txns
+                .iter()
+                .filter(|t| t.parent_id == Some(parent_id) && !tagged_ids.contains(&t.id))
+                .cloned()
+                .collect();
+
+            let (splits, debtor_amounts) =
+                create_resplit_items(&tagged_children, &siblings, proxy_category_id);
+
+            // Use update_transaction_and_split (instead of bare update_split) so we
+            // can mark the parent Cleared in the same call. The new child splits
+            // inherit the parent's cleared status from the Lunch Money API,
+            // matching the visible state of normal-split children in the UI.
+            let update = TransactionUpdateItem {
+                payee: None,
+                category_id: None,
+                notes: None,
+                tags: None,
+                status: Some(TransactionStatus::Cleared),
+            };
+
+            PlannedResplit {
+                parent_id,
+                children: tagged_children,
+                siblings,
+                update,
+                splits,
+                debtor_amounts,
+            }
+        })
+        .collect()
+}
 
-        // Find siblings (other children of the same parent) in the fetched transactions.
-        let siblings: Vec<Transaction> = all_txns
-            .iter()
-            .filter(|t| t.parent_id == Some(parent_id) && !tagged_ids.contains(&t.id))
-            .cloned()
-            .collect();
-
-        tracing::info!(
-            parent_id,
-            tagged_count = tagged_children.len(),
-            sibling_count = siblings.len(),
-            "Re-splitting parent transaction"
-        );
+/// Execute a planned re-split of a parent transaction.
+async fn execute_resplit(
+    resplit: &PlannedResplit,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> StepResult {
+    let parent_id = resplit.parent_id;
+    let failed = |msg: String| {
+        StepResult::failed(
+            resplit
+                .children
+                .iter()
+                .map(|child| Issue::TransactionUpdateError(child.id, msg.clone()))
+                .collect(),
+        )
+    };
 
-        let (split_items, debtor_amounts) =
-            create_resplit_items(&tagged_children, &siblings, proxy_category_id);
-
-        // Lunch Money rejects splitting an already-split transaction, so unsplit
-        // the parent first. After this succeeds, any failure of update_split
-        // leaves the parent in a no-children state requiring manual repair.
-        if let Err(e) = api.unsplit_transaction(parent_id).await {
-            tracing::warn!(parent_id, error = %e, "Failed to unsplit parent before resplit");
-            let msg = e.to_string();
-            for child in &tagged_children {
-                issues.push(Issue::TransactionUpdateError(child.id, msg.clone()));
-            }
-            continue;
-        }
+    tracing::info!(
+        parent_id,
+        tagged_count = resplit.children.len(),
+        sibling_count = resplit.siblings.len(),
+        "Re-splitting parent transaction"
+    );
 
-        // Use update_transaction_and_split (instead of bare update_split) so we
-        // can mark the parent Cleared in the same call. The new child splits
-        // inherit the parent's cleared status from the Lunch Money API,
-        // matching the visible state of normal-split children in the UI.
-        let parent_update = TransactionUpdateItem {
-            payee: None,
-            category_id: None,
-            notes: None,
-            tags: None,
-            status: Some(TransactionStatus::Cleared),
-        };
-        let result = api
-            .update_transaction_and_split((parent_id, parent_update, split_items))
-            .await;
-
-        match result {
-            Ok(split_response) => {
-                for (i, child) in tagged_children.iter().enumerate() {
-                    // Debtor halves are at odd indices: 1, 3, 5, ...
-                    let debtor_index = 2 * i + 1;
-                    match split_response.split_ids.get(debtor_index) {
-                        Some(&debtor_id) => {
-                            batched_txn_info.push((
-                                debtor_id,
-                                Txn {
-                                    payee: child.payee.clone(),
-                                    amount: debtor_amounts[i],
-                                    date: child.date,
-                                    notes: child.notes.clone(),
-                                },
-                            ));
-                        }
-                        None => {
-                            let msg = format!(
-                                "resplit response missing debtor ID at index {} for child {}",
-                                debtor_index, child.id
-                            );
-                            tracing::warn!(txn_id = child.id, msg, "Resplit response missing expected ID");
-                            issues.push(Issue::TransactionUpdateError(child.id, msg));
-                        }
+    // Keep the original children on disk before touching the parent, so the
+    // split can be put back, now or later with `repair`, if the re-split fails.
+    if let Err(e) = persistence.save_split_snapshot(&resplit_snapshot(resplit)) {
+        tracing::warn!(parent_id, error = %e, "Failed to save split snapshot; not re-splitting");
+        return failed(e.to_string());
+    }
+
+    // Lunch Money rejects splitting an already-split transaction, so unsplit
+    // the parent first.
+    if let Err(e) = api.unsplit_transaction(parent_id).await {
+        tracing::warn!(parent_id, error = %e, "Failed to unsplit parent before resplit");
+        forget_split_snapshot(parent_id, persistence);
+        return failed(e.to_string());
+    }
+
+    split_resplit_parent(resplit, api, persistence).await
+}
+
+/// The parent's split as it was planned against: the tagged children and their siblings.
+pub(crate) fn resplit_snapshot(resplit: &PlannedResplit) -> SplitSnapshot {
+    let mut children: Vec<Transaction> = resplit
+        .children
+        .iter()
+        .chain(&resplit.siblings)
+        .cloned()
+        .collect();
+    children.sort_by_key(|t| t.id);
+    SplitSnapshot {
+        parent_id: resplit.parent_id,
+        children,
+    }
+}
+
+fn forget_split_snapshot(parent_id: TransactionId, persistence: &(impl Persistence + Sync)) {
+    if let Err(e) = persistence.remove_split_snapshot(parent_id) {
+        tracing::warn!(parent_id, error = %e, "Failed to remove split snapshot");
+    }
+}
+
+/// Split an unsplit parent into the planned re-split items. If that fails, the
+/// original split is put back from its snapshot.
+async fn split_resplit_parent(
+    resplit: &PlannedResplit,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> StepResult {
+    let PlannedResplit {
+        parent_id,
+        children: tagged_children,
+        update: parent_update,
+        splits: split_items,
+        debtor_amounts,
+        ..
+    } = resplit;
+    let parent_id = *parent_id;
+
+    let mut result = StepResult::default();
+
+    match api
+        .update_transaction_and_split((parent_id, parent_update.clone(), split_items.clone()))
+        .await
+    {
+        Ok(split_response) => {
+            forget_split_snapshot(parent_id, persistence);
+            for (i, child) in tagged_children.iter().enumerate() {
+                // Creditor halves are at even indices and debtor halves at
+                // odd ones: 0, 1 for the first child, 2, 3 for the next...
+                result.split_ids.extend(split_response.split_ids.get(2 * i));
+                let debtor_index = 2 * i + 1;
+                match split_response.split_ids.get(debtor_index) {
+                    Some(&debtor_id) => {
+                        result
+                            .batched
+                            .push((debtor_id, batched_txn(child, debtor_amounts[i])));
+                    }
+                    None => {
+                        let msg = format!(
+                            "resplit response missing debtor ID at index {} for child {}",
+                            debtor_index, child.id
+                        );
+                        tracing::warn!(txn_id = child.id, msg, "Resplit response missing expected ID");
+                        result.issues.push(Issue::TransactionUpdateError(child.id, msg));
                     }
                 }
             }
-            Err(e) => {
-                let msg = e.to_string();
-                tracing::error!(
-                    parent_id,
-                    error = %msg,
-                    "Resplit failed AFTER unsplit succeeded — parent now has no children. Manual repair required: re-tag the parent or restore splits in Lunch Money UI."
-                );
-                for child in &tagged_children {
-                    issues.push(Issue::TransactionUpdateError(child.id, msg.clone()));
+        }
+        Err(e) => {
+            tracing::warn!(parent_id, error = %e, "Resplit failed after unsplit; restoring original split");
+            let msg = match restore_split(&resplit_snapshot(resplit), api).await {
+                Ok(()) => {
+                    forget_split_snapshot(parent_id, persistence);
+                    format!("{}; original split restored", e)
+                }
+                Err(restore_err) => {
+                    tracing::error!(
+                        parent_id,
+                        error = %restore_err,
+                        "Could not restore original split — parent has no children. Run `equailizer repair` to retry."
+                    );
+                    format!(
+                        "{}; parent {} was left unsplit and couldn't be restored ({}), run `equailizer repair`",
+                        e, parent_id, restore_err
+                    )
                 }
+            };
+            for child in tagged_children {
+                result.issues.push(Issue::TransactionUpdateError(child.id, msg.clone()));
             }
         }
     }
 
-    (batched_txn_info, issues)
+    result
 }
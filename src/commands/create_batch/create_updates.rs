@@ -1,17 +1,20 @@
 use crate::{
     commands::create_batch::process_tags::ProcessTagsOutput,
+    config::Tags,
     lunch_money::{
         api::update_transaction::{
             SplitUpdateItem, TransactionAndSplitUpdate, TransactionUpdate, TransactionUpdateItem,
         },
         model::transaction::{Tag, Transaction, TransactionStatus},
     },
-    usd::USD,
+    money::Money,
 };
+use rust_decimal::Decimal;
 
 pub fn create_updates(
     processed_data: ProcessTagsOutput,
     proxy_category_id: u32,
+    tags: &Tags,
 ) -> (
     Vec<(Transaction, TransactionUpdate)>,
     Vec<(Transaction, TransactionAndSplitUpdate)>,
@@ -19,22 +22,53 @@ pub fn create_updates(
     let add_updates: Vec<(Transaction, TransactionUpdate)> = create_add_updates(
         processed_data.txns_to_add,
         proxy_category_id,
-        &processed_data.add_tag,
+        tags,
     );
 
-    let split_updates: Vec<(Transaction, TransactionAndSplitUpdate)> = create_split_updates(
+    let mut split_updates: Vec<(Transaction, TransactionAndSplitUpdate)> = create_split_updates(
         processed_data.txns_to_split,
         proxy_category_id,
-        &processed_data.split_tag,
+        tags,
     );
 
+    split_updates.extend(create_ratio_split_updates(
+        processed_data.txns_to_ratio_split,
+        proxy_category_id,
+        tags,
+    ));
+
     return (add_updates, split_updates);
 }
 
+/// Amortized transactions move to the proxy category in full, like an add.
+/// The debtor is billed for them later, one installment at a time.
+pub fn create_amortize_updates(
+    txns_to_amortize: Vec<(Transaction, u32)>,
+    proxy_category_id: u32,
+    tags: &Tags,
+) -> Vec<(Transaction, TransactionUpdate, u32)> {
+    txns_to_amortize
+        .into_iter()
+        .map(|(txn, months)| {
+            let update = (
+                txn.id,
+                TransactionUpdateItem {
+                    payee: None,
+                    category_id: Some(proxy_category_id),
+                    notes: None,
+                    tags: updated_tags(&txn.tags, tags, |name| tags.is_amortize_tag(name)),
+                    status: Some(TransactionStatus::Cleared),
+                },
+            );
+            (txn, update, months)
+        })
+        .collect()
+}
+
 fn create_add_updates(
     txns_to_add: Vec<Transaction>,
     proxy_category_id: u32,
-    add_tag: &str,
+    tags: &Tags,
 ) -> Vec<(Transaction, TransactionUpdate)> {
     txns_to_add
         .into_iter()
@@ -45,7 +79,7 @@ fn create_add_updates(
                     payee: None,
                     category_id: Some(proxy_category_id),
                     notes: None,
-                    tags: Some(tag_names_without(&txn.tags, add_tag)),
+                    tags: updated_tags(&txn.tags, tags, |name| tags.is_add_tag(name)),
                     status: Some(TransactionStatus::Cleared),
                 },
             );
@@ -57,38 +91,65 @@ fn create_add_updates(
 fn create_split_updates(
     txns_to_split: Vec<Transaction>,
     proxy_category_id: u32,
-    split_tag: &str,
+    tags: &Tags,
 ) -> Vec<(Transaction, TransactionAndSplitUpdate)> {
     txns_to_split
         .into_iter()
         .map(|txn| {
-            let (creditor_amt, debtor_amt) = txn.amount.random_rounded_even_split();
-            let (creditor_split, debtor_split) = create_splits(
-                creditor_amt,
-                debtor_amt,
-                proxy_category_id,
-                txn.category_id,
-                txn.payee.to_owned(),
-            );
-            let update = (
-                txn.id,
-                TransactionUpdateItem {
-                    payee: None,
-                    category_id: None,
-                    notes: None,
-                    tags: Some(tag_names_without(&txn.tags, split_tag)),
-                    status: Some(TransactionStatus::Cleared),
-                },
-                vec![creditor_split, debtor_split],
-            );
-            return (txn, update);
+            let amounts = txn.amount.random_rounded_even_split();
+            create_split_update(txn, amounts, proxy_category_id, tags)
+        })
+        .collect()
+}
+
+fn create_ratio_split_updates(
+    txns_to_split: Vec<(Transaction, Decimal)>,
+    proxy_category_id: u32,
+    tags: &Tags,
+) -> Vec<(Transaction, TransactionAndSplitUpdate)> {
+    txns_to_split
+        .into_iter()
+        .map(|(txn, debtor_share)| {
+            let amounts = txn.amount.split_by_ratio(debtor_share);
+            create_split_update(txn, amounts, proxy_category_id, tags)
         })
         .collect()
 }
 
+fn create_split_update(
+    txn: Transaction,
+    (creditor_amt, debtor_amt): (Money, Money),
+    proxy_category_id: u32,
+    tags: &Tags,
+) -> (Transaction, TransactionAndSplitUpdate) {
+    let (creditor_split, debtor_split) = create_splits(
+        creditor_amt,
+        debtor_amt,
+        proxy_category_id,
+        txn.category_id,
+        txn.payee.to_owned(),
+    );
+    // An add-tagged transaction can end up split after interactive review, so
+    // either tag is removed.
+    let update = (
+        txn.id,
+        TransactionUpdateItem {
+            payee: None,
+            category_id: None,
+            notes: None,
+            tags: updated_tags(&txn.tags, tags, |name| {
+                tags.is_split_tag(name) || tags.is_add_tag(name)
+            }),
+            status: Some(TransactionStatus::Cleared),
+        },
+        vec![creditor_split, debtor_split],
+    );
+    (txn, update)
+}
+
 fn create_splits(
-    creditor_amt: USD,
-    debtor_amt: USD,
+    creditor_amt: Money,
+    debtor_amt: Money,
     proxy_category: u32,
     original_category: Option<u32>,
     original_payee: String,
@@ -117,12 +178,12 @@ fn create_splits(
 /// The returned split items are ordered as:
 /// [tagged1_creditor, tagged1_debtor, tagged2_creditor, tagged2_debtor, ..., sibling1, sibling2, ...]
 ///
-/// The returned `Vec<USD>` contains the debtor half amount for each tagged child, in order.
+/// The returned `Vec<Money>` contains the debtor half amount for each tagged child, in order.
 pub fn create_resplit_items(
     tagged_children: &[Transaction],
     siblings: &[Transaction],
     proxy_category_id: u32,
-) -> (Vec<SplitUpdateItem>, Vec<USD>) {
+) -> (Vec<SplitUpdateItem>, Vec<Money>) {
     let mut split_items = Vec::new();
     let mut debtor_amounts = Vec::new();
 
@@ -164,12 +225,25 @@ pub fn create_resplit_items(
     (split_items, debtor_amounts)
 }
 
-/// Return the transaction's tag names with the equailizer action tag removed.
-fn tag_names_without(tags: &[Tag], action_tag_to_remove: &str) -> Vec<String> {
-    tags.iter()
-        .map(|tag| tag.name.clone())
-        .filter(|name| name != action_tag_to_remove)
-        .collect()
+/// Return the tags to write back to a processed transaction: its tag names with
+/// the equailizer action tag removed, or `None` to leave its tags untouched when
+/// the profile keeps trigger tags after processing.
+fn updated_tags(
+    txn_tags: &[Tag],
+    config: &Tags,
+    is_action_tag: impl Fn(&str) -> bool,
+) -> Option<Vec<String>> {
+    if !config.remove_after_processing {
+        return None;
+    }
+
+    Some(
+        txn_tags
+            .iter()
+            .map(|tag| tag.name.clone())
+            .filter(|name| !is_action_tag(name))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
@@ -177,17 +251,31 @@ mod tests {
     use chrono::NaiveDate;
 
     use super::*;
+    use crate::money::Currency;
+
+    fn test_tags() -> Tags {
+        Tags {
+            add: "add-tag".to_string(),
+            split: "split-tag".to_string(),
+            add_aliases: vec![],
+            split_aliases: vec![],
+            amortize: "amortize-tag".to_string(),
+            remove_after_processing: true,
+        }
+    }
 
     #[test]
     fn create_actions() {
-        let add_tag = "add-tag".to_string();
-        let split_tag = "split-tag".to_string();
+        let tags = test_tags();
+        let add_tag = tags.add.clone();
+        let split_tag = tags.split.clone();
 
         let add_t1: Transaction = Transaction {
             id: 1024,
             date: NaiveDate::from_ymd_opt(2025, 10, 21).expect("NaiveDate creation failed"),
             payee: "JetBlue".to_string(),
-            amount: USD::new_from_cents(18522),
+            amount: Money::new_from_minor(18522, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(41),
             category_name: Some("Airfaire".to_string()),
@@ -206,7 +294,8 @@ mod tests {
             id: 1025,
             date: NaiveDate::from_ymd_opt(2025, 10, 22).expect("NaiveDate creation failed"),
             payee: "More Tags".to_string(),
-            amount: USD::new_from_cents(1299),
+            amount: Money::new_from_minor(1299, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -231,7 +320,8 @@ mod tests {
             id: 1026,
             date: NaiveDate::from_ymd_opt(2025, 10, 23).expect("NaiveDate creation failed"),
             payee: "Split1".to_string(),
-            amount: USD::new_from_cents(1500),
+            amount: Money::new_from_minor(1500, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -250,7 +340,8 @@ mod tests {
             id: 1027,
             date: NaiveDate::from_ymd_opt(2025, 10, 24).expect("NaiveDate creation failed"),
             payee: "More Tags".to_string(),
-            amount: USD::new_from_cents(1200),
+            amount: Money::new_from_minor(1200, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -274,14 +365,16 @@ mod tests {
         let proxy_category_id = 20;
         let (add_updates, split_updates) = super::create_updates(
             ProcessTagsOutput {
-                add_tag: add_tag,
-                split_tag: split_tag,
                 txns_to_add: vec![add_t1.clone(), add_t2.clone()],
                 txns_to_split: vec![split_t1.clone(), split_t2.clone()],
+                txns_to_ratio_split: vec![],
                 txns_to_resplit: vec![],
+                txns_to_amortize: vec![],
+                pending_ids: vec![],
                 issues: vec![],
             },
             proxy_category_id,
+            &tags,
         );
 
         let assert_add_updates = vec![
@@ -327,14 +420,14 @@ mod tests {
                     },
                     vec![
                         SplitUpdateItem {
-                            amount: USD::new_from_cents(750),
+                            amount: Money::new_from_minor(750, Currency::USD),
                             payee: Some("Split1".to_string()),
                             category_id: Some(42),
                             notes: None,
                             date: None,
                         },
                         SplitUpdateItem {
-                            amount: USD::new_from_cents(750),
+                            amount: Money::new_from_minor(750, Currency::USD),
                             payee: Some("Split1".to_string()),
                             category_id: Some(proxy_category_id),
                             notes: None,
@@ -356,14 +449,14 @@ mod tests {
                     },
                     vec![
                         SplitUpdateItem {
-                            amount: USD::new_from_cents(600),
+                            amount: Money::new_from_minor(600, Currency::USD),
                             payee: Some("More Tags".to_string()),
                             category_id: Some(42),
                             notes: None,
                             date: None,
                         },
                         SplitUpdateItem {
-                            amount: USD::new_from_cents(600),
+                            amount: Money::new_from_minor(600, Currency::USD),
                             payee: Some("More Tags".to_string()),
                             category_id: Some(proxy_category_id),
                             notes: None,
@@ -377,4 +470,58 @@ mod tests {
         assert_eq!(add_updates, assert_add_updates);
         assert_eq!(split_updates, assert_split_updates);
     }
+
+    #[test]
+    fn create_actions_keeps_tags_when_configured() {
+        let tags = Tags {
+            remove_after_processing: false,
+            ..test_tags()
+        };
+
+        let add_t: Transaction = Transaction {
+            id: 1024,
+            date: NaiveDate::from_ymd_opt(2025, 10, 21).expect("NaiveDate creation failed"),
+            payee: "Keep Tags".to_string(),
+            amount: Money::new_from_minor(1000, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: Some(41),
+            category_name: Some("Testing".to_string()),
+            tags: vec![Tag {
+                name: tags.add.clone(),
+                id: 0,
+            }],
+            notes: None,
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        };
+
+        let split_t: Transaction = Transaction {
+            id: 1025,
+            tags: vec![Tag {
+                name: tags.split.clone(),
+                id: 0,
+            }],
+            ..add_t.clone()
+        };
+
+        let (add_updates, split_updates) = super::create_updates(
+            ProcessTagsOutput {
+                txns_to_add: vec![add_t],
+                txns_to_split: vec![split_t],
+                txns_to_ratio_split: vec![],
+                txns_to_resplit: vec![],
+                txns_to_amortize: vec![],
+                pending_ids: vec![],
+                issues: vec![],
+            },
+            20,
+            &tags,
+        );
+
+        assert_eq!(add_updates[0].1.1.tags, None);
+        assert_eq!(split_updates[0].1.1.tags, None);
+    }
 }
@@ -0,0 +1,122 @@
+use crate::amortization::AmortizationSchedule;
+use crate::commands::create_batch::plan::BatchPlan;
+use crate::email::Txn;
+use crate::issue::Issue;
+use crate::lunch_money::model::transaction::TransactionId;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A write-ahead record of one `create-batch` run. It's saved before the run
+/// changes anything and again around every step, so a run that dies partway
+/// can be finished or rolled back with `equailizer resume`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Journal {
+    /// The id the batch is saved under once the run finishes.
+    pub batch_id: String,
+    pub plan: BatchPlan,
+    /// Steps in the order they were started.
+    pub steps: Vec<StepRecord>,
+}
+
+/// One mutation of the plan, identified by the transaction it changes (the
+/// parent, for a re-split).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum Step {
+    Add(TransactionId),
+    Split(TransactionId),
+    Resplit(TransactionId),
+    Amortize(TransactionId),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepRecord {
+    pub step: Step,
+    pub state: StepState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum StepState {
+    /// Written just before the step's API calls. If a run stops here, whether
+    /// they went through is unknown.
+    Started,
+    Finished(Box<StepResult>),
+}
+
+/// What a finished step added to the batch. A step that failed has only issues.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StepResult {
+    pub batched: Vec<(TransactionId, Txn)>,
+    /// The creditor's halves of the splits the step made.
+    #[serde(default)]
+    pub split_ids: Vec<TransactionId>,
+    /// The schedule started by an amortization. Not saved until the batch is.
+    pub schedule: Option<AmortizationSchedule>,
+    pub issues: Vec<Issue>,
+}
+
+impl StepResult {
+    pub fn failed(issues: Vec<Issue>) -> Self {
+        Self {
+            issues,
+            ..Self::default()
+        }
+    }
+}
+
+impl StepRecord {
+    /// Whether the step may have changed anything in Lunch Money. A step that
+    /// finished without batching anything failed before changing anything,
+    /// except a split whose response was missing the debtor's id.
+    pub fn may_have_changed(&self) -> bool {
+        match &self.state {
+            StepState::Started => true,
+            StepState::Finished(result) => {
+                !result.batched.is_empty()
+                    || result.schedule.is_some()
+                    || matches!(self.step, Step::Split(_))
+            }
+        }
+    }
+}
+
+impl Journal {
+    pub fn new(plan: BatchPlan) -> Self {
+        Self {
+            batch_id: Uuid::new_v4().to_string(),
+            plan,
+            steps: vec![],
+        }
+    }
+
+    /// Every step of the plan, in the order they run.
+    pub fn planned_steps(&self) -> Vec<Step> {
+        let plan = &self.plan;
+        plan.adds
+            .iter()
+            .map(|a| Step::Add(a.transaction.id))
+            .chain(plan.splits.iter().map(|s| Step::Split(s.transaction.id)))
+            .chain(plan.resplits.iter().map(|r| Step::Resplit(r.parent_id)))
+            .chain(
+                plan.amortizations
+                    .iter()
+                    .map(|a| Step::Amortize(a.transaction.id)),
+            )
+            .collect()
+    }
+
+    pub fn state(&self, step: Step) -> Option<&StepState> {
+        self.steps
+            .iter()
+            .find(|r| r.step == step)
+            .map(|r| &r.state)
+    }
+
+    pub fn record(&mut self, step: Step, state: StepState) {
+        match self.steps.iter_mut().find(|r| r.step == step) {
+            Some(record) => record.state = state,
+            None => self.steps.push(StepRecord { step, state }),
+        }
+    }
+}
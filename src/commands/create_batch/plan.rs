@@ -0,0 +1,103 @@
+use crate::error::Result;
+use crate::lunch_money::api::update_transaction::{SplitUpdateItem, TransactionUpdateItem};
+use crate::lunch_money::model::transaction::{Transaction, TransactionId};
+use crate::marker::BatchMarker;
+use crate::money::{Currency, Money};
+use crate::persist::BatchInstallment;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Everything a `create-batch` run will do, worked out up front: the exact
+/// updates and split amounts for each transaction, and the snapshot of each
+/// transaction they were planned against.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchPlan {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub currency: Currency,
+    pub adds: Vec<PlannedUpdate>,
+    pub splits: Vec<PlannedSplit>,
+    pub resplits: Vec<PlannedResplit>,
+    pub amortizations: Vec<PlannedAmortization>,
+    /// Installments of existing schedules that are due by `end_date`.
+    pub installments: Vec<BatchInstallment>,
+    /// Tagged transactions that are still pending, to remember for later runs.
+    pub pending_ids: Vec<TransactionId>,
+    pub issues: Vec<String>,
+    /// Undo everything and save no batch if any transaction can't be processed.
+    #[serde(default)]
+    pub all_or_nothing: bool,
+    /// Marks the batched transactions with the batch id once it's saved.
+    #[serde(default)]
+    pub batch_marker: Option<BatchMarker>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedUpdate {
+    pub transaction: Transaction,
+    pub update: TransactionUpdateItem,
+}
+
+/// A split of `transaction`. The second split item is the debtor's share.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedSplit {
+    pub transaction: Transaction,
+    pub update: TransactionUpdateItem,
+    pub splits: Vec<SplitUpdateItem>,
+}
+
+/// A re-split of `parent_id` that splits each tagged child and keeps its
+/// siblings. See `create_resplit_items` for the order of the split items.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedResplit {
+    pub parent_id: TransactionId,
+    pub children: Vec<Transaction>,
+    pub siblings: Vec<Transaction>,
+    pub update: TransactionUpdateItem,
+    pub splits: Vec<SplitUpdateItem>,
+    /// The debtor's share of each tagged child, in order.
+    pub debtor_amounts: Vec<Money>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedAmortization {
+    pub transaction: Transaction,
+    pub update: TransactionUpdateItem,
+    pub months: u32,
+}
+
+impl BatchPlan {
+    pub fn is_empty(&self) -> bool {
+        self.adds.is_empty()
+            && self.splits.is_empty()
+            && self.resplits.is_empty()
+            && self.amortizations.is_empty()
+            && self.installments.is_empty()
+    }
+
+    /// Every transaction the plan was made against, as it was then.
+    pub fn snapshots(&self) -> impl Iterator<Item = &Transaction> {
+        self.adds
+            .iter()
+            .map(|a| &a.transaction)
+            .chain(self.splits.iter().map(|s| &s.transaction))
+            .chain(
+                self.resplits
+                    .iter()
+                    .flat_map(|r| r.children.iter().chain(&r.siblings)),
+            )
+            .chain(self.amortizations.iter().map(|a| &a.transaction))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        tracing::info!(path = %path.display(), "Plan saved");
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&file)?)
+    }
+}
@@ -1,50 +1,213 @@
-use crate::{issue::Issue, lunch_money::model::transaction::Transaction};
+use crate::{
+    config::Tags,
+    issue::Issue,
+    lunch_money::model::transaction::{Transaction, TransactionId},
+    money::Currency,
+    review::{ProposedAction, ReviewDecision, Reviewer},
+    rules::RuleMatches,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
 pub struct ProcessTagsOutput {
-    pub add_tag: String,
-    pub split_tag: String,
     pub txns_to_add: Vec<Transaction>,
     pub txns_to_split: Vec<Transaction>,
+    /// Transactions to split unevenly, with the debtor's share of each.
+    pub txns_to_ratio_split: Vec<(Transaction, Decimal)>,
     pub txns_to_resplit: Vec<Transaction>,
+    /// Transactions to amortize, with the number of monthly installments.
+    pub txns_to_amortize: Vec<(Transaction, u32)>,
+    /// Tagged transactions that are still pending, to be carried forward until
+    /// they post.
+    pub pending_ids: Vec<TransactionId>,
     pub issues: Vec<Issue>,
 }
 
-pub fn process_tags(
-    in_txns: Vec<Transaction>,
-    add_tag: &String,
-    split_tag: &String,
-) -> ProcessTagsOutput {
+impl ProcessTagsOutput {
+    /// Fold in transactions matched by profile rules. Rules never match tagged
+    /// transactions, so there's no overlap with what the tags found.
+    pub fn add_rule_matches(&mut self, matches: RuleMatches) {
+        self.txns_to_add.extend(matches.txns_to_add);
+        self.txns_to_split.extend(matches.txns_to_split);
+        self.txns_to_ratio_split.extend(matches.txns_to_ratio_split);
+    }
+
+    /// Drop transactions that aren't in the batch currency, with an issue for
+    /// each, since a batch can only be settled in one currency.
+    pub fn retain_currency(&mut self, currency: Currency) {
+        let issues = &mut self.issues;
+        let mut keep = |txn: &Transaction| {
+            if txn.amount.currency() == currency {
+                return true;
+            }
+            issues.push(Issue::ForeignCurrency(txn.id, txn.amount, currency));
+            false
+        };
+
+        self.txns_to_add.retain(|t| keep(t));
+        self.txns_to_split.retain(|t| keep(t));
+        self.txns_to_ratio_split.retain(|(t, _)| keep(t));
+        self.txns_to_resplit.retain(|t| keep(t));
+        self.txns_to_amortize.retain(|(t, _)| keep(t));
+    }
+
+    /// Drop transactions that have already been billed, with an issue for
+    /// each: those in a saved batch or whose split parent is, and those
+    /// already in the proxy category. `batched` maps each batched transaction
+    /// id, and the creditor's half of each split a batch made, to its batch
+    /// id. When the profile keeps trigger tags after processing, every billed
+    /// transaction is still tagged, so ones found in a batch are left out
+    /// quietly; ones in the proxy category without a batch are still reported.
+    pub fn retain_unbatched(
+        &mut self,
+        batched: &HashMap<TransactionId, String>,
+        proxy_category_id: u32,
+        tags: &Tags,
+    ) {
+        let issues = &mut self.issues;
+        let mut keep = |txn: &Transaction| {
+            let batch_id = batched
+                .get(&txn.id)
+                .or_else(|| txn.parent_id.and_then(|p| batched.get(&p)));
+            if batch_id.is_none() && txn.category_id != Some(proxy_category_id) {
+                return true;
+            }
+            if batch_id.is_some() && !tags.remove_after_processing {
+                tracing::debug!(txn_id = txn.id, batch_id, "Transaction still tagged from an earlier batch; leaving it out");
+                return false;
+            }
+            tracing::warn!(txn_id = txn.id, batch_id, "Transaction already batched; leaving it out");
+            issues.push(Issue::AlreadyBatched(txn.id, batch_id.cloned()));
+            false
+        };
+
+        self.txns_to_add.retain(|t| keep(t));
+        self.txns_to_split.retain(|t| keep(t));
+        self.txns_to_ratio_split.retain(|(t, _)| keep(t));
+        self.txns_to_resplit.retain(|t| keep(t));
+        self.txns_to_amortize.retain(|(t, _)| keep(t));
+    }
+
+    /// When the profile keeps trigger tags after processing, a parent that an
+    /// earlier batch split is still tagged. Drop the issue about its children
+    /// when any of them is in `batched`, rather than repeating it every run.
+    /// `txns` is every fetched transaction, to find the children.
+    pub fn forget_batched_parents(
+        &mut self,
+        batched: &HashMap<TransactionId, String>,
+        txns: &[Transaction],
+        tags: &Tags,
+    ) {
+        if tags.remove_after_processing {
+            return;
+        }
+        let batched_parents: Vec<TransactionId> = txns
+            .iter()
+            .filter(|t| batched.contains_key(&t.id))
+            .filter_map(|t| t.parent_id)
+            .collect();
+        self.issues.retain(|issue| match issue {
+            Issue::AddTagHasChildren(id) | Issue::SplitTagHasChildren(id) => {
+                !batched_parents.contains(id)
+            }
+            _ => true,
+        });
+    }
+
+    /// Let the reviewer accept, skip or change the split of each transaction.
+    pub fn review(&mut self, reviewer: &mut dyn Reviewer) {
+        let adds = std::mem::take(&mut self.txns_to_add)
+            .into_iter()
+            .map(|t| (t, ProposedAction::Add));
+        let splits = std::mem::take(&mut self.txns_to_split)
+            .into_iter()
+            .map(|t| (t, ProposedAction::Split));
+        let ratio_splits = std::mem::take(&mut self.txns_to_ratio_split)
+            .into_iter()
+            .map(|(t, share)| (t, ProposedAction::RatioSplit(share)));
+
+        for (txn, action) in adds.chain(splits).chain(ratio_splits).collect::<Vec<_>>() {
+            let decision = reviewer.review(&txn, action);
+            match (decision, action) {
+                (ReviewDecision::Skip, _) => {
+                    tracing::info!(txn_id = txn.id, "Skipped during review");
+                }
+                (ReviewDecision::Accept, ProposedAction::Add) => self.txns_to_add.push(txn),
+                (ReviewDecision::Accept, ProposedAction::RatioSplit(share)) => {
+                    self.txns_to_ratio_split.push((txn, share))
+                }
+                (ReviewDecision::Accept, _) | (ReviewDecision::Split, _) => {
+                    self.txns_to_split.push(txn)
+                }
+                (ReviewDecision::Ratio(share), _) => self.txns_to_ratio_split.push((txn, share)),
+            }
+        }
+
+        // Re-splits and amortizations can only be accepted or skipped.
+        self.txns_to_resplit
+            .retain(|t| reviewer.review(t, ProposedAction::Resplit) != ReviewDecision::Skip);
+        self.txns_to_amortize.retain(|(t, months)| {
+            reviewer.review(t, ProposedAction::Amortize(*months)) != ReviewDecision::Skip
+        });
+    }
+}
+
+pub fn process_tags(in_txns: Vec<Transaction>, tags: &Tags) -> ProcessTagsOutput {
     let span = tracing::info_span!("Processing Tags");
     let _enter = span.enter();
 
     let mut issues: Vec<Issue> = vec![];
 
     let total_count = in_txns.len();
-    let pending_count = in_txns.iter().filter(|t| t.is_pending).count();
+    let (pending_txns, in_txns): (Vec<Transaction>, Vec<Transaction>) =
+        in_txns.into_iter().partition(|t| t.is_pending);
+    let pending_count = pending_txns.len();
     if pending_count > 0 {
         tracing::info!(pending_count, "Skipping pending transactions");
     }
 
-    // Just ignore pending transactions
-    let (txns_to_add, txns_to_split) = in_txns.into_iter().filter(|t| !t.is_pending).fold(
-        (Vec::<Transaction>::new(), Vec::<Transaction>::new()),
-        |(mut add, mut split), txn| {
-            if txn.tag_names().contains(&&add_tag) {
-                add.push(txn);
-            } else if txn.tag_names().contains(&&split_tag) {
-                split.push(txn);
-            }
-            return (add, split);
-        },
-    );
+    // Pending transactions can't be batched yet, but tagged ones are
+    // remembered so a later run picks them up once they post.
+    let pending_ids: Vec<TransactionId> = pending_txns
+        .iter()
+        .filter(|t| t.tag_names().iter().any(|name| tags.is_trigger_tag(name)))
+        .map(|t| t.id)
+        .collect();
+    for id in &pending_ids {
+        issues.push(Issue::TaggedPending(*id));
+    }
+
+    let (txns_to_add, txns_to_split, txns_to_amortize) =
+        in_txns.into_iter().fold(
+            (
+                Vec::<Transaction>::new(),
+                Vec::<Transaction>::new(),
+                Vec::<Transaction>::new(),
+            ),
+            |(mut add, mut split, mut amortize), txn| {
+                if txn.tag_names().iter().any(|name| tags.is_add_tag(name)) {
+                    add.push(txn);
+                } else if txn.tag_names().iter().any(|name| tags.is_split_tag(name)) {
+                    split.push(txn);
+                } else if txn.tag_names().iter().any(|name| tags.is_amortize_tag(name)) {
+                    amortize.push(txn);
+                }
+                return (add, split, amortize);
+            },
+        );
 
     tracing::info!(
         total = total_count,
         pending_skipped = pending_count,
         tagged_add = txns_to_add.len(),
         tagged_split = txns_to_split.len(),
-        untagged = total_count - pending_count - txns_to_add.len() - txns_to_split.len(),
+        tagged_amortize = txns_to_amortize.len(),
+        untagged = total_count
+            - pending_count
+            - txns_to_add.len()
+            - txns_to_split.len()
+            - txns_to_amortize.len(),
         "Transaction breakdown"
     );
 
@@ -60,6 +223,9 @@ pub fn process_tags(
         filter_invalid_txns_to_split(txns_to_split);
     issues.append(&mut new_issues);
 
+    let (txns_to_amortize, mut new_issues) = filter_invalid_txns_to_amortize(txns_to_amortize, tags);
+    issues.append(&mut new_issues);
+
     if !txns_to_resplit.is_empty() {
         tracing::info!(
             count = txns_to_resplit.len(),
@@ -68,11 +234,12 @@ pub fn process_tags(
     }
 
     return ProcessTagsOutput {
-        add_tag: add_tag.clone(),
-        split_tag: split_tag.clone(),
         txns_to_add: txns_to_add,
         txns_to_split: txns_to_split,
+        txns_to_ratio_split: vec![],
         txns_to_resplit: txns_to_resplit,
+        txns_to_amortize,
+        pending_ids,
         issues: issues,
     };
 }
@@ -127,27 +294,72 @@ fn filter_invalid_txns_to_split(
     )
 }
 
+fn filter_invalid_txns_to_amortize(
+    txns: Vec<Transaction>,
+    tags: &Tags,
+) -> (Vec<(Transaction, u32)>, Vec<Issue>) {
+    txns.into_iter().fold(
+        (vec![], vec![]),
+        |(mut valid, mut issues), txn| {
+            let tag = txn
+                .tag_names()
+                .into_iter()
+                .find(|name| tags.is_amortize_tag(name))
+                .cloned()
+                .unwrap_or_default();
+
+            if txn.has_children {
+                tracing::debug!(
+                    txn_id = txn.id,
+                    "Found 'amortize' tag, but transaction has children"
+                );
+                issues.push(Issue::AmortizeTagHasChildren(txn.id));
+            } else if let Some(months) = tags.amortize_months(&tag) {
+                valid.push((txn, months));
+            } else {
+                tracing::debug!(txn_id = txn.id, tag, "Amortize tag has no valid month count");
+                issues.push(Issue::InvalidAmortizeTag(txn.id, tag));
+            }
+
+            (valid, issues)
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::NaiveDate;
 
     use crate::{
         lunch_money::model::transaction::{Tag, TransactionStatus},
-        usd::USD,
+        money::{Currency, Money},
     };
 
     use super::*;
 
+    fn test_tags() -> Tags {
+        Tags {
+            add: "add-tag".to_string(),
+            split: "split-tag".to_string(),
+            add_aliases: vec!["add-alias".to_string()],
+            split_aliases: vec!["split-alias".to_string()],
+            amortize: "amortize-tag".to_string(),
+            remove_after_processing: true,
+        }
+    }
+
     #[test]
     fn process_tags() {
-        let add_tag = "add-tag".to_string();
-        let split_tag = "split-tag".to_string();
+        let tags = test_tags();
+        let add_tag = tags.add.clone();
+        let split_tag = tags.split.clone();
 
         let add_t: Transaction = Transaction {
             id: 1024,
             date: NaiveDate::from_ymd_opt(2025, 10, 21).expect("NaiveDate creation failed"),
             payee: "JetBlue".to_string(),
-            amount: USD::new_from_cents(18522),
+            amount: Money::new_from_minor(18522, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(41),
             category_name: Some("Airfaire".to_string()),
@@ -166,7 +378,8 @@ mod tests {
             id: 1025,
             date: NaiveDate::from_ymd_opt(2025, 10, 22).expect("NaiveDate creation failed"),
             payee: "Has Children".to_string(),
-            amount: USD::new_from_cents(1299),
+            amount: Money::new_from_minor(1299, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -185,7 +398,8 @@ mod tests {
             id: 1026,
             date: NaiveDate::from_ymd_opt(2025, 10, 22).expect("NaiveDate creation failed"),
             payee: "Split".to_string(),
-            amount: USD::new_from_cents(1299),
+            amount: Money::new_from_minor(1299, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -204,7 +418,8 @@ mod tests {
             id: 1027,
             date: NaiveDate::from_ymd_opt(2025, 10, 22).expect("NaiveDate creation failed"),
             payee: "Split".to_string(),
-            amount: USD::new_from_cents(1299),
+            amount: Money::new_from_minor(1299, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -223,7 +438,8 @@ mod tests {
             id: 1028,
             date: NaiveDate::from_ymd_opt(2025, 10, 22).expect("NaiveDate creation failed"),
             payee: "Split".to_string(),
-            amount: USD::new_from_cents(1299),
+            amount: Money::new_from_minor(1299, Currency::USD),
+            to_base: None,
             plaid_account_id: None,
             category_id: Some(42),
             category_name: Some("Testing".to_string()),
@@ -246,19 +462,19 @@ mod tests {
                 split_has_parent_t.clone(),
                 split_has_children_t,
             ],
-            &add_tag,
-            &split_tag,
+            &tags,
         );
 
         let add_has_children_issue: Issue = Issue::AddTagHasChildren(1025);
         let split_has_children_issue: Issue = Issue::SplitTagHasChildren(1028);
 
         let assert_output: ProcessTagsOutput = ProcessTagsOutput {
-            add_tag,
-            split_tag,
             txns_to_add: vec![add_t],
             txns_to_split: vec![split_t],
+            txns_to_ratio_split: vec![],
             txns_to_resplit: vec![split_has_parent_t],
+            txns_to_amortize: vec![],
+            pending_ids: vec![],
             issues: vec![
                 add_has_children_issue,
                 split_has_children_issue,
@@ -267,4 +483,100 @@ mod tests {
 
         assert_eq!(output, assert_output);
     }
+
+    #[test]
+    fn process_tags_matches_aliases() {
+        let tags = test_tags();
+
+        let alias_add_t: Transaction = Transaction {
+            id: 2048,
+            date: NaiveDate::from_ymd_opt(2025, 10, 21).expect("NaiveDate creation failed"),
+            payee: "Alias Add".to_string(),
+            amount: Money::new_from_minor(1000, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: None,
+            category_name: None,
+            tags: vec![Tag {
+                name: "add-alias".to_string(),
+                id: 0,
+            }],
+            notes: None,
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        };
+
+        let alias_split_t: Transaction = Transaction {
+            id: 2049,
+            payee: "Alias Split".to_string(),
+            tags: vec![Tag {
+                name: "split-alias".to_string(),
+                id: 0,
+            }],
+            ..alias_add_t.clone()
+        };
+
+        let default_tag_t: Transaction = Transaction {
+            id: 2050,
+            payee: "Default Tag".to_string(),
+            tags: vec![Tag {
+                name: crate::config::TAG_BATCH_ADD.to_string(),
+                id: 0,
+            }],
+            ..alias_add_t.clone()
+        };
+
+        let output = super::process_tags(
+            vec![alias_add_t.clone(), alias_split_t.clone(), default_tag_t],
+            &tags,
+        );
+
+        assert_eq!(output.txns_to_add, vec![alias_add_t]);
+        assert_eq!(output.txns_to_split, vec![alias_split_t]);
+        assert!(output.issues.is_empty());
+    }
+
+    #[test]
+    fn process_tags_amortize() {
+        let tags = test_tags();
+
+        let amortize_t: Transaction = Transaction {
+            id: 3000,
+            date: NaiveDate::from_ymd_opt(2025, 10, 21).expect("NaiveDate creation failed"),
+            payee: "Insurance".to_string(),
+            amount: Money::new_from_minor(120000, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: None,
+            category_name: None,
+            tags: vec![Tag {
+                name: "amortize-tag-12".to_string(),
+                id: 0,
+            }],
+            notes: None,
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        };
+
+        let no_months_t: Transaction = Transaction {
+            id: 3001,
+            tags: vec![Tag {
+                name: "amortize-tag".to_string(),
+                id: 0,
+            }],
+            ..amortize_t.clone()
+        };
+
+        let output = super::process_tags(vec![amortize_t.clone(), no_months_t], &tags);
+
+        assert_eq!(output.txns_to_amortize, vec![(amortize_t, 12)]);
+        assert_eq!(
+            output.issues,
+            vec![Issue::InvalidAmortizeTag(3001, "amortize-tag".to_string())]
+        );
+    }
 }
@@ -0,0 +1,365 @@
+use crate::{
+    commands::{
+        create_batch::{execute_plan, plan_batch},
+        reconcile::reconcile_all,
+        remind::remind_overdue,
+    },
+    config::{self, Config},
+    date_helpers,
+    email::BatchNotifier,
+    error::Result,
+    lunch_money::api::LunchMoney,
+    persist::{DaemonState, Persistence},
+    plugin::PluginManager,
+    report::BatchReport,
+};
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeZone};
+use chrono_tz::{Tz, US::Eastern};
+use equailizer_plugin::PluginMessage;
+use std::future::Future;
+use std::time::Duration;
+
+/// How often the daemon wakes up to check whether a job is due.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Run `create-batch` and `reconcile-all` (and `remind`, if the profile asks
+/// for it) on the profile's schedule until
+/// `shutdown` resolves. A job that's running when `shutdown` resolves is
+/// allowed to finish first.
+pub async fn run_daemon(
+    config: &Config,
+    creditor_api: &(impl LunchMoney + Sync),
+    debtor_api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let started_at = date_helpers::now_eastern();
+    tracing::info!(
+        batch_weekday = %config.daemon.batch_weekday,
+        batch_hour = config.daemon.batch_hour,
+        reconcile_every_hours = config.daemon.reconcile_every_hours,
+        "Daemon started"
+    );
+
+    tokio::pin!(shutdown);
+    loop {
+        run_due_jobs(
+            date_helpers::now_eastern(),
+            started_at,
+            config,
+            creditor_api,
+            debtor_api,
+            persistence,
+            notifier,
+            plugins,
+        )
+        .await?;
+
+        tokio::select! {
+            _ = &mut shutdown => {
+                tracing::info!("Shutdown signal received — stopping daemon");
+                return Ok(());
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Run whichever jobs are due at `now`, and record that they ran. A failed job
+/// is reported to plugins and not retried until its next scheduled run; a
+/// failed batch leaves `last_batch_end` alone so the next batch covers its range.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_due_jobs(
+    now: DateTime<Tz>,
+    started_at: DateTime<Tz>,
+    config: &Config,
+    creditor_api: &(impl LunchMoney + Sync),
+    debtor_api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<()> {
+    let mut state = persistence.daemon_state()?;
+
+    if batch_due(now, started_at, &config.daemon, &state) {
+        let today = now.date_naive();
+        match batch_range(today, &config.daemon, &state) {
+            Some((start_date, end_date)) => {
+                tracing::info!(%start_date, %end_date, "Running scheduled create-batch");
+                match run_batch(
+                    start_date,
+                    end_date,
+                    config,
+                    creditor_api,
+                    persistence,
+                    notifier,
+                    plugins,
+                )
+                .await
+                {
+                    // A rolled-back batch is retried over the same range next time.
+                    Ok(report) if report.rollback.is_some() => {
+                        tracing::warn!("Scheduled batch was rolled back after a failure");
+                    }
+                    Ok(_) => state.last_batch_end = Some(end_date),
+                    Err(e) => report_error("create-batch", &e, plugins).await,
+                }
+            }
+            None => tracing::info!("Previous batch already covers today — skipping create-batch"),
+        }
+        state.last_batch_run = Some(now.to_utc());
+        persistence.save_daemon_state(&state)?;
+    }
+
+    if reconcile_due(now, &config.daemon, &state) {
+        tracing::info!("Running scheduled reconcile-all");
+        match reconcile_all(config, creditor_api, debtor_api, persistence, notifier, plugins).await {
+            Ok(result) => {
+                for error in &result.errors {
+                    tracing::warn!("{}", error);
+                }
+                // Unlike the one-off command, stay quiet when there was nothing to do.
+                if result.reconciled > 0 || !result.errors.is_empty() {
+                    plugins
+                        .dispatch(&crate::plugin::reconcile_all_complete_message(
+                            result.reconciled,
+                            &result.errors,
+                        ))
+                        .await;
+                }
+            }
+            Err(e) => report_error("reconcile-all", &e, plugins).await,
+        }
+        // Reminders go out after reconciling, so a batch settled since the
+        // last run isn't reminded about.
+        if config.daemon.remind
+            && let Err(e) =
+                remind_overdue(now.date_naive(), config, creditor_api, persistence, notifier).await
+        {
+            report_error("remind", &e, plugins).await;
+        }
+        state.last_reconcile_run = Some(now.to_utc());
+        persistence.save_daemon_state(&state)?;
+    }
+
+    Ok(())
+}
+
+/// Create a batch over the range, all-or-nothing if the profile asks for it.
+async fn run_batch(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    config: &Config,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<BatchReport> {
+    let mut plan = plan_batch(start_date, end_date, config, api, persistence, None).await?;
+    plan.all_or_nothing = config.daemon.all_or_nothing;
+    execute_plan(plan, api, persistence, notifier, plugins).await
+}
+
+async fn report_error(command: &str, e: &crate::error::Error, plugins: &mut PluginManager) {
+    tracing::error!(command, "{e:#}", e = e);
+    plugins
+        .dispatch(&PluginMessage::CommandError {
+            command: command.to_string(),
+            error: format!("{e:#}"),
+        })
+        .await;
+}
+
+/// The most recent scheduled batch time at or before `now`.
+pub fn last_batch_slot(now: DateTime<Tz>, daemon: &config::Daemon) -> DateTime<Tz> {
+    let days_back = (7 + now.weekday().num_days_from_monday()
+        - daemon.batch_weekday.num_days_from_monday())
+        % 7;
+    let slot_date = now.date_naive() - Days::new(days_back.into());
+    let slot = slot_at(slot_date, daemon.batch_hour);
+    if slot > now {
+        slot_at(slot_date - Days::new(7), daemon.batch_hour)
+    } else {
+        slot
+    }
+}
+
+fn slot_at(date: NaiveDate, hour: u32) -> DateTime<Tz> {
+    let naive = date
+        .and_hms_opt(hour, 0, 0)
+        .expect("batch hour is validated to be 0-23");
+    // On a DST change the hour may be repeated or skipped; take the earlier of
+    // a repeated hour, or the hour after a skipped one.
+    Eastern
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            Eastern
+                .from_local_datetime(&(naive + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .expect("only one hour is skipped at a DST change")
+}
+
+/// A batch is due once a scheduled time has passed that the daemon hasn't
+/// batched for yet. With no previous run, the daemon waits for the first
+/// scheduled time after it started rather than batching straight away.
+pub fn batch_due(
+    now: DateTime<Tz>,
+    started_at: DateTime<Tz>,
+    daemon: &config::Daemon,
+    state: &DaemonState,
+) -> bool {
+    let since = state
+        .last_batch_run
+        .map(|t| t.with_timezone(&Eastern))
+        .unwrap_or(started_at);
+    last_batch_slot(now, daemon) > since
+}
+
+pub fn reconcile_due(now: DateTime<Tz>, daemon: &config::Daemon, state: &DaemonState) -> bool {
+    state.last_reconcile_run.is_none_or(|last| {
+        now.to_utc() - last >= chrono::Duration::hours(daemon.reconcile_every_hours.into())
+    })
+}
+
+/// The date range for the next batch: from `overlap_days` before the previous
+/// batch ended through today, so transactions that turned up late are still
+/// batched. `None` if the previous batch already reached today.
+pub fn batch_range(
+    today: NaiveDate,
+    daemon: &config::Daemon,
+    state: &DaemonState,
+) -> Option<(NaiveDate, NaiveDate)> {
+    match state.last_batch_end {
+        Some(end) if end >= today => None,
+        Some(end) => Some((end + Days::new(1) - Days::new(daemon.overlap_days.into()), today)),
+        None => Some((today - Days::new(daemon.initial_days_ago.into()), today)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Utc, Weekday};
+
+    fn eastern(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        Eastern.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn sunday_at_nine() -> config::Daemon {
+        config::Daemon {
+            batch_weekday: Weekday::Sun,
+            batch_hour: 9,
+            ..config::Daemon::default()
+        }
+    }
+
+    #[test]
+    fn last_batch_slot_finds_previous_weekday() {
+        let daemon = sunday_at_nine();
+        // 2025-03-12 is a Wednesday.
+        assert_eq!(
+            last_batch_slot(eastern(2025, 3, 12, 15, 0), &daemon),
+            eastern(2025, 3, 9, 9, 0)
+        );
+        // Sunday before 9am still points at the previous Sunday.
+        assert_eq!(
+            last_batch_slot(eastern(2025, 3, 16, 8, 59), &daemon),
+            eastern(2025, 3, 9, 9, 0)
+        );
+        assert_eq!(
+            last_batch_slot(eastern(2025, 3, 16, 9, 0), &daemon),
+            eastern(2025, 3, 16, 9, 0)
+        );
+    }
+
+    #[test]
+    fn batch_due_waits_for_first_slot_after_start() {
+        let daemon = sunday_at_nine();
+        let started_at = eastern(2025, 3, 12, 15, 0);
+        let state = DaemonState::default();
+
+        assert!(!batch_due(eastern(2025, 3, 15, 12, 0), started_at, &daemon, &state));
+        assert!(batch_due(eastern(2025, 3, 16, 9, 1), started_at, &daemon, &state));
+    }
+
+    #[test]
+    fn batch_due_catches_up_after_missed_slot() {
+        let daemon = sunday_at_nine();
+        let state = DaemonState {
+            last_batch_run: Some(eastern(2025, 3, 9, 9, 0).to_utc()),
+            ..DaemonState::default()
+        };
+        let started_at = eastern(2025, 3, 20, 10, 0);
+
+        // Down over Sunday the 16th, so a batch is due as soon as it restarts.
+        assert!(batch_due(started_at, started_at, &daemon, &state));
+
+        let state = DaemonState {
+            last_batch_run: Some(eastern(2025, 3, 16, 9, 0).to_utc()),
+            ..DaemonState::default()
+        };
+        assert!(!batch_due(eastern(2025, 3, 20, 10, 0), started_at, &daemon, &state));
+    }
+
+    #[test]
+    fn reconcile_due_after_interval() {
+        let daemon = config::Daemon {
+            reconcile_every_hours: 4,
+            ..config::Daemon::default()
+        };
+        assert!(reconcile_due(eastern(2025, 3, 12, 10, 0), &daemon, &DaemonState::default()));
+
+        let state = DaemonState {
+            last_reconcile_run: Some(Utc.with_ymd_and_hms(2025, 3, 12, 12, 0, 0).unwrap()),
+            ..DaemonState::default()
+        };
+        // 12:00 UTC is 08:00 Eastern.
+        assert!(!reconcile_due(eastern(2025, 3, 12, 11, 59), &daemon, &state));
+        assert!(reconcile_due(eastern(2025, 3, 12, 12, 0), &daemon, &state));
+    }
+
+    #[test]
+    fn batch_range_overlaps_previous_end() {
+        let daemon = config::Daemon {
+            initial_days_ago: 7,
+            overlap_days: 3,
+            ..config::Daemon::default()
+        };
+
+        assert_eq!(
+            batch_range(date(2025, 3, 16), &daemon, &DaemonState::default()),
+            Some((date(2025, 3, 9), date(2025, 3, 16)))
+        );
+
+        let state = DaemonState {
+            last_batch_end: Some(date(2025, 3, 9)),
+            ..DaemonState::default()
+        };
+        assert_eq!(
+            batch_range(date(2025, 3, 16), &daemon, &state),
+            Some((date(2025, 3, 7), date(2025, 3, 16)))
+        );
+        let no_overlap = config::Daemon {
+            overlap_days: 0,
+            ..daemon
+        };
+        assert_eq!(
+            batch_range(date(2025, 3, 16), &no_overlap, &state),
+            Some((date(2025, 3, 10), date(2025, 3, 16)))
+        );
+
+        let state = DaemonState {
+            last_batch_end: Some(date(2025, 3, 16)),
+            ..DaemonState::default()
+        };
+        assert_eq!(batch_range(date(2025, 3, 16), &daemon, &state), None);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    commands::reconcile::installment_transactions,
+    error::{Error, Result},
+    export::ExportRow,
+    lunch_money::api::LunchMoney,
+    persist::Persistence,
+};
+use chrono::NaiveDate;
+
+/// Which batches and transactions to export.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Only transactions dated on or after this day.
+    pub start_date: Option<NaiveDate>,
+    /// Only transactions dated on or before this day.
+    pub end_date: Option<NaiveDate>,
+    /// Only reconciled (`Some(true)`) or unreconciled (`Some(false)`) batches.
+    pub reconciled: Option<bool>,
+}
+
+/// One row per batched transaction and installment of every saved batch that
+/// passes the filter, ordered by date. Transactions are fetched from Lunch
+/// Money for their date, payee and notes.
+pub async fn export_batches(
+    filter: &ExportFilter,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<Vec<ExportRow>> {
+    if let (Some(start), Some(end)) = (filter.start_date, filter.end_date)
+        && start > end
+    {
+        return Err(Error::InvalidDateRange);
+    }
+
+    let batches: Vec<_> = persistence
+        .all_batches()?
+        .into_iter()
+        .filter(|b| filter.reconciled.is_none_or(|r| r == b.reconciliation.is_some()))
+        .collect();
+    tracing::info!(batches = batches.len(), "Exporting batches");
+
+    let mut rows = vec![];
+    for batch in batches {
+        let mut txns = api.get_transactions_by_id(&batch.transaction_ids).await?;
+        txns.extend(installment_transactions(&batch.installments, api).await?);
+
+        let settlement = batch.reconciliation.as_ref();
+        rows.extend(
+            txns.into_iter()
+                .filter(|t| filter.start_date.is_none_or(|start| t.date >= start))
+                .filter(|t| filter.end_date.is_none_or(|end| t.date <= end))
+                .map(|t| ExportRow {
+                    batch_id: batch.id.clone(),
+                    transaction_id: t.id,
+                    date: t.date,
+                    payee: t.payee,
+                    amount: t.amount,
+                    notes: t.notes,
+                    settlement_credit_id: settlement.map(|s| s.settlement_credit_id),
+                    settlement_debit_id: settlement.map(|s| s.settlement_debit_id),
+                }),
+        );
+    }
+
+    rows.sort_by(|a, b| (a.date, &a.batch_id).cmp(&(b.date, &b.batch_id)));
+    tracing::info!(rows = rows.len(), "Export finished");
+    Ok(rows)
+}
@@ -1,2 +1,13 @@
+pub mod amortize;
+pub mod apply;
+pub mod balance;
+pub mod categories;
 pub mod create_batch;
+pub mod daemon;
+pub mod export;
 pub mod reconcile;
+pub mod remind;
+pub mod repair;
+pub mod resume;
+pub mod rules;
+pub mod spending;
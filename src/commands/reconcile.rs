@@ -1,6 +1,8 @@
 use crate::{
+    category_map::CategoryMap,
     config::Config,
     date_helpers,
+    email::{BatchNotifier, Txn},
     error::{Error, Result},
     lunch_money::{
         api::{
@@ -9,21 +11,48 @@ use crate::{
         },
         model::transaction::{Transaction, TransactionId, TransactionStatus},
     },
-    persist::{Batch, Persistence, Settlement},
+    marker::BatchMarker,
+    persist::{Batch, BatchInstallment, Persistence, Settlement},
     plugin::PluginManager,
-    usd::USD,
+    money::Money,
+    report::{BatchError, ReconcileAllReport, ReconcileReport},
 };
+use std::collections::BTreeMap;
 
 pub struct ReconcileAllResult {
     pub reconciled: u32,
+    pub reports: Vec<ReconcileReport>,
     pub errors: Vec<Error>,
 }
 
+impl ReconcileAllResult {
+    pub fn report(&self) -> ReconcileAllReport {
+        ReconcileAllReport {
+            reconciled: self.reports.clone(),
+            errors: self
+                .errors
+                .iter()
+                .map(|e| match e {
+                    Error::BatchReconcile { batch_id, source } => BatchError {
+                        batch_id: batch_id.clone(),
+                        error: source.to_string(),
+                    },
+                    other => BatchError {
+                        batch_id: String::new(),
+                        error: other.to_string(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
 pub async fn reconcile_all(
     config: &Config,
     creditor_api: &(impl LunchMoney + Sync),
     debtor_api: &(impl LunchMoney + Sync),
     persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
     plugins: &mut PluginManager,
 ) -> Result<ReconcileAllResult> {
     let unreconciled = persistence.unreconciled_batches()?;
@@ -34,16 +63,31 @@ pub async fn reconcile_all(
         tracing::info!("No unreconciled batches found");
         return Ok(ReconcileAllResult {
             reconciled: 0,
+            reports: vec![],
             errors: vec![],
         });
     }
 
     let mut reconciled = 0u32;
+    let mut reports: Vec<ReconcileReport> = vec![];
     let mut errors: Vec<Error> = vec![];
     for batch in unreconciled {
         let batch_id = batch.id.clone();
-        match reconcile_batch(batch, config, creditor_api, debtor_api, persistence, plugins).await {
-            Ok(()) => reconciled += 1,
+        match reconcile_batch(
+            batch,
+            config,
+            creditor_api,
+            debtor_api,
+            persistence,
+            notifier,
+            plugins,
+        )
+        .await
+        {
+            Ok(report) => {
+                reconciled += 1;
+                reports.push(report);
+            }
             Err(e) => {
                 tracing::warn!(batch_id, error = %e, "Failed to reconcile batch");
                 errors.push(Error::BatchReconcile {
@@ -55,7 +99,11 @@ pub async fn reconcile_all(
     }
 
     tracing::info!(reconciled, failed = errors.len(), total, "Reconcile-all complete");
-    Ok(ReconcileAllResult { reconciled, errors })
+    Ok(ReconcileAllResult {
+        reconciled,
+        reports,
+        errors,
+    })
 }
 
 pub async fn reconcile_batch_name(
@@ -64,14 +112,16 @@ pub async fn reconcile_batch_name(
     creditor_api: &(impl LunchMoney + Sync),
     debtor_api: &(impl LunchMoney + Sync),
     persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
     plugins: &mut PluginManager,
-) -> Result<()> {
+) -> Result<ReconcileReport> {
     reconcile_batch(
         persistence.get_batch(batch_name)?,
         config,
         creditor_api,
         debtor_api,
         persistence,
+        notifier,
         plugins,
     )
     .await
@@ -83,11 +133,15 @@ async fn reconcile_batch(
     creditor_api: &(impl LunchMoney + Sync),
     debtor_api: &(impl LunchMoney + Sync),
     persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
     plugins: &mut PluginManager,
-) -> Result<()> {
+) -> Result<ReconcileReport> {
     if batch.reconciliation.is_some() {
         return Err(Error::BatchAlreadyReconciled(batch.id));
     }
+    if let Some(adjustment_id) = batch.closed_by {
+        return Err(Error::BatchClosed(batch.id, adjustment_id));
+    }
 
     let span = tracing::info_span!("Reconcile Batch", batch_id = %batch.id);
     let _enter = span.enter();
@@ -99,9 +153,10 @@ async fn reconcile_batch(
         "Starting batch reconciliation"
     );
 
-    let batch_txns = creditor_api
+    let mut batch_txns = creditor_api
         .get_transactions_by_id(&batch.transaction_ids)
         .await?;
+    batch_txns.extend(installment_transactions(&batch.installments, creditor_api).await?);
 
     // Find the last transaction date to limit our settlement search window.
     let last_txn_date = batch_txns
@@ -196,8 +251,18 @@ async fn reconcile_batch(
 
     // Split the debtor settlement, or skip if a previous attempt already split it.
     // We don't need debtor child IDs because we intentionally don't clear them.
+    let debtor_splits = build_debtor_splits(&batch_txns, &batch.categories, &config.category_map);
+    let settled_splits: Vec<Txn> = debtor_splits
+        .iter()
+        .map(|split| Txn {
+            payee: split.payee.clone().unwrap_or_default(),
+            amount: split.amount,
+            date: split.date.unwrap_or(settlement_debit.date),
+            notes: split.notes.clone(),
+            installment: None,
+        })
+        .collect();
     if !settlement_debit.has_children {
-        let debtor_splits = build_debtor_splits(&batch_txns);
         debtor_api
             .update_split((settlement_debit.id, debtor_splits))
             .await?;
@@ -219,6 +284,12 @@ async fn reconcile_batch(
         clear_transactions(&[settlement_debit.id], debtor_api).await?;
     }
 
+    // Mark both settlement parents with the batch they settle.
+    if let Some(marker) = &config.batch_marker {
+        mark_settlement(marker, settlement_credit.id, &batch.id, creditor_api).await;
+        mark_settlement(marker, settlement_debit.id, &batch.id, debtor_api).await;
+    }
+
     // Dispatch to plugins before saving (which moves batch fields).
     plugins
         .dispatch(&crate::plugin::batch_reconciled_message(
@@ -228,32 +299,92 @@ async fn reconcile_batch(
         ))
         .await;
 
-    let batch_id = batch.id.clone();
+    let report = ReconcileReport {
+        batch_id: batch.id.clone(),
+        amount: batch.amount,
+        settlement_credit_id: settlement_credit.id,
+        settlement_debit_id: settlement_debit.id,
+    };
 
     // Save batch so we know it's reconciled.
     persistence.save_batch(&Batch {
-        id: batch.id,
-        amount: batch.amount,
-        transaction_ids: batch.transaction_ids,
         reconciliation: Some(Settlement {
             settlement_credit_id: settlement_credit.id,
             settlement_debit_id: settlement_debit.id,
+            settled_on: Some(settlement_credit.date),
         }),
+        ..batch
     })?;
 
+    // The batch is reconciled either way, so a failed email is only a warning.
+    if let Err(e) = notifier
+        .send_settled_notification(
+            &report.batch_id,
+            &report.amount,
+            settlement_credit.date,
+            &settled_splits,
+        )
+        .await
+    {
+        tracing::warn!(batch_id = report.batch_id, error = %e, "Failed to send settled email");
+    }
+
     tracing::info!(
-        batch_id,
+        batch_id = report.batch_id,
         settlement_credit_id = settlement_credit.id,
         settlement_debit_id = settlement_debit.id,
         "Batch reconciled"
     );
-    Ok(())
+    Ok(report)
+}
+
+/// Marking is only a convenience, so a failure is logged and the batch is
+/// still reconciled.
+async fn mark_settlement(
+    marker: &BatchMarker,
+    id: TransactionId,
+    batch_id: &str,
+    api: &(impl LunchMoney + Sync),
+) {
+    if let Err(e) = marker.mark(id, batch_id, api).await {
+        tracing::warn!(txn_id = id, error = %e, "Failed to mark settlement with its batch");
+    }
+}
+
+/// Stand-in transactions for a batch's amortization installments, so they get
+/// settlement splits like any other batched transaction. Each one is the
+/// original transaction with the installment's amount and due date.
+pub(crate) async fn installment_transactions(
+    installments: &[BatchInstallment],
+    api: &(impl LunchMoney + Sync),
+) -> Result<Vec<Transaction>> {
+    let ids: Vec<TransactionId> = installments.iter().map(|i| i.transaction_id).collect();
+    let originals = api.get_transactions_by_id(&ids).await?;
+
+    Ok(installments
+        .iter()
+        .zip(originals)
+        .map(|(installment, original)| installment_transaction(installment, original))
+        .collect())
+}
+
+fn installment_transaction(installment: &BatchInstallment, original: Transaction) -> Transaction {
+    let label = format!("installment {} of {}", installment.number, installment.count);
+    Transaction {
+        amount: installment.amount,
+        date: installment.due,
+        notes: Some(match original.notes {
+            Some(notes) => format!("{notes} ({label})"),
+            None => label,
+        }),
+        ..original
+    }
 }
 
 /// Find a transaction matching the expected amount in the given settlement account.
 pub fn find_settlement_transaction(
     candidates: &[Transaction],
-    expected_amount: USD,
+    expected_amount: Money,
     settlement_account_id: u32,
 ) -> Option<&Transaction> {
     candidates.iter().find(|t| {
@@ -304,14 +435,21 @@ pub fn build_creditor_splits(
         .collect()
 }
 
-/// Build debtor settlement splits: original amounts, payees, and notes passed through.
-pub fn build_debtor_splits(batch_txns: &[Transaction]) -> Vec<SplitUpdateItem> {
+/// Build debtor settlement splits: original amounts, payees, and notes passed
+/// through, with each transaction's creditor category mapped to the debtor's.
+/// `creditor_categories` holds the categories the transactions had before they
+/// were batched.
+pub fn build_debtor_splits(
+    batch_txns: &[Transaction],
+    creditor_categories: &BTreeMap<TransactionId, u32>,
+    category_map: &CategoryMap,
+) -> Vec<SplitUpdateItem> {
     batch_txns
         .iter()
         .map(|t| SplitUpdateItem {
             amount: t.amount,
             payee: Some(t.payee.to_owned()),
-            category_id: None,
+            category_id: category_map.debtor_category(creditor_categories.get(&t.id).copied()),
             notes: t.notes.clone(),
             date: Some(t.date),
         })
@@ -0,0 +1,103 @@
+use crate::{
+    config::{Config, Reminders},
+    email::{BatchNotifier, InstallmentInfo, Txn},
+    error::Result,
+    lunch_money::api::LunchMoney,
+    persist::{Batch, Persistence},
+};
+use chrono::{Days, NaiveDate};
+
+/// Remind the debtor about every unreconciled batch that's overdue as of
+/// `today`, and record each reminder on its batch. A batch whose transactions
+/// can't be fetched or whose reminder can't be sent is skipped with a warning
+/// and tried again next time.
+/// Returns the ids of the batches that were reminded about.
+pub async fn remind_overdue(
+    today: NaiveDate,
+    config: &Config,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+) -> Result<Vec<String>> {
+    let mut reminded = vec![];
+    for mut batch in persistence.unreconciled_batches()? {
+        if !reminder_due(&batch, today, &config.reminders) {
+            continue;
+        }
+        let txns = match batch_txns(&batch, api).await {
+            Ok(txns) => txns,
+            Err(e) => {
+                tracing::warn!(batch_id = %batch.id, error = %e, "Failed to fetch batch transactions for reminder");
+                continue;
+            }
+        };
+        // Older batches don't know when they were made; their latest transaction is close.
+        let Some(created) = batch
+            .created_on
+            .or_else(|| txns.iter().map(|t| t.date).max())
+        else {
+            continue;
+        };
+        let days_open = (today - created).num_days();
+        if days_open < config.reminders.after_days.into() {
+            continue;
+        }
+
+        tracing::info!(batch_id = %batch.id, days_open, "Sending reminder for overdue batch");
+        if let Err(e) = notifier
+            .send_reminder(&batch.id, &batch.amount, &txns, days_open)
+            .await
+        {
+            tracing::warn!(batch_id = %batch.id, error = %e, "Failed to send reminder");
+            continue;
+        }
+        batch.reminders.push(today);
+        persistence.save_batch(&batch)?;
+        reminded.push(batch.id);
+    }
+    Ok(reminded)
+}
+
+/// Whether `batch` is old enough for a reminder and wasn't reminded about too
+/// recently. Batches that don't know when they were made are checked against
+/// their transactions once they've been fetched.
+pub fn reminder_due(batch: &Batch, today: NaiveDate, reminders: &Reminders) -> bool {
+    let old_enough = batch
+        .created_on
+        .is_none_or(|created| created + Days::new(reminders.after_days.into()) <= today);
+    let not_recent = batch
+        .reminders
+        .last()
+        .is_none_or(|last| *last + Days::new(reminders.every_days.into()) <= today);
+    batch.reconciliation.is_none() && old_enough && not_recent
+}
+
+/// The batch's itemised list, as it was in the batch email.
+async fn batch_txns(batch: &Batch, api: &(impl LunchMoney + Sync)) -> Result<Vec<Txn>> {
+    let mut txns: Vec<Txn> = api
+        .get_transactions_by_id(&batch.transaction_ids)
+        .await?
+        .into_iter()
+        .map(|t| Txn {
+            payee: t.payee,
+            amount: t.amount,
+            date: t.date,
+            notes: t.notes,
+            installment: None,
+        })
+        .collect();
+
+    let ids: Vec<_> = batch.installments.iter().map(|i| i.transaction_id).collect();
+    let originals = api.get_transactions_by_id(&ids).await?;
+    txns.extend(batch.installments.iter().zip(originals).map(|(i, original)| Txn {
+        payee: original.payee,
+        amount: i.amount,
+        date: i.due,
+        notes: original.notes,
+        installment: Some(InstallmentInfo {
+            number: i.number,
+            count: i.count,
+        }),
+    }));
+    Ok(txns)
+}
@@ -0,0 +1,81 @@
+use crate::error::Result;
+use crate::lunch_money::api::update_transaction::{SplitUpdateItem, TransactionUpdateItem};
+use crate::lunch_money::api::LunchMoney;
+use crate::persist::{Persistence, SplitSnapshot};
+use crate::report::{RepairReport, RevertError};
+
+/// Put back every split whose snapshot is still on disk, i.e. every re-split
+/// that failed after unsplitting and couldn't be restored at the time.
+pub async fn repair_splits(
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<RepairReport> {
+    let span = tracing::info_span!("Repair Splits");
+    let _enter = span.enter();
+
+    let mut report = RepairReport {
+        repaired: vec![],
+        errors: vec![],
+    };
+
+    for snapshot in persistence.split_snapshots()? {
+        let parent_id = snapshot.parent_id;
+        let result = match api.get_transaction(parent_id).await {
+            // Split again since, by hand or otherwise. Don't stack another split on top.
+            Ok(parent) if parent.has_children => {
+                Err("already has splits; left alone — check it and restore by hand if needed".to_string())
+            }
+            Ok(_) => restore_split(&snapshot, api).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(()) => {
+                tracing::info!(parent_id, "Original split restored");
+                persistence.remove_split_snapshot(parent_id)?;
+                report.repaired.push(parent_id);
+            }
+            Err(error) => {
+                tracing::warn!(parent_id, error, "Could not restore original split");
+                report.errors.push(RevertError {
+                    transaction_id: parent_id,
+                    error,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Split the (unsplit) parent back into the snapshot's children. Split items
+/// can't carry tags, so those are put back on the new children afterwards.
+pub async fn restore_split(snapshot: &SplitSnapshot, api: &(impl LunchMoney + Sync)) -> Result<()> {
+    let splits = snapshot
+        .children
+        .iter()
+        .map(|c| SplitUpdateItem {
+            amount: c.amount,
+            payee: Some(c.payee.clone()),
+            category_id: c.category_id,
+            notes: c.notes.clone(),
+            date: Some(c.date),
+        })
+        .collect();
+    let response = api.update_split((snapshot.parent_id, splits)).await?;
+
+    for (id, original) in response.split_ids.iter().zip(&snapshot.children) {
+        if original.tags.is_empty() {
+            continue;
+        }
+        let update = TransactionUpdateItem {
+            payee: None,
+            category_id: None,
+            notes: None,
+            tags: Some(original.tag_names().into_iter().cloned().collect()),
+            status: None,
+        };
+        api.update_transaction((*id, update)).await?;
+    }
+    Ok(())
+}
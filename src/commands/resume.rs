@@ -0,0 +1,180 @@
+use crate::commands::create_batch::journal::{Journal, Step};
+use crate::commands::create_batch::{resplit_snapshot, run_journal, split_children};
+use crate::email::BatchNotifier;
+use crate::error::Result;
+use crate::commands::repair::restore_split;
+use crate::lunch_money::api::update_transaction::TransactionUpdateItem;
+use crate::lunch_money::api::LunchMoney;
+use crate::lunch_money::model::transaction::Transaction;
+use crate::persist::{Persistence, SplitSnapshot};
+use crate::plugin::PluginManager;
+use crate::report::{BatchReport, RevertError, RollbackReport};
+
+/// Finish a `create-batch` run that stopped partway: carry out the steps it
+/// didn't get to, then save and send the batch. Returns `None` when there's no
+/// unfinished run.
+pub async fn resume_batch(
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+    notifier: &(impl BatchNotifier + Sync),
+    plugins: &mut PluginManager,
+) -> Result<Option<BatchReport>> {
+    let Some(journal) = persistence.unfinished_journal()? else {
+        tracing::info!("No unfinished create-batch run to resume");
+        return Ok(None);
+    };
+
+    tracing::info!(
+        batch_id = %journal.batch_id,
+        steps_started = journal.steps.len(),
+        steps_planned = journal.planned_steps().len(),
+        "Resuming create-batch run"
+    );
+    run_journal(journal, api, persistence, notifier, plugins)
+        .await
+        .map(Some)
+}
+
+/// Undo a `create-batch` run that stopped partway, putting every transaction
+/// it touched back the way the plan found it. No batch is saved. The journal is
+/// kept if anything couldn't be restored, so the rollback can be tried again.
+pub async fn rollback_batch(
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<Option<RollbackReport>> {
+    let Some(journal) = persistence.unfinished_journal()? else {
+        tracing::info!("No unfinished create-batch run to roll back");
+        return Ok(None);
+    };
+
+    let span = tracing::info_span!("Rollback", batch_id = %journal.batch_id);
+    let _enter = span.enter();
+
+    let report = rollback_journal(&journal, api, persistence).await?;
+    tracing::info!(
+        reverted = report.reverted.len(),
+        failed = report.errors.len(),
+        "Rollback finished"
+    );
+    Ok(Some(report))
+}
+
+/// Revert every step of the journal that may have changed anything, latest
+/// first. Reverting writes the snapshot's values back, so it's safe for steps
+/// that never went through. A category or note the transaction didn't have
+/// can't be cleared through the API, so those are left as the run set them.
+/// The journal is removed unless something couldn't be reverted, so the
+/// rollback can be tried again.
+pub async fn rollback_journal(
+    journal: &Journal,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<RollbackReport> {
+    let mut report = RollbackReport {
+        batch_id: journal.batch_id.clone(),
+        reverted: vec![],
+        errors: vec![],
+    };
+
+    for record in journal.steps.iter().rev().filter(|r| r.may_have_changed()) {
+        let plan = &journal.plan;
+        let (id, result) = match record.step {
+            Step::Add(id) => {
+                let original = &plan
+                    .adds
+                    .iter()
+                    .find(|a| a.transaction.id == id)
+                    .expect("journal step is not in its plan")
+                    .transaction;
+                (id, restore_transaction(original, api).await)
+            }
+            Step::Amortize(id) => {
+                let original = &plan
+                    .amortizations
+                    .iter()
+                    .find(|a| a.transaction.id == id)
+                    .expect("journal step is not in its plan")
+                    .transaction;
+                (id, restore_transaction(original, api).await)
+            }
+            Step::Split(id) => {
+                let original = &plan
+                    .splits
+                    .iter()
+                    .find(|s| s.transaction.id == id)
+                    .expect("journal step is not in its plan")
+                    .transaction;
+                (id, revert_split(original, api).await)
+            }
+            Step::Resplit(parent_id) => {
+                let resplit = plan
+                    .resplits
+                    .iter()
+                    .find(|r| r.parent_id == parent_id)
+                    .expect("journal step is not in its plan");
+                (parent_id, revert_resplit(resplit_snapshot(resplit), api).await)
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                tracing::info!(txn_id = id, ?record.step, "Reverted");
+                if let Step::Resplit(parent_id) = record.step {
+                    // A re-split interrupted after unsplitting left its snapshot behind.
+                    persistence.remove_split_snapshot(parent_id)?;
+                }
+                report.reverted.push(id);
+            }
+            Err(e) => {
+                tracing::warn!(txn_id = id, ?record.step, error = %e, "Failed to revert");
+                report.errors.push(RevertError {
+                    transaction_id: id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    if report.errors.is_empty() {
+        persistence.remove_journal(&journal.batch_id)?;
+    }
+    Ok(report)
+}
+
+/// Write back the payee, category, notes, tags and status of `original`.
+async fn restore_transaction(original: &Transaction, api: &(impl LunchMoney + Sync)) -> Result<()> {
+    let update = TransactionUpdateItem {
+        payee: Some(original.payee.clone()),
+        category_id: original.category_id,
+        notes: original.notes.clone(),
+        tags: Some(original.tag_names().into_iter().cloned().collect()),
+        status: Some(original.status.clone()),
+    };
+    api.update_transaction((original.id, update)).await
+}
+
+async fn revert_split(original: &Transaction, api: &(impl LunchMoney + Sync)) -> Result<()> {
+    if api.get_transaction(original.id).await?.has_children {
+        api.unsplit_transaction(original.id).await?;
+    }
+    restore_transaction(original, api).await
+}
+
+/// Put a re-split parent back to its original children, unless it still has them.
+async fn revert_resplit(snapshot: SplitSnapshot, api: &(impl LunchMoney + Sync)) -> Result<()> {
+    let parent_id = snapshot.parent_id;
+    let parent = api.get_transaction(parent_id).await?;
+    if parent.has_children {
+        let children = split_children(&parent, api).await?;
+        if !children.is_empty()
+            && children
+                .iter()
+                .all(|c| snapshot.children.iter().any(|o| o.id == c.id))
+        {
+            return Ok(());
+        }
+        api.unsplit_transaction(parent_id).await?;
+    }
+
+    restore_split(&snapshot, api).await
+}
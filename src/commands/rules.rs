@@ -0,0 +1,54 @@
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    lunch_money::{api::LunchMoney, model::transaction::Transaction},
+    rules::{self, RuleAction},
+};
+use chrono::NaiveDate;
+
+pub struct RuleTestResult {
+    pub rule_name: String,
+    pub action: RuleAction,
+    pub matches: Vec<Transaction>,
+}
+
+/// Show which transactions in the date range each rule would match, without
+/// changing anything. Each rule is evaluated on its own, so a transaction can
+/// show up under several rules; in a real run only the first matching rule applies.
+pub async fn test_rules(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    config: &Config,
+    api: &(impl LunchMoney + Sync),
+) -> Result<Vec<RuleTestResult>> {
+    if start_date > end_date {
+        return Err(Error::InvalidDateRange);
+    }
+
+    let txns = api.get_transactions(start_date, end_date).await?;
+    let eligible: Vec<&Transaction> = txns
+        .iter()
+        .filter(|t| rules::is_eligible(t, &config.tags, config.creditor.proxy_category_id))
+        .collect();
+
+    tracing::info!(
+        total = txns.len(),
+        eligible = eligible.len(),
+        rules = config.rules.len(),
+        "Testing rules"
+    );
+
+    Ok(config
+        .rules
+        .iter()
+        .map(|rule| RuleTestResult {
+            rule_name: rule.name.clone(),
+            action: rule.action.clone(),
+            matches: eligible
+                .iter()
+                .filter(|t| rule.matches(t))
+                .map(|t| (*t).clone())
+                .collect(),
+        })
+        .collect())
+}
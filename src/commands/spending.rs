@@ -0,0 +1,168 @@
+use crate::{
+    commands::export::{export_batches, ExportFilter},
+    config::Config,
+    error::Result,
+    lunch_money::api::LunchMoney,
+    money::{Currency, Money},
+    persist::Persistence,
+    report::{ReconcileTimes, SpendingReport, SpendingTotal},
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Add up everything batched between `from` and `to` by month, by the
+/// creditor's original category and by payee, and work out how long batches
+/// took to be reconciled. Only amounts in the profile's currency are counted.
+pub async fn spending_report(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    config: &Config,
+    api: &(impl LunchMoney + Sync),
+    persistence: &(impl Persistence + Sync),
+) -> Result<SpendingReport> {
+    let currency = config.creditor.currency;
+    let filter = ExportFilter {
+        start_date: from,
+        end_date: to,
+        reconciled: None,
+    };
+    let rows = export_batches(&filter, api, persistence).await?;
+    let (rows, other_currencies): (Vec<_>, Vec<_>) =
+        rows.into_iter().partition(|r| r.amount.currency() == currency);
+    if !other_currencies.is_empty() {
+        tracing::warn!(count = other_currencies.len(), %currency, "Leaving out transactions in other currencies");
+    }
+
+    let batches: BTreeMap<String, _> = persistence
+        .all_batches()?
+        .into_iter()
+        .map(|b| (b.id.clone(), b))
+        .collect();
+    let category_names: BTreeMap<u32, String> = api
+        .get_categories()
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let mut by_month = Totals::new(currency);
+    let mut by_category = Totals::new(currency);
+    let mut by_payee = Totals::new(currency);
+    for row in &rows {
+        by_month.add(row.date.format("%Y-%m").to_string(), row.amount);
+        let category = batches
+            .get(&row.batch_id)
+            .and_then(|b| b.categories.get(&row.transaction_id))
+            .map(|id| category_names.get(id).cloned().unwrap_or_else(|| format!("category {id}")))
+            .unwrap_or_else(|| "Uncategorized".to_string());
+        by_category.add(category, row.amount);
+        by_payee.add(row.payee.clone(), row.amount);
+    }
+
+    // Time to reconcile, for the batches with transactions in the range. A
+    // batch closed by an adjustment counts as settled on the adjustment's date.
+    let closed_on: BTreeMap<String, NaiveDate> = persistence
+        .adjustments()?
+        .into_iter()
+        .map(|a| (a.id, a.date))
+        .collect();
+    let mut days: Vec<i64> = vec![];
+    let mut unreconciled = 0;
+    for batch_id in rows.iter().map(|r| &r.batch_id).collect::<BTreeSet<_>>() {
+        let batch = &batches[batch_id];
+        if batch.is_open() {
+            unreconciled += 1;
+            continue;
+        }
+        let settled = match &batch.reconciliation {
+            Some(settlement) => settlement.settled_on,
+            None => batch.closed_by.as_ref().and_then(|id| closed_on.get(id).copied()),
+        };
+        let Some(settled) = settled else {
+            tracing::debug!(batch_id, "Batch doesn't know when it was settled, leaving it out of reconcile times");
+            continue;
+        };
+        // Older batches don't know when they were made; their latest transaction is close.
+        let created = batch.created_on.or_else(|| {
+            rows.iter()
+                .filter(|r| &r.batch_id == batch_id)
+                .map(|r| r.date)
+                .max()
+        });
+        if let Some(created) = created {
+            days.push((settled - created).num_days().max(0));
+        }
+    }
+
+    let total = rows
+        .iter()
+        .fold(Money::zero(currency), |total, r| total + r.amount);
+    tracing::info!(transactions = rows.len(), %total, "Spending report ready");
+
+    Ok(SpendingReport {
+        from,
+        to,
+        total,
+        transaction_count: rows.len(),
+        by_month: by_month.chronological(),
+        by_category: by_category.largest_first(),
+        by_payee: by_payee.largest_first(),
+        reconcile_times: reconcile_times(days, unreconciled),
+    })
+}
+
+struct Totals {
+    currency: Currency,
+    totals: BTreeMap<String, (usize, Money)>,
+}
+
+impl Totals {
+    fn new(currency: Currency) -> Self {
+        Self {
+            currency,
+            totals: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, name: String, amount: Money) {
+        let entry = self
+            .totals
+            .entry(name)
+            .or_insert((0, Money::zero(self.currency)));
+        entry.0 += 1;
+        entry.1 = entry.1 + amount;
+    }
+
+    fn chronological(self) -> Vec<SpendingTotal> {
+        self.totals
+            .into_iter()
+            .map(|(name, (count, total))| SpendingTotal { name, count, total })
+            .collect()
+    }
+
+    fn largest_first(self) -> Vec<SpendingTotal> {
+        let mut totals = self.chronological();
+        totals.sort_by_key(|t| std::cmp::Reverse(t.total.value()));
+        totals
+    }
+}
+
+fn reconcile_times(mut days: Vec<i64>, unreconciled: usize) -> ReconcileTimes {
+    days.sort_unstable();
+    let median_days = match days.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(Decimal::from(days[n / 2])),
+        n => Some(Decimal::from(days[n / 2 - 1] + days[n / 2]) / Decimal::from(2)),
+    };
+    let mean_days = (!days.is_empty())
+        .then(|| (Decimal::from(days.iter().sum::<i64>()) / Decimal::from(days.len())).round_dp(1));
+    ReconcileTimes {
+        reconciled: days.len(),
+        unreconciled,
+        min_days: days.first().copied(),
+        max_days: days.last().copied(),
+        median_days,
+        mean_days,
+    }
+}
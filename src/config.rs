@@ -1,19 +1,235 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
+use chrono::Weekday;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 
+use crate::category_map::CategoryMap;
+use crate::email::Party;
+use crate::marker::BatchMarker;
+use crate::money::Currency;
+use crate::payment::PaymentMethod;
 use crate::persist;
+use crate::rules::{self, Rule};
+use crate::secret::Secret;
 
 pub const TAG_BATCH_SPLIT: &str = "eq-to-split";
 pub const TAG_BATCH_ADD: &str = "eq-to-batch";
+pub const TAG_AMORTIZE: &str = "eq-to-amortize";
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub creditor: Creditor,
     pub debtor: Debtor,
-    pub jmap: JMAP,
+    /// How email is sent: set either `jmap` or `smtp`.
+    #[serde(default)]
+    pub jmap: Option<JMAP>,
+    #[serde(default)]
+    pub smtp: Option<Smtp>,
+    /// More places notifications are sent, alongside `jmap` or `smtp`.
+    #[serde(default)]
+    pub notifiers: Vec<NotifierEntry>,
+    #[serde(default)]
+    pub tags: Tags,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
     #[serde(default)]
     pub plugins: Vec<PluginEntry>,
+    #[serde(default)]
+    pub daemon: Daemon,
+    /// Write a reference to the batch onto batched and settlement transactions.
+    #[serde(default)]
+    pub batch_marker: Option<BatchMarker>,
+    /// Debtor categories for the debtor's settlement splits.
+    #[serde(default)]
+    pub category_map: CategoryMap,
+    #[serde(default)]
+    pub reminders: Reminders,
+}
+
+/// The Lunch Money tags that mark transactions for equailizer. Profiles that
+/// share a Lunch Money account can give each profile its own tags.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Tags {
+    #[serde(default = "default_add_tag")]
+    pub add: String,
+    #[serde(default = "default_split_tag")]
+    pub split: String,
+    #[serde(default)]
+    pub add_aliases: Vec<String>,
+    #[serde(default)]
+    pub split_aliases: Vec<String>,
+    /// Prefix of the tag that amortizes a transaction, followed by the number
+    /// of months, e.g. `eq-to-amortize-12`.
+    #[serde(default = "default_amortize_tag")]
+    pub amortize: String,
+    /// Whether the trigger tag is stripped from a transaction once it's batched.
+    #[serde(default = "default_true")]
+    pub remove_after_processing: bool,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            add: default_add_tag(),
+            split: default_split_tag(),
+            add_aliases: vec![],
+            split_aliases: vec![],
+            amortize: default_amortize_tag(),
+            remove_after_processing: true,
+        }
+    }
+}
+
+impl Tags {
+    pub fn is_add_tag(&self, name: &str) -> bool {
+        self.add == name || self.add_aliases.iter().any(|a| a == name)
+    }
+
+    pub fn is_split_tag(&self, name: &str) -> bool {
+        self.split == name || self.split_aliases.iter().any(|a| a == name)
+    }
+
+    pub fn is_amortize_tag(&self, name: &str) -> bool {
+        name == self.amortize
+            || name
+                .strip_prefix(self.amortize.as_str())
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+
+    /// The number of months in an amortize tag, or `None` if it isn't an
+    /// amortize tag with a valid month count.
+    pub fn amortize_months(&self, name: &str) -> Option<u32> {
+        name.strip_prefix(self.amortize.as_str())?
+            .strip_prefix('-')?
+            .parse::<u32>()
+            .ok()
+            .filter(|months| *months > 0)
+    }
+
+    /// Whether the tag is any equailizer trigger tag for this profile.
+    pub fn is_trigger_tag(&self, name: &str) -> bool {
+        self.is_add_tag(name) || self.is_split_tag(name) || self.is_amortize_tag(name)
+    }
+}
+
+fn default_add_tag() -> String {
+    TAG_BATCH_ADD.to_string()
+}
+
+fn default_split_tag() -> String {
+    TAG_BATCH_SPLIT.to_string()
+}
+
+fn default_amortize_tag() -> String {
+    TAG_AMORTIZE.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// When `equailizer daemon` runs each job. Times are US Eastern, like the rest
+/// of equailizer's dates.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Daemon {
+    /// Day of the week to create a batch, e.g. `"Sun"`.
+    #[serde(default = "default_batch_weekday")]
+    pub batch_weekday: Weekday,
+    /// Hour of the day (0-23) to create a batch.
+    #[serde(default = "default_batch_hour")]
+    pub batch_hour: u32,
+    /// How often to try `reconcile-all`.
+    #[serde(default = "default_reconcile_every_hours")]
+    pub reconcile_every_hours: u32,
+    /// How far back the first batch reaches when there's no previous one.
+    #[serde(default = "default_initial_days_ago")]
+    pub initial_days_ago: u16,
+    /// How many days before the previous batch's end each later batch reaches
+    /// back, to catch transactions imported or tagged after it ran.
+    /// Transactions already batched in the overlap are left out.
+    #[serde(default = "default_overlap_days")]
+    pub overlap_days: u16,
+    /// Run each batch all-or-nothing, like `create-batch --all-or-nothing`.
+    #[serde(default)]
+    pub all_or_nothing: bool,
+    /// Send reminders for overdue batches after each reconcile, like `remind`.
+    #[serde(default)]
+    pub remind: bool,
+}
+
+impl Default for Daemon {
+    fn default() -> Self {
+        Self {
+            batch_weekday: default_batch_weekday(),
+            batch_hour: default_batch_hour(),
+            reconcile_every_hours: default_reconcile_every_hours(),
+            initial_days_ago: default_initial_days_ago(),
+            overlap_days: default_overlap_days(),
+            all_or_nothing: false,
+            remind: false,
+        }
+    }
+}
+
+fn default_batch_weekday() -> Weekday {
+    Weekday::Sun
+}
+
+fn default_batch_hour() -> u32 {
+    9
+}
+
+fn default_reconcile_every_hours() -> u32 {
+    4
+}
+
+fn default_initial_days_ago() -> u16 {
+    7
+}
+
+fn default_overlap_days() -> u16 {
+    7
+}
+
+fn validate_daemon(daemon: &Daemon) -> Result<()> {
+    if daemon.batch_hour > 23 {
+        return Err(Error::Config("daemon batch_hour must be between 0 and 23".to_string()));
+    }
+    if daemon.reconcile_every_hours == 0 {
+        return Err(Error::Config(
+            "daemon reconcile_every_hours must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// When the debtor is reminded about a batch that hasn't been reconciled.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Reminders {
+    /// How many days old a batch is before the first reminder.
+    #[serde(default = "default_remind_after_days")]
+    pub after_days: u32,
+    /// The fewest days between two reminders for the same batch.
+    #[serde(default = "default_remind_every_days")]
+    pub every_days: u32,
+}
+
+impl Default for Reminders {
+    fn default() -> Self {
+        Self {
+            after_days: default_remind_after_days(),
+            every_days: default_remind_every_days(),
+        }
+    }
+}
+
+fn default_remind_after_days() -> u32 {
+    14
+}
+
+fn default_remind_every_days() -> u32 {
+    7
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,34 +247,173 @@ pub enum PluginType {
 
 #[derive(Debug, Deserialize)]
 pub struct Creditor {
-    pub api_key: String,
+    pub api_key: Secret,
     pub proxy_category_id: u32,
     pub settlement_account_id: u32,
     pub email_address: String,
+    /// The currency batches are settled in. Transactions in any other currency
+    /// are left out of batches with a warning.
+    #[serde(default)]
+    pub currency: Currency,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Debtor {
-    pub api_key: String,
+    pub api_key: Secret,
     pub name: String,
     pub settlement_account_id: u32,
     pub email_address: String,
-    pub venmo_username: String,
+    /// The debtor's Venmo account, from before `payment_methods`. It's used
+    /// as a Venmo payment method.
+    #[serde(default)]
+    pub venmo_username: Option<String>,
+    #[serde(default)]
+    pub payment_methods: Vec<PaymentMethod>,
+}
+
+impl Debtor {
+    /// Every way the debtor can pay, with `venmo_username` first.
+    pub fn payment_methods(&self) -> Vec<PaymentMethod> {
+        let venmo = self
+            .venmo_username
+            .iter()
+            .map(|username| PaymentMethod::Venmo { username: username.clone() });
+        venmo.chain(self.payment_methods.iter().cloned()).collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JMAP {
     pub api_session_endpoint: String,
-    pub api_key: String,
+    pub api_key: Secret,
     pub sent_mailbox: String,
     pub sending_address: String,
 }
 
+/// An SMTP server to send email through, for accounts without JMAP.
+#[derive(Debug, Deserialize)]
+pub struct Smtp {
+    pub host: String,
+    /// Defaults to the usual port for `tls`.
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// Leave out both `username` and `password` for a server that doesn't
+    /// need to log in.
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<Secret>,
+    pub sending_address: String,
+}
+
+impl Smtp {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            SmtpTls::Tls => 465,
+            SmtpTls::Starttls => 587,
+            SmtpTls::None => 25,
+        })
+    }
+}
+
+/// How the SMTP connection is encrypted.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// TLS from the start of the connection.
+    Tls,
+    /// A plain connection upgraded with STARTTLS, which must be offered.
+    #[default]
+    Starttls,
+    /// No encryption, e.g. for a local relay.
+    None,
+}
+
+/// One place notifications are sent, as listed in `notifiers`.
+#[derive(Debug, Deserialize)]
+pub struct NotifierEntry {
+    /// Names the notifier in logs and failure reports. Defaults to its type.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Whose emails this notifier sends.
+    #[serde(default = "default_recipients")]
+    pub recipients: Vec<Party>,
+    #[serde(flatten)]
+    pub kind: NotifierKind,
+}
+
+impl NotifierEntry {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(match self.kind {
+            NotifierKind::Jmap(_) => "jmap",
+            NotifierKind::Smtp(_) => "smtp",
+            NotifierKind::File { .. } => "file",
+            NotifierKind::Webhook { .. } => "webhook",
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierKind {
+    Jmap(JMAP),
+    Smtp(Smtp),
+    /// Writes each email to `dir` as an `.eml` file, or prints it to stderr
+    /// when there's no `dir`.
+    File {
+        #[serde(default)]
+        dir: Option<String>,
+    },
+    /// POSTs each email as JSON to `url`, with any extra `headers`.
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, Secret>,
+    },
+}
+
+fn default_recipients() -> Vec<Party> {
+    vec![Party::Creditor, Party::Debtor]
+}
+
+fn validate_email(config: &Config) -> Result<()> {
+    if config.jmap.is_some() && config.smtp.is_some() {
+        return Err(Error::Config("set only one of jmap and smtp".to_string()));
+    }
+    if config.jmap.is_none() && config.smtp.is_none() && config.notifiers.is_empty() {
+        return Err(Error::Config(
+            "set jmap, smtp or notifiers for sending notifications".to_string(),
+        ));
+    }
+    let listed = config.notifiers.iter().filter_map(|n| match &n.kind {
+        NotifierKind::Smtp(smtp) => Some(smtp),
+        _ => None,
+    });
+    if config
+        .smtp
+        .iter()
+        .chain(listed)
+        .any(|smtp| smtp.username.is_some() != smtp.password.is_some())
+    {
+        return Err(Error::Config(
+            "smtp username and password must be set together".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub fn read_config(profile: &str) -> Result<Config> {
     let mut config_path = persist::base_path()?;
     config_path.push(format!("profiles/{}/config.json", profile));
 
     let file = fs::read_to_string(config_path).expect("config.json should be present");
     let parsed: Config = serde_json::from_str(&file)?;
+    rules::validate_rules(&parsed.rules)?;
+    validate_daemon(&parsed.daemon)?;
+    validate_email(&parsed)?;
     Ok(parsed)
 }
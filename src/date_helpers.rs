@@ -1,5 +1,5 @@
-use chrono::NaiveDate;
-use chrono_tz::US::Eastern;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::{Tz, US::Eastern};
 
 // Use this for naive dates - I'm based in Eastern currently
 // and it's easier to just use a single reference time zone for all ops
@@ -7,6 +7,10 @@ pub fn now_date_naive_eastern() -> NaiveDate {
     chrono::Utc::now().with_timezone(&Eastern).date_naive()
 }
 
+pub fn now_eastern() -> DateTime<Tz> {
+    chrono::Utc::now().with_timezone(&Eastern)
+}
+
 pub trait DefaultDate {
     fn or_naive_date_now(&self) -> NaiveDate;
 }
@@ -1,19 +1,48 @@
+mod file;
+mod jmap;
+mod smtp;
+mod webhook;
+
 use crate::date_helpers;
 use crate::error::{Error, Result};
-use crate::usd::USD;
+use crate::money::Money;
+use crate::payment::{PaymentMethod, PaymentRequest};
+use crate::template::{Escape, RuntimeTemplate};
 use askama::Template;
 use async_trait::async_trait;
 use chrono::NaiveDate;
-use jmap_client::{client::Client, core::response::MethodResponse::*, email::EmailBodyPart};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::BTreeMap;
+use std::path::Path;
+
+pub use file::FileTransport;
+pub use jmap::JmapTransport;
+pub use smtp::SmtpTransport;
+pub use webhook::WebhookTransport;
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Txn {
     pub payee: String,
-    pub amount: USD,
+    pub amount: Money,
     pub date: NaiveDate,
     #[allow(unused_variables)]
     // This is used in the askama html template, which isn't seen by the linter
     pub notes: Option<String>,
+    pub installment: Option<InstallmentInfo>,
+}
+
+/// Marks an email line item as one installment of an amortized expense.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstallmentInfo {
+    pub number: u32,
+    pub count: u32,
+}
+
+impl std::fmt::Display for InstallmentInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "installment {} of {}", self.number, self.count)
+    }
 }
 
 #[async_trait]
@@ -21,319 +50,595 @@ pub trait BatchNotifier: Send + Sync {
     async fn send_batch_notification(
         &self,
         batch_id: &str,
-        total: &USD,
+        total: &Money,
         txns: &[Txn],
         warnings: Vec<String>,
     ) -> Result<()>;
+
+    /// Tell both parties a batch was settled on `settled_on`, with the splits
+    /// made on the debtor's side.
+    async fn send_settled_notification(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        settled_on: NaiveDate,
+        splits: &[Txn],
+    ) -> Result<()>;
+
+    /// Remind the debtor about a batch that's been open for `days_open` days.
+    async fn send_reminder(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        txns: &[Txn],
+        days_open: i64,
+    ) -> Result<()>;
+}
+
+/// Sends emails through a mail server. The emails are built the same way
+/// whichever transport a profile uses.
+#[async_trait]
+pub trait MailTransport: Send + Sync {
+    /// Send `emails` in order, stopping at the first one that fails.
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: MailTransport + ?Sized> MailTransport for Box<T> {
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()> {
+        (**self).send(emails).await
+    }
 }
 
-pub struct JmapBatchNotifier {
-    pub api_session_endpoint: String,
-    pub api_key: String,
-    pub sent_mailbox: String,
+/// One side of a batch, as the recipient of an email.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Party {
+    Creditor,
+    Debtor,
+}
+
+impl Party {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Party::Creditor => "creditor",
+            Party::Debtor => "debtor",
+        }
+    }
+}
+
+/// Notifies by email, sent through `transport`. Only emails to `recipients`
+/// are sent; anyone copied on them still gets their copy.
+pub struct EmailNotifier<T> {
+    pub emails: BatchEmails,
+    pub transport: T,
+    pub recipients: Vec<Party>,
+    pub dry_run: bool,
+}
+
+/// Every notifier a profile has, each sent every notification in turn. One
+/// failing doesn't stop the others; the failures are returned together as
+/// [`Error::NotifiersFailed`].
+pub struct Notifiers {
+    pub notifiers: Vec<(String, Box<dyn BatchNotifier>)>,
+}
+
+pub type JmapBatchNotifier = EmailNotifier<JmapTransport>;
+pub type SmtpBatchNotifier = EmailNotifier<SmtpTransport>;
+
+/// Builds each notification's emails.
+pub struct BatchEmails {
     pub sending_address: String,
     pub creditor_email: String,
     pub debtor_email: String,
-    pub debtor_venmo_username: String,
-    pub dry_run: bool,
+    pub payment_methods: Vec<PaymentMethod>,
+    pub templates: EmailTemplates,
+}
+
+/// One email, addressed and ready to send.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct OutgoingEmail {
+    /// Which email this is, e.g. `creditor` or `reminder`.
+    pub label: &'static str,
+    pub from: String,
+    /// Which party `to` is.
+    pub recipient: Party,
+    pub to: String,
+    pub cc: Option<String>,
+    /// The Message-ID it's sent with, and the one it replies to, so mail
+    /// clients can show a batch's emails as one thread.
+    pub message_id: String,
+    pub in_reply_to: Option<String>,
+    pub content: EmailContent,
+}
+
+/// One email, ready to send.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EmailContent {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// A profile's overrides for the built-in emails, from its `templates`
+/// directory. Each email (`creditor`, `debtor`, `reminder` or `settled`) can override
+/// its subject (`<email>_subject.txt`), plain-text body (`<email>.txt`) and
+/// HTML body (`<email>.html`) separately; anything not overridden is built in.
+#[derive(Debug, Clone, Default)]
+pub struct EmailTemplates {
+    templates: BTreeMap<String, RuntimeTemplate>,
+}
+
+const TEMPLATE_FILES: [&str; 12] = [
+    "creditor_subject.txt",
+    "creditor.txt",
+    "creditor.html",
+    "debtor_subject.txt",
+    "debtor.txt",
+    "debtor.html",
+    "reminder_subject.txt",
+    "reminder.txt",
+    "reminder.html",
+    "settled_subject.txt",
+    "settled.txt",
+    "settled.html",
+];
+
+impl EmailTemplates {
+    /// Read the overrides in `dir`. A missing directory means no overrides;
+    /// a template that doesn't parse is an error, so it's found before any
+    /// email is due.
+    pub fn load(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            return Ok(Self::default());
+        }
+        let mut sources = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !TEMPLATE_FILES.contains(&name) {
+                tracing::warn!(path = %path.display(), "Ignoring unknown email template");
+                continue;
+            }
+            sources.push((name.to_string(), std::fs::read_to_string(&path)?));
+        }
+        let templates = Self::from_sources(sources)?;
+        tracing::debug!(overrides = ?templates.templates.keys().collect::<Vec<_>>(), "Email templates loaded");
+        Ok(templates)
+    }
+
+    /// Overrides keyed by file name, e.g. `("creditor.html", "...")`.
+    pub fn from_sources(sources: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut templates = BTreeMap::new();
+        for (name, source) in sources {
+            let escape = if name.ends_with(".html") { Escape::Html } else { Escape::None };
+            let template = RuntimeTemplate::parse(&source, escape).map_err(|e| match e {
+                Error::Template(reason) => Error::Template(format!("{}: {}", name, reason)),
+                other => other,
+            })?;
+            templates.insert(name, template);
+        }
+        Ok(Self { templates })
+    }
+
+    /// `built_in` with whichever parts `email` overrides rendered against `context`.
+    pub fn content(&self, email: &str, context: &Value, built_in: EmailContent) -> EmailContent {
+        let render = |file: String| self.templates.get(&file).map(|t| t.render(context));
+        EmailContent {
+            subject: render(format!("{email}_subject.txt"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or(built_in.subject),
+            text: render(format!("{email}.txt")).unwrap_or(built_in.text),
+            html: render(format!("{email}.html")).unwrap_or(built_in.html),
+        }
+    }
+}
+
+/// What an email template is rendered against. Amounts and dates are
+/// formatted as in the built-in emails.
+pub fn email_context(
+    batch_id: &str,
+    total: &Money,
+    txns: &[Txn],
+    warnings: &[String],
+    payments: &[PaymentRequest],
+    days_open: Option<i64>,
+) -> Value {
+    let txns_by_date: Vec<Value> = group_txns_by_date(txns)
+        .into_iter()
+        .map(|(date, txns)| {
+            json!({
+                "date": date.format("%b %d, %Y").to_string(),
+                "iso_date": date.to_string(),
+                "txns": txns
+                    .iter()
+                    .map(|t| json!({
+                        "payee": t.payee,
+                        "amount": t.amount.to_string(),
+                        "notes": t.notes,
+                        "installment": t.installment.as_ref().map(|i| i.to_string()),
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "batch_id": batch_id,
+        "total": total.to_string(),
+        "txns_by_date": txns_by_date,
+        "warnings": warnings,
+        "payment_methods": payments,
+        // Kept for templates from before payment methods.
+        "venmo_request_link": payments
+            .iter()
+            .find(|p| p.provider == "Venmo")
+            .and_then(|p| p.link.as_deref())
+            .unwrap_or(""),
+        "days_open": days_open,
+    })
 }
 
 #[async_trait]
-impl BatchNotifier for JmapBatchNotifier {
+impl<T: MailTransport> BatchNotifier for EmailNotifier<T> {
     async fn send_batch_notification(
         &self,
         batch_id: &str,
-        total: &USD,
+        total: &Money,
         txns: &[Txn],
         warnings: Vec<String>,
     ) -> Result<()> {
-        let client = jmap_client::client::Client::new()
-            .credentials(self.api_key.clone())
-            .connect(&self.api_session_endpoint)
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?;
-
-        let mut identity_req = client.build();
-        let identity_get_req = identity_req.get_identity();
-        identity_get_req.account_id(client.default_account_id());
-        identity_req.using.push(jmap_client::URI::Submission);
-
-        let sending_identity = identity_req
-            .send()
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?
-            .pop_method_response()
-            .ok_or_else(|| Error::Notification("get identity response missing".to_string()))?
-            .unwrap_get_identity()
-            .map_err(|e| Error::Notification(e.to_string()))?
-            .list()
+        let emails = vec![
+            self.emails.creditor(batch_id, total, txns, warnings),
+            self.emails.debtor(batch_id, total, txns),
+        ];
+        self.deliver(batch_id, total, &emails).await
+    }
+
+    async fn send_settled_notification(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        settled_on: NaiveDate,
+        splits: &[Txn],
+    ) -> Result<()> {
+        let emails = self.emails.settled(batch_id, total, settled_on, splits);
+        self.deliver(batch_id, total, &emails).await
+    }
+
+    async fn send_reminder(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        txns: &[Txn],
+        days_open: i64,
+    ) -> Result<()> {
+        let emails = vec![self.emails.reminder(batch_id, total, txns, days_open)];
+        self.deliver(batch_id, total, &emails).await
+    }
+}
+
+impl<T: MailTransport> EmailNotifier<T> {
+    async fn deliver(&self, batch_id: &str, total: &Money, emails: &[OutgoingEmail]) -> Result<()> {
+        let emails: Vec<OutgoingEmail> = emails
             .iter()
-            .filter_map(|x| {
-                if x.email()? == self.sending_address
-                    && let Some(sending_id) = x.id()
-                {
-                    Some(sending_id.to_string())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<String>>()
-            .first()
-            .ok_or_else(|| {
-                Error::Notification("no identity matching config's sending address".to_string())
-            })?
-            .clone();
-
-        self.send_creditor_email(
-            &client,
-            &sending_identity,
-            batch_id,
-            total,
-            txns,
-            warnings,
-        )
-        .await?;
+            .filter(|e| self.recipients.contains(&e.recipient))
+            .cloned()
+            .collect();
+        if emails.is_empty() {
+            return Ok(());
+        }
 
-        self.send_debtor_email(&client, &sending_identity, batch_id, total, txns)
-            .await?;
+        if self.dry_run {
+            for email in &emails {
+                tracing::info!(to = %email.to, batch_id, "Dry run — skipping {} email", email.label);
+            }
+            return Ok(());
+        }
+
+        for email in &emails {
+            tracing::info!(to = %email.to, batch_id, amount = %total, "Sending {} email", email.label);
+        }
+        self.transport.send(&emails).await?;
+        tracing::info!(batch_id, count = emails.len(), "Emails sent");
 
         Ok(())
     }
 }
 
-impl JmapBatchNotifier {
-    async fn send_creditor_email(
+#[async_trait]
+impl BatchNotifier for Notifiers {
+    async fn send_batch_notification(
         &self,
-        client: &Client,
-        sending_identity: &str,
         batch_id: &str,
-        total: &USD,
+        total: &Money,
         txns: &[Txn],
         warnings: Vec<String>,
     ) -> Result<()> {
-        if self.dry_run {
-            tracing::info!(
-                to = %self.creditor_email,
-                batch_id,
-                "Dry run — skipping creditor email"
-            );
-            return Ok(());
+        let mut failures = vec![];
+        for (name, notifier) in &self.notifiers {
+            let result = notifier
+                .send_batch_notification(batch_id, total, txns, warnings.clone())
+                .await;
+            record_failure(name, result, &mut failures);
         }
+        failed(failures)
+    }
 
-        tracing::info!(
-            to = %self.creditor_email,
-            batch_id,
-            amount = %total,
-            transaction_count = txns.len(),
-            "Sending creditor email"
-        );
-
-        let mut email_req = client.build();
-        let email_set_req = email_req.set_email();
-
-        let email = email_set_req.create_with_id("m0");
-        email.from([self.sending_address.clone()]);
-        email.to([self.creditor_email.clone()]);
-        email.subject("Quail alert! Batch ready from equailizer");
-        email.mailbox_ids([&self.sent_mailbox]);
-
-        let venmo_text = format!("equailizer_{}", date_helpers::now_date_naive_eastern());
-        let venmo_request_link =
-            venmo_request_link(&self.debtor_venmo_username, &venmo_text, total);
-
-        let text_body_id = EmailBodyPart::new().part_id("t1");
-        email.body_value(
-            "t1".to_string(),
-            format!(
-                "New batch ready!\n\nClick here to initiate Venmo request: {}\n\nbatch id: {}",
-                venmo_request_link, batch_id
-            ),
-        );
-        email.text_body(text_body_id);
-
-        let html_body_id = EmailBodyPart::new().part_id("t2");
-        let html_text = make_creditor_email_html_string(
-            txns,
-            &venmo_request_link,
-            warnings,
-            &batch_id.to_string(),
-            total,
-        );
-        email.body_value("t2".to_string(), html_text);
-        email.html_body(html_body_id);
-
-        let email_response = match email_req
-            .send()
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?
-            .pop_method_response()
-        {
-            Some(res) => res.unwrap_method_response(),
-            None => {
-                return Err(Error::Notification(
-                    "JMAP create email response did not contain any methodResponses".to_string(),
-                ));
-            }
-        };
-
-        let email_id = match email_response {
-            SetEmail(mut es) => es
-                .created("m0")
-                .map_err(|e| Error::Notification(e.to_string()))?
-                .id()
-                .ok_or_else(|| {
-                    Error::Notification(
-                        "didn't find email submission id in response".to_string(),
-                    )
-                })?
-                .to_string(),
-            _ => {
-                return Err(Error::Notification(
-                    "JMAP create email response was not of type SetEmail".to_string(),
-                ))
-            }
-        };
+    async fn send_settled_notification(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        settled_on: NaiveDate,
+        splits: &[Txn],
+    ) -> Result<()> {
+        let mut failures = vec![];
+        for (name, notifier) in &self.notifiers {
+            let result = notifier
+                .send_settled_notification(batch_id, total, settled_on, splits)
+                .await;
+            record_failure(name, result, &mut failures);
+        }
+        failed(failures)
+    }
 
-        client
-            .email_submission_create(email_id, sending_identity.to_string())
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?;
+    async fn send_reminder(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        txns: &[Txn],
+        days_open: i64,
+    ) -> Result<()> {
+        let mut failures = vec![];
+        for (name, notifier) in &self.notifiers {
+            let result = notifier.send_reminder(batch_id, total, txns, days_open).await;
+            record_failure(name, result, &mut failures);
+        }
+        failed(failures)
+    }
+}
 
-        tracing::info!(
-            to = %self.creditor_email,
-            batch_id,
-            "Creditor email sent"
-        );
+fn record_failure(name: &str, result: Result<()>, failures: &mut Vec<String>) {
+    if let Err(e) = result {
+        tracing::warn!(notifier = name, error = %e, "Notifier failed");
+        failures.push(format!("{}: {}", name, e));
+    }
+}
 
+fn failed(failures: Vec<String>) -> Result<()> {
+    if failures.is_empty() {
         Ok(())
+    } else {
+        Err(Error::NotifiersFailed(failures))
     }
+}
 
-    async fn send_debtor_email(
+impl BatchEmails {
+    /// The batch's itemised list, with warnings and every way to pay.
+    pub fn creditor(
         &self,
-        client: &Client,
-        sending_identity: &str,
         batch_id: &str,
-        total: &USD,
+        total: &Money,
         txns: &[Txn],
-    ) -> Result<()> {
-        if self.dry_run {
-            tracing::info!(
-                to = %self.debtor_email,
-                batch_id,
-                "Dry run — skipping debtor email"
-            );
-            return Ok(());
-        }
+        warnings: Vec<String>,
+    ) -> OutgoingEmail {
+        let memo = format!("equailizer_{}", date_helpers::now_date_naive_eastern());
+        let payments = self.payment_requests(&memo, total);
+        let context = email_context(batch_id, total, txns, &warnings, &payments, None);
+        let content = self.templates.content(
+            "creditor",
+            &context,
+            EmailContent {
+                subject: "Quail alert! Batch ready from equailizer".to_string(),
+                text: format!(
+                    "New batch ready!\n\n{}\n\nbatch id: {}",
+                    payment_text(&payments),
+                    batch_id
+                ),
+                html: make_creditor_email_html_string(
+                    txns,
+                    &payments,
+                    warnings,
+                    &batch_id.to_string(),
+                    total,
+                ),
+            },
+        );
+        self.email("creditor", Party::Creditor, None, batch_id, "creditor", None, content)
+    }
 
-        tracing::info!(
-            to = %self.debtor_email,
-            batch_id,
-            amount = %total,
-            transaction_count = txns.len(),
-            "Sending debtor email"
+    /// The batch's itemised list, with the ways the debtor can pay or the
+    /// requests to expect.
+    pub fn debtor(&self, batch_id: &str, total: &Money, txns: &[Txn]) -> OutgoingEmail {
+        let memo = format!("equailizer_{}", date_helpers::now_date_naive_eastern());
+        let payments = self.payment_requests(&memo, total);
+        let context = email_context(batch_id, total, txns, &[], &payments, None);
+        let mut text = "New batch incoming!".to_string();
+        for payment in payments.iter().filter(|p| p.requested_by_creditor) {
+            text.push_str(&format!(" You'll see a {} request for it soon.", payment.provider));
+        }
+        let to_pay: Vec<PaymentRequest> =
+            payments.iter().filter(|p| !p.requested_by_creditor).cloned().collect();
+        if !to_pay.is_empty() {
+            text.push_str(&format!("\n\nTo pay:\n{}", payment_text(&to_pay)));
+        }
+        let content = self.templates.content(
+            "debtor",
+            &context,
+            EmailContent {
+                subject: "Quail alert! Batch incoming from equailizer".to_string(),
+                text: format!("{}\n\nbatch id: {}", text, batch_id),
+                html: make_debtor_email_html_string(txns, &payments, &batch_id.to_string(), total),
+            },
         );
+        self.email("debtor", Party::Debtor, None, batch_id, "debtor", None, content)
+    }
 
-        let mut email_req = client.build();
-        let email_set_req = email_req.set_email();
-
-        let email = email_set_req.create_with_id("m0");
-        email.from([self.sending_address.clone()]);
-        email.to([self.debtor_email.clone()]);
-        email.subject("Quail alert! Batch incoming from equailizer");
-        email.mailbox_ids([&self.sent_mailbox]);
-
-        let text_body_id = EmailBodyPart::new().part_id("t1");
-        email.body_value(
-            "t1".to_string(),
-            format!(
-                "New batch incoming! You'll see a venmo request for it soon.\n\nbatch id: {}",
-                batch_id
-            ),
+    /// One copy for each party, each replying to the batch email they were sent.
+    pub fn settled(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        settled_on: NaiveDate,
+        splits: &[Txn],
+    ) -> Vec<OutgoingEmail> {
+        let mut context = email_context(batch_id, total, splits, &[], &[], None);
+        context["settled_on"] = json!(settled_on.format("%b %d, %Y").to_string());
+        let content = self.templates.content(
+            "settled",
+            &context,
+            EmailContent {
+                subject: "Quail settled! Batch from equailizer is paid".to_string(),
+                text: format!(
+                    "Batch {} for {} was settled on {}.\n\nbatch id: {}",
+                    batch_id,
+                    total,
+                    settled_on.format("%b %d, %Y"),
+                    batch_id
+                ),
+                html: make_settled_email_html_string(
+                    splits,
+                    &batch_id.to_string(),
+                    total,
+                    settled_on,
+                ),
+            },
         );
-        email.text_body(text_body_id);
-
-        let html_body_id = EmailBodyPart::new().part_id("t2");
-        let html_text =
-            make_debtor_email_html_string(txns, &batch_id.to_string(), total);
-        email.body_value("t2".to_string(), html_text);
-        email.html_body(html_body_id);
-
-        let email_response = match email_req
-            .send()
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?
-            .pop_method_response()
-        {
-            Some(res) => res.unwrap_method_response(),
-            None => {
-                return Err(Error::Notification(
-                    "JMAP create email response did not contain any methodResponses".to_string(),
-                ));
-            }
-        };
-
-        let email_id = match email_response {
-            SetEmail(mut es) => es
-                .created("m0")
-                .map_err(|e| Error::Notification(e.to_string()))?
-                .id()
-                .ok_or_else(|| {
-                    Error::Notification(
-                        "didn't find email submission id in response".to_string(),
-                    )
-                })?
-                .to_string(),
-            _ => {
-                return Err(Error::Notification(
-                    "JMAP create email response was not of type SetEmail".to_string(),
-                ))
-            }
-        };
 
-        client
-            .email_submission_create(email_id, sending_identity.to_string())
-            .await
-            .map_err(|e| Error::Notification(e.to_string()))?;
+        [Party::Creditor, Party::Debtor]
+            .into_iter()
+            .map(|party| {
+                let parent = party.as_str();
+                let id = format!("settled-{parent}");
+                self.email("settled", party, None, batch_id, &id, Some(parent), content.clone())
+            })
+            .collect()
+    }
 
-        tracing::info!(
-            to = %self.debtor_email,
-            batch_id,
-            "Debtor email sent"
+    /// A reminder to the debtor, replying to their batch email. The creditor
+    /// is copied so they can re-send any payment request.
+    pub fn reminder(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        txns: &[Txn],
+        days_open: i64,
+    ) -> OutgoingEmail {
+        let memo = format!("equailizer_{}", batch_id);
+        let payments = self.payment_requests(&memo, total);
+        let context = email_context(batch_id, total, txns, &[], &payments, Some(days_open));
+        let content = self.templates.content(
+            "reminder",
+            &context,
+            EmailContent {
+                subject: "Quail reminder: a batch from equailizer is still open".to_string(),
+                text: format!(
+                    "Just a friendly reminder: batch {} for {} has been open for {} days.\n\n{}",
+                    batch_id,
+                    total,
+                    days_open,
+                    payment_text(&payments)
+                ),
+                html: make_reminder_email_html_string(
+                    txns,
+                    &payments,
+                    &batch_id.to_string(),
+                    total,
+                    days_open,
+                ),
+            },
         );
+        self.email(
+            "reminder",
+            Party::Debtor,
+            Some(&self.creditor_email),
+            batch_id,
+            &format!("reminder-{}", days_open),
+            Some("debtor"),
+            content,
+        )
+    }
 
-        Ok(())
+    fn payment_requests(&self, memo: &str, total: &Money) -> Vec<PaymentRequest> {
+        self.payment_methods
+            .iter()
+            .map(|method| method.request(memo, total))
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn email(
+        &self,
+        label: &'static str,
+        recipient: Party,
+        cc: Option<&str>,
+        batch_id: &str,
+        id: &str,
+        reply_to: Option<&str>,
+        content: EmailContent,
+    ) -> OutgoingEmail {
+        OutgoingEmail {
+            label,
+            from: self.sending_address.clone(),
+            recipient,
+            to: match recipient {
+                Party::Creditor => self.creditor_email.clone(),
+                Party::Debtor => self.debtor_email.clone(),
+            },
+            cc: cc.map(str::to_string),
+            message_id: self.message_id(batch_id, id),
+            in_reply_to: reply_to.map(|parent| self.message_id(batch_id, parent)),
+            content,
+        }
+    }
+
+    /// The Message-ID of one of a batch's emails. It's made from the batch id,
+    /// so later emails can reply to the batch emails without storing anything.
+    fn message_id(&self, batch_id: &str, email: &str) -> String {
+        let domain = self
+            .sending_address
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or("equailizer");
+        format!("equailizer.{}.{}@{}", batch_id, email, domain)
     }
 }
 
-fn venmo_request_link(venmo_username: &str, text: &str, amount: &USD) -> String {
-    format!(
-        "https://venmo.com/{}?txn=charge&note={}&amount={}",
-        venmo_username, text, amount
-    )
+/// One line per payment method, for plain-text emails.
+fn payment_text(payments: &[PaymentRequest]) -> String {
+    payments
+        .iter()
+        .map(|p| match &p.link {
+            Some(link) => format!("{}: {} — {}", p.provider, p.instructions, link),
+            None => format!("{}: {}", p.provider, p.instructions),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Template)]
 #[template(path = "batch_ready_creditor_email.html")]
 struct BatchReadyEmailTemplate<'a> {
     txns_by_date: BTreeMap<NaiveDate, Vec<&'a Txn>>,
-    venmo_request_link: &'a String,
+    payments: &'a [PaymentRequest],
     warnings: Vec<String>,
     batch_id: &'a String,
-    total: &'a USD,
+    total: &'a Money,
 }
 
 pub fn make_creditor_email_html_string(
     txns: &[Txn],
-    venmo_request_link: &String,
+    payments: &[PaymentRequest],
     warnings: Vec<String>,
     batch_id: &String,
-    total: &USD,
+    total: &Money,
 ) -> String {
     let txns_by_date = group_txns_by_date(txns);
 
     let email = BatchReadyEmailTemplate {
         txns_by_date,
-        venmo_request_link,
+        payments,
         warnings,
         batch_id,
         total,
@@ -346,15 +651,22 @@ pub fn make_creditor_email_html_string(
 #[template(path = "batch_ready_debtor_email.html")]
 struct BatchReadyDebtorEmailTemplate<'a> {
     txns_by_date: BTreeMap<NaiveDate, Vec<&'a Txn>>,
+    payments: &'a [PaymentRequest],
     batch_id: &'a String,
-    total: &'a USD,
+    total: &'a Money,
 }
 
-pub fn make_debtor_email_html_string(txns: &[Txn], batch_id: &String, total: &USD) -> String {
+pub fn make_debtor_email_html_string(
+    txns: &[Txn],
+    payments: &[PaymentRequest],
+    batch_id: &String,
+    total: &Money,
+) -> String {
     let txns_by_date = group_txns_by_date(txns);
 
     let email = BatchReadyDebtorEmailTemplate {
         txns_by_date,
+        payments,
         batch_id,
         total,
     };
@@ -362,6 +674,63 @@ pub fn make_debtor_email_html_string(txns: &[Txn], batch_id: &String, total: &US
     email.render().unwrap()
 }
 
+#[derive(Template)]
+#[template(path = "batch_reminder_email.html")]
+struct BatchReminderEmailTemplate<'a> {
+    txns_by_date: BTreeMap<NaiveDate, Vec<&'a Txn>>,
+    payments: &'a [PaymentRequest],
+    batch_id: &'a String,
+    total: &'a Money,
+    days_open: i64,
+}
+
+pub fn make_reminder_email_html_string(
+    txns: &[Txn],
+    payments: &[PaymentRequest],
+    batch_id: &String,
+    total: &Money,
+    days_open: i64,
+) -> String {
+    let txns_by_date = group_txns_by_date(txns);
+
+    let email = BatchReminderEmailTemplate {
+        txns_by_date,
+        payments,
+        batch_id,
+        total,
+        days_open,
+    };
+
+    email.render().unwrap()
+}
+
+#[derive(Template)]
+#[template(path = "batch_settled_email.html")]
+struct BatchSettledEmailTemplate<'a> {
+    txns_by_date: BTreeMap<NaiveDate, Vec<&'a Txn>>,
+    batch_id: &'a String,
+    total: &'a Money,
+    settled_on: NaiveDate,
+}
+
+pub fn make_settled_email_html_string(
+    splits: &[Txn],
+    batch_id: &String,
+    total: &Money,
+    settled_on: NaiveDate,
+) -> String {
+    let txns_by_date = group_txns_by_date(splits);
+
+    let email = BatchSettledEmailTemplate {
+        txns_by_date,
+        batch_id,
+        total,
+        settled_on,
+    };
+
+    email.render().unwrap()
+}
+
 fn group_txns_by_date<'a>(txns: &'a [Txn]) -> BTreeMap<NaiveDate, Vec<&'a Txn>> {
     txns.iter()
         .fold(BTreeMap::<NaiveDate, Vec<&Txn>>::new(), |mut acc, txn| {
@@ -371,7 +740,7 @@ fn group_txns_by_date<'a>(txns: &'a [Txn]) -> BTreeMap<NaiveDate, Vec<&'a Txn>>
 }
 
 #[cfg(debug_assertions)]
-pub fn dev_print(batch_id: &String, txns: Vec<Txn>, warnings: Vec<String>, amount: &USD) {
+pub fn dev_print(batch_id: &String, txns: Vec<Txn>, warnings: Vec<String>, amount: &Money) {
     use std::fs;
 
     let mut path = std::env::current_exe()
@@ -396,17 +765,16 @@ pub fn dev_print(batch_id: &String, txns: Vec<Txn>, warnings: Vec<String>, amoun
 
     let creditor_file_path = path.join("email_creditor.html");
 
-    let creditor_html = make_creditor_email_html_string(
-        &txns,
-        &("https://www.example.com".to_string()),
-        warnings,
-        batch_id,
-        amount,
-    );
+    let payments = [PaymentMethod::Venmo {
+        username: "example".to_string(),
+    }
+    .request("equailizer_dev", amount)];
+    let creditor_html =
+        make_creditor_email_html_string(&txns, &payments, warnings, batch_id, amount);
 
     fs::write(creditor_file_path, creditor_html).expect("failed to write html file");
 
-    let debtor_html = make_debtor_email_html_string(&txns, batch_id, amount);
+    let debtor_html = make_debtor_email_html_string(&txns, &payments, batch_id, amount);
     let debtor_file_path = path.join("email_debtor.html");
     fs::write(debtor_file_path, debtor_html).expect("failed to write html file");
 }
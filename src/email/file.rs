@@ -0,0 +1,34 @@
+use crate::email::{smtp, MailTransport, OutgoingEmail};
+use crate::error::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// Writes each email to `dir` as `<message id>.eml`, which mail clients can
+/// open, or prints its subject and text body to stderr when there's no `dir`,
+/// keeping stdout free for `--output` reports.
+pub struct FileTransport {
+    pub dir: Option<PathBuf>,
+}
+
+#[async_trait]
+impl MailTransport for FileTransport {
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()> {
+        let Some(dir) = &self.dir else {
+            for email in emails {
+                eprintln!(
+                    "To: {}\nSubject: {}\n\n{}\n",
+                    email.to, email.content.subject, email.content.text
+                );
+            }
+            return Ok(());
+        };
+
+        std::fs::create_dir_all(dir)?;
+        for email in emails {
+            let path = dir.join(format!("{}.eml", email.message_id));
+            std::fs::write(&path, smtp::message(email)?.formatted())?;
+            tracing::info!(to = %email.to, path = %path.display(), "Email written");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::email::{MailTransport, OutgoingEmail};
+use crate::error::{Error, Result};
+use crate::secret::Secret;
+use async_trait::async_trait;
+use jmap_client::{client::Client, core::response::MethodResponse::*, email::EmailBodyPart};
+
+/// Sends through a JMAP account, e.g. Fastmail, keeping a copy in the sent
+/// mailbox.
+pub struct JmapTransport {
+    pub api_session_endpoint: String,
+    pub api_key: Secret,
+    pub sent_mailbox: String,
+    pub sending_address: String,
+}
+
+#[async_trait]
+impl MailTransport for JmapTransport {
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()> {
+        let (client, sending_identity) = self.connect().await?;
+        for email in emails {
+            self.send_email(&client, &sending_identity, email).await?;
+            tracing::info!(to = %email.to, message_id = %email.message_id, "Email sent");
+        }
+        Ok(())
+    }
+}
+
+impl JmapTransport {
+    /// Connect to the JMAP server and find the identity for the sending address.
+    async fn connect(&self) -> Result<(Client, String)> {
+        let client = jmap_client::client::Client::new()
+            .credentials(self.api_key.expose())
+            .connect(&self.api_session_endpoint)
+            .await
+            .map_err(|e| Error::Notification(e.to_string()))?;
+
+        let mut identity_req = client.build();
+        let identity_get_req = identity_req.get_identity();
+        identity_get_req.account_id(client.default_account_id());
+        identity_req.using.push(jmap_client::URI::Submission);
+
+        let sending_identity = identity_req
+            .send()
+            .await
+            .map_err(|e| Error::Notification(e.to_string()))?
+            .pop_method_response()
+            .ok_or_else(|| Error::Notification("get identity response missing".to_string()))?
+            .unwrap_get_identity()
+            .map_err(|e| Error::Notification(e.to_string()))?
+            .list()
+            .iter()
+            .filter_map(|x| {
+                if x.email()? == self.sending_address
+                    && let Some(sending_id) = x.id()
+                {
+                    Some(sending_id.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<String>>()
+            .first()
+            .ok_or_else(|| {
+                Error::Notification("no identity matching config's sending address".to_string())
+            })?
+            .clone();
+
+        Ok((client, sending_identity))
+    }
+
+    /// Create the email in the sent mailbox and submit it.
+    async fn send_email(
+        &self,
+        client: &Client,
+        sending_identity: &str,
+        outgoing: &OutgoingEmail,
+    ) -> Result<()> {
+        let mut email_req = client.build();
+        let email_set_req = email_req.set_email();
+
+        let email = email_set_req.create_with_id("m0");
+        email.from([outgoing.from.clone()]);
+        email.to([outgoing.to.clone()]);
+        if let Some(cc) = &outgoing.cc {
+            email.cc([cc.clone()]);
+        }
+        email.subject(outgoing.content.subject.clone());
+        email.message_id([outgoing.message_id.clone()]);
+        if let Some(parent) = &outgoing.in_reply_to {
+            email.in_reply_to([parent.clone()]);
+            email.references([parent.clone()]);
+        }
+        email.mailbox_ids([&self.sent_mailbox]);
+
+        let text_body_id = EmailBodyPart::new().part_id("t1");
+        email.body_value("t1".to_string(), outgoing.content.text.clone());
+        email.text_body(text_body_id);
+
+        let html_body_id = EmailBodyPart::new().part_id("t2");
+        email.body_value("t2".to_string(), outgoing.content.html.clone());
+        email.html_body(html_body_id);
+
+        let email_response = match email_req
+            .send()
+            .await
+            .map_err(|e| Error::Notification(e.to_string()))?
+            .pop_method_response()
+        {
+            Some(res) => res.unwrap_method_response(),
+            None => {
+                return Err(Error::Notification(
+                    "JMAP create email response did not contain any methodResponses".to_string(),
+                ));
+            }
+        };
+
+        let email_id = match email_response {
+            SetEmail(mut es) => es
+                .created("m0")
+                .map_err(|e| Error::Notification(e.to_string()))?
+                .id()
+                .ok_or_else(|| {
+                    Error::Notification(
+                        "didn't find email submission id in response".to_string(),
+                    )
+                })?
+                .to_string(),
+            _ => {
+                return Err(Error::Notification(
+                    "JMAP create email response was not of type SetEmail".to_string(),
+                ))
+            }
+        };
+
+        client
+            .email_submission_create(email_id, sending_identity.to_string())
+            .await
+            .map_err(|e| Error::Notification(e.to_string()))?;
+
+        Ok(())
+    }
+}
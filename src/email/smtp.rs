@@ -0,0 +1,85 @@
+use crate::config::SmtpTls;
+use crate::email::{MailTransport, OutgoingEmail};
+use crate::error::{Error, Result};
+use crate::secret::Secret;
+use async_trait::async_trait;
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+/// Sends through an SMTP server.
+pub struct SmtpTransport {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<Secret>,
+}
+
+#[async_trait]
+impl MailTransport for SmtpTransport {
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()> {
+        let mailer = self.connect()?;
+        for email in emails {
+            mailer.send(message(email)?).await.map_err(smtp_error)?;
+            tracing::info!(to = %email.to, message_id = %email.message_id, "Email sent");
+        }
+        Ok(())
+    }
+}
+
+impl SmtpTransport {
+    fn connect(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let builder = match self.tls {
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host).map_err(smtp_error)?,
+            SmtpTls::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host).map_err(smtp_error)?
+            }
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host),
+        };
+        let builder = match (&self.username, &self.password) {
+            (Some(username), Some(password)) => builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose().to_string(),
+            )),
+            _ => builder,
+        };
+        Ok(builder.port(self.port).build())
+    }
+}
+
+/// The email as a multipart/alternative message. SMTP headers put Message-IDs
+/// in angle brackets.
+pub(super) fn message(email: &OutgoingEmail) -> Result<Message> {
+    let mut builder = Message::builder()
+        .from(mailbox(&email.from)?)
+        .to(mailbox(&email.to)?)
+        .subject(email.content.subject.clone())
+        .message_id(Some(format!("<{}>", email.message_id)));
+    if let Some(cc) = &email.cc {
+        builder = builder.cc(mailbox(cc)?);
+    }
+    if let Some(parent) = &email.in_reply_to {
+        builder = builder
+            .in_reply_to(format!("<{}>", parent))
+            .references(format!("<{}>", parent));
+    }
+    builder
+        .multipart(MultiPart::alternative_plain_html(
+            email.content.text.clone(),
+            email.content.html.clone(),
+        ))
+        .map_err(smtp_error)
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .map_err(|e| Error::Notification(format!("invalid email address `{}`: {}", address, e)))
+}
+
+fn smtp_error(e: impl std::fmt::Display) -> Error {
+    Error::Notification(e.to_string())
+}
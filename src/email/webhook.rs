@@ -0,0 +1,32 @@
+use crate::email::{MailTransport, OutgoingEmail};
+use crate::error::{Error, Result};
+use crate::secret::Secret;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+/// POSTs each email as JSON to `url`, e.g. for a chat bot or automation
+/// service. Any response other than a success is a failure.
+pub struct WebhookTransport {
+    pub url: String,
+    pub headers: BTreeMap<String, Secret>,
+}
+
+#[async_trait]
+impl MailTransport for WebhookTransport {
+    async fn send(&self, emails: &[OutgoingEmail]) -> Result<()> {
+        let client = reqwest::Client::new();
+        for email in emails {
+            let mut request = client.post(&self.url).json(email);
+            for (name, value) in &self.headers {
+                request = request.header(name, value.expose());
+            }
+            request
+                .send()
+                .await?
+                .error_for_status()
+                .map_err(|e| Error::Notification(format!("webhook: {}", e)))?;
+            tracing::info!(to = %email.to, url = %self.url, "Email posted to webhook");
+        }
+        Ok(())
+    }
+}
@@ -9,6 +9,9 @@ pub enum Error {
     #[error("batch '{0}' is already reconciled")]
     BatchAlreadyReconciled(String),
 
+    #[error("batch '{0}' was closed by adjustment {1}")]
+    BatchClosed(String, String),
+
     #[error("no creditor transactions found for reconciliation")]
     NoTransactionsFound,
 
@@ -24,6 +27,22 @@ pub enum Error {
         source: Box<Error>,
     },
 
+    #[error("transaction {0} cannot be amortized: {1}")]
+    NotAmortizable(crate::lunch_money::model::transaction::TransactionId, String),
+
+    #[error("plan is out of date; these transactions changed since it was made: {0:?}")]
+    StalePlan(Vec<crate::lunch_money::model::transaction::TransactionId>),
+
+    #[error("create-batch run '{0}' didn't finish; run `equailizer resume` to finish it, or `equailizer resume --rollback` to undo it")]
+    UnfinishedRun(String),
+
+    #[error("invalid adjustment: {0}")]
+    InvalidAdjustment(String),
+
+    // ── Config ──
+    #[error("invalid config: {0}")]
+    Config(String),
+
     // ── Lunch Money API ──
     #[error("{0}")]
     Api(String),
@@ -41,4 +60,11 @@ pub enum Error {
     // ── Notifications ──
     #[error("{0}")]
     Notification(String),
+
+    /// Each notifier that failed, as `name: error`.
+    #[error("notifiers failed: {}", .0.join("; "))]
+    NotifiersFailed(Vec<String>),
+
+    #[error("invalid email template: {0}")]
+    Template(String),
 }
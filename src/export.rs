@@ -0,0 +1,327 @@
+use crate::lunch_money::model::transaction::TransactionId;
+use crate::money::{Currency, Money};
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+/// One batched transaction or installment, for `export`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRow {
+    pub batch_id: String,
+    pub transaction_id: TransactionId,
+    pub date: NaiveDate,
+    pub payee: String,
+    pub amount: Money,
+    pub notes: Option<String>,
+    pub settlement_credit_id: Option<TransactionId>,
+    pub settlement_debit_id: Option<TransactionId>,
+}
+
+impl ExportRow {
+    pub fn is_reconciled(&self) -> bool {
+        self.settlement_credit_id.is_some()
+    }
+}
+
+const CSV_HEADERS: [&str; 9] = [
+    "batch_id",
+    "transaction_id",
+    "date",
+    "payee",
+    "amount",
+    "currency",
+    "notes",
+    "settlement_credit_id",
+    "settlement_debit_id",
+];
+
+pub fn to_csv(rows: &[ExportRow]) -> String {
+    let mut out = CSV_HEADERS.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields = [
+            row.batch_id.clone(),
+            row.transaction_id.to_string(),
+            row.date.to_string(),
+            row.payee.clone(),
+            row.amount.amount_string(),
+            row.amount.currency().to_string(),
+            row.notes.clone().unwrap_or_default(),
+            optional_id(row.settlement_credit_id),
+            optional_id(row.settlement_debit_id),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// An OFX 2 bank statement with one transaction per row. Amounts are positive,
+/// since each one is owed to the creditor. OFX statements have a single
+/// currency, so rows in several currencies get one statement (and account)
+/// per currency.
+pub fn to_ofx(rows: &[ExportRow]) -> String {
+    let mut currencies: Vec<Currency> = vec![];
+    for row in rows {
+        if !currencies.contains(&row.amount.currency()) {
+            currencies.push(row.amount.currency());
+        }
+    }
+    if currencies.is_empty() {
+        currencies.push(Currency::USD);
+    }
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n");
+    out.push_str("<OFX>\n<BANKMSGSRSV1>\n");
+    for (index, &currency) in currencies.iter().enumerate() {
+        let statement: Vec<&ExportRow> = rows.iter().filter(|r| r.amount.currency() == currency).collect();
+        let account = if currencies.len() > 1 {
+            format!("equailizer-{}", currency)
+        } else {
+            "equailizer".to_string()
+        };
+        write_ofx_statement(&mut out, index, currency, &account, &statement);
+    }
+    out.push_str("</BANKMSGSRSV1>\n</OFX>\n");
+    out
+}
+
+fn write_ofx_statement(
+    out: &mut String,
+    index: usize,
+    currency: Currency,
+    account: &str,
+    rows: &[&ExportRow],
+) {
+    let start = rows.iter().map(|r| r.date).min();
+    let end = rows.iter().map(|r| r.date).max();
+    let ofx_date = |d: Option<NaiveDate>| d.map(|d| d.format("%Y%m%d").to_string()).unwrap_or_default();
+
+    let _ = writeln!(out, "<STMTTRNRS>\n<TRNUID>{}</TRNUID>", index);
+    out.push_str("<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n<STMTRS>\n");
+    let _ = writeln!(out, "<CURDEF>{}</CURDEF>", currency);
+    let _ = writeln!(
+        out,
+        "<BANKACCTFROM><BANKID>equailizer</BANKID><ACCTID>{}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>",
+        account
+    );
+    let _ = writeln!(
+        out,
+        "<BANKTRANLIST>\n<DTSTART>{}</DTSTART>\n<DTEND>{}</DTEND>",
+        ofx_date(start),
+        ofx_date(end)
+    );
+    for row in rows {
+        let memo = match &row.notes {
+            Some(notes) => format!("{} (batch {})", notes, row.batch_id),
+            None => format!("batch {}", row.batch_id),
+        };
+        let _ = writeln!(
+            out,
+            "<STMTTRN><TRNTYPE>OTHER</TRNTYPE><DTPOSTED>{}</DTPOSTED><TRNAMT>{}</TRNAMT><FITID>{}-{}</FITID><NAME>{}</NAME><MEMO>{}</MEMO></STMTTRN>",
+            row.date.format("%Y%m%d"),
+            row.amount.amount_string(),
+            row.batch_id,
+            row.transaction_id,
+            xml_escape(&row.payee),
+            xml_escape(&memo)
+        );
+    }
+    out.push_str("</BANKTRANLIST>\n</STMTRS>\n</STMTTRNRS>\n");
+}
+
+/// A ledger journal moving each row from the proxy category to what the debtor
+/// owes. Reconciled rows are cleared (`*`), the rest pending (`!`).
+pub fn to_ledger(rows: &[ExportRow], debtor_name: &str) -> String {
+    let receivable = receivable_account(debtor_name);
+    let mut out = String::new();
+    for row in rows {
+        let flag = if row.is_reconciled() { '*' } else { '!' };
+        let _ = writeln!(out, "{} {} {}", row.date, flag, row.payee);
+        let _ = writeln!(out, "    ; batch: {}", row.batch_id);
+        let _ = writeln!(out, "    ; transaction: {}", row.transaction_id);
+        if let Some(notes) = &row.notes {
+            let _ = writeln!(out, "    ; notes: {}", notes);
+        }
+        if let (Some(credit), Some(debit)) = (row.settlement_credit_id, row.settlement_debit_id) {
+            let _ = writeln!(out, "    ; settlement: {} / {}", credit, debit);
+        }
+        let _ = writeln!(out, "    {}  {}", receivable, commodity_amount(&row.amount));
+        let _ = writeln!(out, "    {}", PROXY_ACCOUNT);
+        out.push('\n');
+    }
+    out
+}
+
+/// Like `to_ledger`, in beancount syntax, with the accounts opened on the
+/// first row's date.
+pub fn to_beancount(rows: &[ExportRow], debtor_name: &str) -> String {
+    let receivable = receivable_account(debtor_name);
+    let mut out = String::new();
+    if let Some(first) = rows.iter().map(|r| r.date).min() {
+        let _ = writeln!(out, "{} open {}", first, receivable);
+        let _ = writeln!(out, "{} open {}", first, PROXY_ACCOUNT);
+        out.push('\n');
+    }
+    for row in rows {
+        let flag = if row.is_reconciled() { '*' } else { '!' };
+        let _ = writeln!(
+            out,
+            "{} {} \"{}\" \"{}\"",
+            row.date,
+            flag,
+            beancount_escape(&row.payee),
+            beancount_escape(row.notes.as_deref().unwrap_or(""))
+        );
+        let _ = writeln!(out, "  batch: \"{}\"", row.batch_id);
+        let _ = writeln!(out, "  transaction: \"{}\"", row.transaction_id);
+        if let (Some(credit), Some(debit)) = (row.settlement_credit_id, row.settlement_debit_id) {
+            let _ = writeln!(out, "  settlement: \"{} / {}\"", credit, debit);
+        }
+        let _ = writeln!(out, "  {}  {}", receivable, commodity_amount(&row.amount));
+        let _ = writeln!(out, "  {}", PROXY_ACCOUNT);
+        out.push('\n');
+    }
+    out
+}
+
+const PROXY_ACCOUNT: &str = "Expenses:Proxy";
+
+/// `Assets:Receivable:<Debtor>`, with the name reduced to what both ledger
+/// and beancount accept in an account name.
+fn receivable_account(debtor_name: &str) -> String {
+    let mut name: String = debtor_name.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    if let Some(first) = name.get(..1) {
+        name.replace_range(..1, &first.to_ascii_uppercase());
+    }
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        name.insert(0, 'D');
+    }
+    format!("Assets:Receivable:{}", name)
+}
+
+fn commodity_amount(amount: &Money) -> String {
+    format!("{} {}", amount.amount_string(), amount.currency())
+}
+
+fn optional_id(id: Option<TransactionId>) -> String {
+    id.map(|id| id.to_string()).unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn beancount_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<ExportRow> {
+        vec![
+            ExportRow {
+                batch_id: "abc".to_string(),
+                transaction_id: 1,
+                date: NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
+                payee: "Grocer, Inc".to_string(),
+                amount: Money::new_from_minor(1500, Currency::USD),
+                notes: Some("said \"hi\"".to_string()),
+                settlement_credit_id: Some(500),
+                settlement_debit_id: Some(600),
+            },
+            ExportRow {
+                batch_id: "def".to_string(),
+                transaction_id: 22,
+                date: NaiveDate::from_ymd_opt(2025, 3, 9).unwrap(),
+                payee: "Couch & Co".to_string(),
+                amount: Money::new_from_minor(1000, Currency::USD),
+                notes: None,
+                settlement_credit_id: None,
+                settlement_debit_id: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        let expected = "\
+batch_id,transaction_id,date,payee,amount,currency,notes,settlement_credit_id,settlement_debit_id
+abc,1,2025-03-02,\"Grocer, Inc\",15.00,USD,\"said \"\"hi\"\"\",500,600
+def,22,2025-03-09,Couch & Co,10.00,USD,,,
+";
+        assert_eq!(to_csv(&rows()), expected);
+    }
+
+    #[test]
+    fn ofx_has_one_transaction_per_row() {
+        let ofx = to_ofx(&rows());
+        assert!(ofx.contains("<CURDEF>USD</CURDEF>"));
+        assert!(ofx.contains("<DTSTART>20250302</DTSTART>\n<DTEND>20250309</DTEND>"));
+        assert!(ofx.contains("<DTPOSTED>20250302</DTPOSTED><TRNAMT>15.00</TRNAMT><FITID>abc-1</FITID><NAME>Grocer, Inc</NAME>"));
+        assert!(ofx.contains("<NAME>Couch &amp; Co</NAME><MEMO>batch def</MEMO>"));
+        assert_eq!(ofx.matches("<STMTTRNRS>").count(), 1);
+        assert!(ofx.contains("<ACCTID>equailizer</ACCTID>"));
+    }
+
+    #[test]
+    fn ofx_has_one_statement_per_currency() {
+        let mut rows = rows();
+        rows[1].amount = Money::new_from_minor(1000, "eur".parse().unwrap());
+
+        let ofx = to_ofx(&rows);
+        assert_eq!(ofx.matches("<STMTTRNRS>").count(), 2);
+        let (usd, eur) = ofx.split_once("</STMTTRNRS>").unwrap();
+        assert!(usd.contains("<CURDEF>USD</CURDEF>"));
+        assert!(usd.contains("<ACCTID>equailizer-USD</ACCTID>"));
+        assert!(usd.contains("<FITID>abc-1</FITID>"));
+        assert!(!usd.contains("<FITID>def-22</FITID>"));
+        assert!(eur.contains("<CURDEF>EUR</CURDEF>"));
+        assert!(eur.contains("<TRNUID>1</TRNUID>"));
+        assert!(eur.contains("<DTSTART>20250309</DTSTART>\n<DTEND>20250309</DTEND>"));
+        assert!(eur.contains("<FITID>def-22</FITID>"));
+    }
+
+    #[test]
+    fn ledger_marks_reconciled_rows_cleared() {
+        let expected = "\
+2025-03-02 * Grocer, Inc
+    ; batch: abc
+    ; transaction: 1
+    ; notes: said \"hi\"
+    ; settlement: 500 / 600
+    Assets:Receivable:Alice  15.00 USD
+    Expenses:Proxy
+
+2025-03-09 ! Couch & Co
+    ; batch: def
+    ; transaction: 22
+    Assets:Receivable:Alice  10.00 USD
+    Expenses:Proxy
+
+";
+        assert_eq!(to_ledger(&rows(), "alice"), expected);
+    }
+
+    #[test]
+    fn beancount_opens_accounts() {
+        let beancount = to_beancount(&rows(), "Mary Jane");
+        assert!(beancount.starts_with(
+            "2025-03-02 open Assets:Receivable:MaryJane\n2025-03-02 open Expenses:Proxy\n\n"
+        ));
+        assert!(beancount.contains("2025-03-02 * \"Grocer, Inc\" \"said \\\"hi\\\"\"\n  batch: \"abc\"\n"));
+        assert!(beancount.contains("2025-03-09 ! \"Couch & Co\" \"\"\n"));
+    }
+}
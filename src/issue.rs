@@ -1,11 +1,20 @@
 use crate::lunch_money::model::transaction::TransactionId;
+use crate::money::{Currency, Money};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Issue {
     AddTagHasChildren(TransactionId),
     SplitTagHasChildren(TransactionId),
+    AmortizeTagHasChildren(TransactionId),
+    InvalidAmortizeTag(TransactionId, String),
     TransactionUpdateError(TransactionId, String),
+    ForeignCurrency(TransactionId, Money, Currency),
+    TaggedPending(TransactionId),
+    /// The batch the transaction (or its split parent) is already in, or
+    /// `None` if it's in the proxy category without being in any batch.
+    AlreadyBatched(TransactionId, Option<String>),
 }
 
 impl fmt::Display for Issue {
@@ -25,9 +34,54 @@ impl fmt::Display for Issue {
                     txn
                 )
             }
+            Issue::AmortizeTagHasChildren(txn) => {
+                write!(
+                    f,
+                    "Transaction was tagged to amortize, but it has children: {}",
+                    txn
+                )
+            }
+            Issue::InvalidAmortizeTag(txn, tag) => {
+                write!(
+                    f,
+                    "Transaction {} has amortize tag '{}' without a valid number of months",
+                    txn, tag
+                )
+            }
             Issue::TransactionUpdateError(txn, e_str) => {
                 write!(f, "Error when updating transaction {}: {}", txn, e_str)
             }
+            Issue::ForeignCurrency(txn, amount, batch_currency) => {
+                write!(
+                    f,
+                    "Transaction {} is in {} ({}), but batches are in {} — it was left out",
+                    txn,
+                    amount.currency(),
+                    amount,
+                    batch_currency
+                )
+            }
+            Issue::TaggedPending(txn) => {
+                write!(
+                    f,
+                    "Transaction {} is tagged but still pending — it will be batched once it posts",
+                    txn
+                )
+            }
+            Issue::AlreadyBatched(txn, Some(batch_id)) => {
+                write!(
+                    f,
+                    "Transaction {} is already in batch {} — it was left out",
+                    txn, batch_id
+                )
+            }
+            Issue::AlreadyBatched(txn, None) => {
+                write!(
+                    f,
+                    "Transaction {} is already in the proxy category but isn't in any batch — it was left out",
+                    txn
+                )
+            }
         }
     }
 }
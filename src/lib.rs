@@ -1,12 +1,22 @@
 #![recursion_limit = "512"]
 
+pub mod amortization;
+pub mod category_map;
 pub mod commands;
 pub mod config;
 pub mod date_helpers;
 pub mod email;
 pub mod error;
+pub mod export;
 pub mod issue;
 pub mod lunch_money;
+pub mod marker;
+pub mod money;
+pub mod payment;
 pub mod persist;
 pub mod plugin;
-pub mod usd;
+pub mod report;
+pub mod review;
+pub mod rules;
+pub mod secret;
+pub mod template;
@@ -1,9 +1,12 @@
 use equailizer::error::Result;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{fmt, layer::*, util::SubscriberInitExt};
 
-pub fn init_tracing() -> Result<WorkerGuard> {
+/// Log to the console and a daily log file. Console logs go to stderr when
+/// stdout is reserved for a command's report.
+pub fn init_tracing(console_to_stderr: bool) -> Result<WorkerGuard> {
     let base_path = equailizer::persist::base_path()?;
     let file_appender = tracing_appender::rolling::daily(base_path, "eq.log");
     let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
@@ -11,7 +14,11 @@ pub fn init_tracing() -> Result<WorkerGuard> {
     let stdout_layer = fmt::layer()
         .with_target(false)
         .with_ansi(false)
-        .with_writer(std::io::stdout)
+        .with_writer(if console_to_stderr {
+            BoxMakeWriter::new(std::io::stderr)
+        } else {
+            BoxMakeWriter::new(std::io::stdout)
+        })
         .with_filter(LevelFilter::INFO);
 
     let file_layer = fmt::layer()
@@ -0,0 +1,38 @@
+use super::LunchMoneyClient;
+use super::http::body_snippet;
+use crate::error::{Error, Result};
+use crate::lunch_money::model::category::Category;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct CategoriesResponse {
+    categories: Vec<Category>,
+}
+
+pub(super) async fn get_all(client: &LunchMoneyClient) -> Result<Vec<Category>> {
+    let request = client
+        .http
+        .client()
+        .get("https://dev.lunchmoney.app/v1/categories")
+        .query(&[("format", "flattened")])
+        .header("Authorization", format!("Bearer {}", client.auth_token.expose()));
+
+    let raw = client.http.send(request).await?;
+    if !raw.status.is_success() {
+        return Err(Error::Api(format!(
+            "get categories: HTTP {}: {}",
+            raw.status,
+            body_snippet(&raw.body)
+        )));
+    }
+
+    let response: CategoriesResponse = serde_json::from_str(&raw.body).map_err(|e| {
+        Error::Api(format!(
+            "get categories: failed to decode response ({e}): {}",
+            body_snippet(&raw.body)
+        ))
+    })?;
+    tracing::debug!(count = response.categories.len(), "Fetched categories");
+    Ok(response.categories)
+}
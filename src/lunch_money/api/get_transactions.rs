@@ -41,7 +41,7 @@ pub(super) async fn get_single(client: &LunchMoneyClient, id: TransactionId) ->
         .http
         .client()
         .get(url)
-        .header("Authorization", format!("Bearer {}", client.auth_token));
+        .header("Authorization", format!("Bearer {}", client.auth_token.expose()));
 
     let raw = client.http.send(request).await?;
     decode_or_api_error::<Transaction>(raw.status, &raw.body, &format!("get transaction {id}"))
@@ -73,7 +73,7 @@ pub(super) async fn get_by_date_range(
         "Fetching transactions by date range"
     );
 
-    let auth_header = format!("Bearer {}", client.auth_token);
+    let auth_header = format!("Bearer {}", client.auth_token.expose());
     let mut all_transactions: Vec<Transaction> = Vec::new();
     let mut offset: u32 = 0;
 
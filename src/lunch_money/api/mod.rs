@@ -1,11 +1,14 @@
+pub mod get_categories;
 pub mod get_transactions;
 pub mod http;
 pub mod update_transaction;
 
 use crate::error::Result;
+use crate::secret::Secret;
 use async_trait::async_trait;
 use chrono::NaiveDate;
 
+use super::model::category::Category;
 use super::model::transaction::{Transaction, TransactionId};
 use http::RateLimitedHttp;
 pub use update_transaction::{
@@ -28,16 +31,17 @@ pub trait LunchMoney: Send + Sync {
         update: TransactionAndSplitUpdate,
     ) -> Result<SplitResponse>;
     async fn unsplit_transaction(&self, parent_id: TransactionId) -> Result<Vec<TransactionId>>;
+    async fn get_categories(&self) -> Result<Vec<Category>>;
 }
 
 pub struct LunchMoneyClient {
-    pub auth_token: String,
+    pub auth_token: Secret,
     pub dry_run: bool,
     pub(crate) http: RateLimitedHttp,
 }
 
 impl LunchMoneyClient {
-    pub fn new(auth_token: String, dry_run: bool) -> Self {
+    pub fn new(auth_token: Secret, dry_run: bool) -> Self {
         Self {
             auth_token,
             dry_run,
@@ -82,4 +86,8 @@ impl LunchMoney for LunchMoneyClient {
     async fn unsplit_transaction(&self, parent_id: TransactionId) -> Result<Vec<TransactionId>> {
         update_transaction::perform_unsplit(self, parent_id).await
     }
+
+    async fn get_categories(&self) -> Result<Vec<Category>> {
+        get_categories::get_all(self).await
+    }
 }
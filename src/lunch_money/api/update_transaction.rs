@@ -2,14 +2,14 @@ use super::LunchMoneyClient;
 use super::http::body_snippet;
 use crate::error::{Error, Result};
 use crate::lunch_money::model::transaction::*;
-use crate::usd::USD;
+use crate::money::Money;
 use chrono::NaiveDate;
 use display_json::DebugAsJson;
 use serde::{Deserialize, Serialize};
 
 pub type TransactionUpdate = (TransactionId, TransactionUpdateItem);
 
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct TransactionUpdateItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payee: Option<String>,
@@ -29,9 +29,10 @@ pub struct TransactionUpdateItem {
 
 pub type SplitUpdate = (TransactionId, Vec<SplitUpdateItem>);
 
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct SplitUpdateItem {
-    pub amount: USD,
+    /// Split amounts are in the parent transaction's currency.
+    pub amount: Money,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payee: Option<String>,
@@ -118,7 +119,7 @@ pub(super) async fn perform_unsplit(
         .http
         .client()
         .post(url)
-        .header("Authorization", format!("Bearer {}", client.auth_token))
+        .header("Authorization", format!("Bearer {}", client.auth_token.expose()))
         .json(&body);
 
     let raw = client.http.send(request).await?;
@@ -173,7 +174,34 @@ async fn execute(
         transaction: Option<&'a TransactionUpdateItem>,
 
         #[serde(skip_serializing_if = "Option::is_none")]
-        split: Option<&'a Vec<SplitUpdateItem>>,
+        split: Option<Vec<RequestSplitItem<'a>>>,
+    }
+
+    // Lunch Money takes split amounts as bare numbers in the parent's currency.
+    #[derive(Debug, Serialize)]
+    struct RequestSplitItem<'a> {
+        amount: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payee: &'a Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        category_id: &'a Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        notes: &'a Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        date: &'a Option<NaiveDate>,
+    }
+
+    fn request_splits(splits: &[SplitUpdateItem]) -> Vec<RequestSplitItem<'_>> {
+        splits
+            .iter()
+            .map(|s| RequestSplitItem {
+                amount: s.amount.amount_string(),
+                payee: &s.payee,
+                category_id: &s.category_id,
+                notes: &s.notes,
+                date: &s.date,
+            })
+            .collect()
     }
 
     let txn_update_body = match &action {
@@ -183,11 +211,11 @@ async fn execute(
         },
         Action::Split(splits) => RequestBodySource {
             transaction: None,
-            split: Some(splits),
+            split: Some(request_splits(splits)),
         },
         Action::UpdateAndSplit(update, splits) => RequestBodySource {
             transaction: Some(update),
-            split: Some(splits),
+            split: Some(request_splits(splits)),
         },
     };
 
@@ -208,7 +236,7 @@ async fn execute(
         .http
         .client()
         .put(url)
-        .header("Authorization", format!("Bearer {}", client.auth_token))
+        .header("Authorization", format!("Bearer {}", client.auth_token.expose()))
         .json(&txn_update_body);
 
     let raw = client.http.send(request).await?;
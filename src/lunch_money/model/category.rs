@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+pub type CategoryId = u32;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Category {
+    pub id: CategoryId,
+    pub name: String,
+    /// Category groups hold other categories and can't be assigned to transactions.
+    #[serde(default)]
+    pub is_group: bool,
+    #[serde(default)]
+    pub archived: bool,
+}
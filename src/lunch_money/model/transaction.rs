@@ -1,16 +1,20 @@
-use crate::usd::USD;
+use crate::money::{Currency, Money};
 use chrono::NaiveDate;
 use display_json::DebugAsJson;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 pub type TransactionId = u32;
 
 #[derive(Deserialize, Serialize, DebugAsJson, PartialEq, Clone)]
+#[serde(try_from = "RawTransaction", into = "RawTransaction")]
 pub struct Transaction {
     pub id: TransactionId,
     pub date: NaiveDate,
     pub payee: String,
-    pub amount: USD, // All my accounts are in dollars. No need for currency complexity just yet.
+    pub amount: Money,
+    /// The amount converted to the account's primary currency by Lunch Money.
+    pub to_base: Option<Decimal>,
     pub plaid_account_id: Option<u32>,
     pub category_id: Option<u32>,
     pub category_name: Option<String>,
@@ -49,6 +53,110 @@ impl Transaction {
     }
 }
 
+/// A transaction as Lunch Money sends it, with the amount and its currency in
+/// separate fields.
+#[derive(Deserialize, Serialize)]
+struct RawTransaction {
+    id: TransactionId,
+    date: NaiveDate,
+    payee: String,
+    amount: String,
+    #[serde(default)]
+    currency: Currency,
+    #[serde(default)]
+    to_base: Option<Decimal>,
+    plaid_account_id: Option<u32>,
+    category_id: Option<u32>,
+    category_name: Option<String>,
+    tags: Vec<Tag>,
+    notes: Option<String>,
+    status: TransactionStatus,
+    parent_id: Option<u32>,
+    has_children: bool,
+    is_pending: bool,
+}
+
+impl TryFrom<RawTransaction> for Transaction {
+    type Error = String;
+
+    fn try_from(raw: RawTransaction) -> Result<Self, Self::Error> {
+        Ok(Transaction {
+            id: raw.id,
+            date: raw.date,
+            payee: raw.payee,
+            amount: Money::parse(&raw.amount, raw.currency)?,
+            to_base: raw.to_base,
+            plaid_account_id: raw.plaid_account_id,
+            category_id: raw.category_id,
+            category_name: raw.category_name,
+            tags: raw.tags,
+            notes: raw.notes,
+            status: raw.status,
+            parent_id: raw.parent_id,
+            has_children: raw.has_children,
+            is_pending: raw.is_pending,
+        })
+    }
+}
+
+impl From<Transaction> for RawTransaction {
+    fn from(txn: Transaction) -> Self {
+        RawTransaction {
+            id: txn.id,
+            date: txn.date,
+            payee: txn.payee,
+            amount: txn.amount.amount_string(),
+            currency: txn.amount.currency(),
+            to_base: txn.to_base,
+            plaid_account_id: txn.plaid_account_id,
+            category_id: txn.category_id,
+            category_name: txn.category_name,
+            tags: txn.tags,
+            notes: txn.notes,
+            status: txn.status,
+            parent_id: txn.parent_id,
+            has_children: txn.has_children,
+            is_pending: txn.is_pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn json(amount: &str, currency: &str) -> String {
+        format!(
+            r#"{{"id": 7, "date": "2025-03-01", "payee": "Café", "amount": "{amount}",
+                "currency": "{currency}", "to_base": 13.02, "plaid_account_id": null,
+                "category_id": null, "category_name": null, "tags": [], "notes": null,
+                "status": "cleared", "parent_id": null, "has_children": false,
+                "is_pending": false}}"#
+        )
+    }
+
+    #[test]
+    fn deserializes_currency_and_to_base() {
+        let txn: Transaction = serde_json::from_str(&json("12.0000", "eur")).unwrap();
+        assert_eq!(txn.amount.value(), dec!(12.00));
+        assert_eq!(txn.amount.currency().code(), "eur");
+        assert_eq!(txn.to_base, Some(dec!(13.02)));
+    }
+
+    #[test]
+    fn rejects_amount_finer_than_currency_allows() {
+        assert!(serde_json::from_str::<Transaction>(&json("1500.5", "jpy")).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let txn: Transaction = serde_json::from_str(&json("-4.50", "gbp")).unwrap();
+        let again: Transaction = serde_json::from_str(&serde_json::to_string(&txn).unwrap()).unwrap();
+        assert_eq!(txn, again);
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use chrono::NaiveDate;
@@ -5,9 +5,10 @@ use equailizer::lunch_money::api::LunchMoneyClient;
 use equailizer::lunch_money::model::transaction::TransactionId;
 use equailizer_plugin::PluginMessage;
 use equailizer::plugin::PluginManager;
-use equailizer::usd::USD;
+use equailizer::money::{Currency, Money};
 use chrono::NaiveDate;
 use clap::Parser;
+use std::path::{Path, PathBuf};
 
 use cli::StartArgs;
 use equailizer::date_helpers::*;
@@ -15,16 +16,20 @@ use equailizer::email::Txn;
 
 #[tokio::main]
 async fn main() {
-    let log_guard = log::init_tracing();
-
     let args = cli::Equailizer::parse();
 
+    let log_guard = log::init_tracing(args.command.prints_to_stdout());
+
     match args.command {
         cli::Commands::CreateBatch {
             start,
             end_date,
             profile,
             dry_run,
+            output,
+            plan,
+            interactive,
+            all_or_nothing,
         } => {
             tracing::info!(
                 command = "create-batch",
@@ -39,8 +44,13 @@ async fn main() {
             };
             match config {
                 Ok(config) => {
-                    match handle_create_batch(start, end_date, &config, &profile, dry_run, &mut plugins).await {
-                        Ok(_) => tracing::info!("Finished create-batch command successfully"),
+                    match handle_create_batch(start, end_date, plan, interactive, all_or_nothing, &config, &profile, dry_run, &mut plugins).await {
+                        Ok(report) => {
+                            if let Some(report) = report {
+                                print_report(output, &report);
+                            }
+                            tracing::info!("Finished create-batch command successfully")
+                        }
                         Err(e) => {
                             plugins.dispatch(&PluginMessage::CommandError {
                                 command: "create-batch".to_string(),
@@ -54,10 +64,99 @@ async fn main() {
             }
             plugins.shutdown().await;
         }
+        cli::Commands::Apply {
+            plan,
+            profile,
+            dry_run,
+            all_or_nothing,
+            output,
+        } => {
+            tracing::info!(
+                command = "apply",
+                profile,
+                plan = %plan.display(),
+                dry_run,
+                "Starting command"
+            );
+            let config = equailizer::config::read_config(&profile);
+            let mut plugins = match &config {
+                Ok(c) => PluginManager::start(&c.plugins, &profile, dry_run).await,
+                Err(_) => PluginManager::empty(),
+            };
+            match config {
+                Ok(config) => {
+                    match handle_apply(&plan, all_or_nothing, &config, &profile, dry_run, &mut plugins).await {
+                        Ok(report) => {
+                            print_report(output, &report);
+                            tracing::info!("Finished apply command successfully")
+                        }
+                        Err(e) => {
+                            plugins.dispatch(&PluginMessage::CommandError {
+                                command: "apply".to_string(),
+                                error: format!("{e:#}"),
+                            }).await;
+                            tracing::error!("{e:#}", e = e);
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+            plugins.shutdown().await;
+        }
+        cli::Commands::Resume {
+            profile,
+            rollback,
+            dry_run,
+            output,
+        } => {
+            tracing::info!(
+                command = "resume",
+                profile,
+                rollback,
+                dry_run,
+                "Starting command"
+            );
+            let config = equailizer::config::read_config(&profile);
+            let mut plugins = match &config {
+                Ok(c) => PluginManager::start(&c.plugins, &profile, dry_run).await,
+                Err(_) => PluginManager::empty(),
+            };
+            match config {
+                Ok(config) => {
+                    match handle_resume(rollback, output, &config, &profile, dry_run, &mut plugins).await {
+                        Ok(()) => tracing::info!("Finished resume command successfully"),
+                        Err(e) => {
+                            plugins.dispatch(&PluginMessage::CommandError {
+                                command: "resume".to_string(),
+                                error: format!("{e:#}"),
+                            }).await;
+                            tracing::error!("{e:#}", e = e);
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+            plugins.shutdown().await;
+        }
+        cli::Commands::Repair {
+            profile,
+            dry_run,
+            output,
+        } => {
+            tracing::info!(command = "repair", profile, dry_run, "Starting command");
+            match handle_repair(&profile, dry_run).await {
+                Ok(report) => {
+                    print_report(output, &report);
+                    tracing::info!("Finished repair command successfully")
+                }
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
         cli::Commands::Reconcile {
             batch_name,
             profile,
             dry_run,
+            output,
         } => {
             tracing::info!(
                 command = "reconcile",
@@ -74,7 +173,10 @@ async fn main() {
             match config {
                 Ok(config) => {
                     match handle_reconcile(batch_name, &config, &profile, dry_run, &mut plugins).await {
-                        Ok(_) => tracing::info!("Finished reconcile command successfully"),
+                        Ok(report) => {
+                            print_report(output, &report);
+                            tracing::info!("Finished reconcile command successfully")
+                        }
                         Err(e) => {
                             plugins.dispatch(&PluginMessage::CommandError {
                                 command: "reconcile".to_string(),
@@ -88,7 +190,11 @@ async fn main() {
             }
             plugins.shutdown().await;
         }
-        cli::Commands::ReconcileAll { profile, dry_run } => {
+        cli::Commands::ReconcileAll {
+            profile,
+            dry_run,
+            output,
+        } => {
             tracing::info!(
                 command = "reconcile-all",
                 profile,
@@ -111,6 +217,7 @@ async fn main() {
                                 result.reconciled,
                                 &result.errors,
                             )).await;
+                            print_report(output, &result.report());
                             tracing::info!("Finished reconcile-all command successfully");
                         }
                         Err(e) => {
@@ -126,6 +233,121 @@ async fn main() {
             }
             plugins.shutdown().await;
         }
+        cli::Commands::Remind { profile, dry_run } => {
+            tracing::info!(command = "remind", profile, dry_run, "Starting command");
+            match handle_remind(&profile, dry_run).await {
+                Ok(_) => tracing::info!("Finished remind command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Daemon { profile, dry_run } => {
+            tracing::info!(command = "daemon", profile, dry_run, "Starting command");
+            let config = equailizer::config::read_config(&profile);
+            let mut plugins = match &config {
+                Ok(c) => PluginManager::start(&c.plugins, &profile, dry_run).await,
+                Err(_) => PluginManager::empty(),
+            };
+            match config {
+                Ok(config) => {
+                    match handle_daemon(&config, &profile, dry_run, &mut plugins).await {
+                        Ok(_) => tracing::info!("Finished daemon command successfully"),
+                        Err(e) => {
+                            plugins.dispatch(&PluginMessage::CommandError {
+                                command: "daemon".to_string(),
+                                error: format!("{e:#}"),
+                            }).await;
+                            tracing::error!("{e:#}", e = e);
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+            plugins.shutdown().await;
+        }
+        cli::Commands::Rules(cli::RulesSubcommand::Test {
+            start,
+            end_date,
+            profile,
+        }) => {
+            tracing::info!(command = "rules test", profile, "Starting command");
+            match handle_rules_test(start, end_date, &profile).await {
+                Ok(_) => tracing::info!("Finished rules test command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Amortize(cli::AmortizeSubcommand::Add {
+            id,
+            months,
+            profile,
+            dry_run,
+        }) => {
+            tracing::info!(command = "amortize add", profile, dry_run, "Starting command");
+            match handle_amortize_add(id, months, &profile, dry_run).await {
+                Ok(_) => tracing::info!("Finished amortize add command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Amortize(cli::AmortizeSubcommand::List { profile }) => {
+            tracing::info!(command = "amortize list", profile, "Starting command");
+            match handle_amortize_list(&profile) {
+                Ok(_) => tracing::info!("Finished amortize list command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Export {
+            profile,
+            format,
+            start_date,
+            end_date,
+            status,
+        } => {
+            tracing::info!(command = "export", profile, ?format, "Starting command");
+            match handle_export(&profile, format, start_date, end_date, status).await {
+                Ok(_) => tracing::info!("Finished export command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Report {
+            profile,
+            from,
+            to,
+            output,
+        } => {
+            tracing::info!(command = "report", profile, "Starting command");
+            match handle_report(&profile, from, to, output).await {
+                Ok(_) => tracing::info!("Finished report command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Balance { profile, output } => {
+            tracing::info!(command = "balance", profile, "Starting command");
+            match handle_balance(&profile, output) {
+                Ok(_) => tracing::info!("Finished balance command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Adjust {
+            profile,
+            kind,
+            amount,
+            date,
+            note,
+            batch_name,
+            dry_run,
+        } => {
+            tracing::info!(command = "adjust", profile, ?kind, dry_run, "Starting command");
+            match handle_adjust(&profile, kind, &amount, date, note, batch_name, dry_run) {
+                Ok(_) => tracing::info!("Finished adjust command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
+        cli::Commands::Categories(cli::CategoriesSubcommand::Suggest { profile }) => {
+            tracing::info!(command = "categories suggest", profile, "Starting command");
+            match handle_categories_suggest(&profile).await {
+                Ok(_) => tracing::info!("Finished categories suggest command successfully"),
+                Err(e) => tracing::error!("{e:#}", e = e),
+            }
+        }
         #[cfg(debug_assertions)]
         cli::Commands::Dev(subcommand) => match subcommand {
             cli::DevSubcommand::Email {} => {
@@ -155,40 +377,197 @@ async fn main() {
     drop(log_guard);
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_create_batch(
     start: StartArgs,
     end_date: Option<NaiveDate>,
+    plan_path: Option<PathBuf>,
+    interactive: bool,
+    all_or_nothing: bool,
     config: &equailizer::config::Config,
     profile: &str,
     dry_run: bool,
     plugins: &mut PluginManager,
-) -> equailizer::error::Result<()> {
+) -> equailizer::error::Result<Option<equailizer::report::BatchReport>> {
     let start_date = cli::start_date_from_args(start);
     let end_date = end_date.or_naive_date_now();
 
     let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
     let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
-    let notifier = equailizer::email::JmapBatchNotifier {
-        api_session_endpoint: config.jmap.api_session_endpoint.clone(),
-        api_key: config.jmap.api_key.clone(),
-        sent_mailbox: config.jmap.sent_mailbox.clone(),
-        sending_address: config.jmap.sending_address.clone(),
-        creditor_email: config.creditor.email_address.clone(),
-        debtor_email: config.debtor.email_address.clone(),
-        debtor_venmo_username: config.debtor.venmo_username.clone(),
-        dry_run,
+
+    // Prompts go to stderr so they don't mix with a report on stdout.
+    let mut terminal =
+        equailizer::review::TerminalReviewer::new(std::io::stdin().lock(), std::io::stderr());
+    let reviewer: Option<&mut dyn equailizer::review::Reviewer> = if interactive {
+        Some(&mut terminal)
+    } else {
+        None
     };
 
-    equailizer::commands::create_batch::create_batch(
+    let mut plan = equailizer::commands::create_batch::plan_batch(
         start_date,
         end_date,
         config,
         &api,
         &persistence,
-        &notifier,
-        plugins,
+        reviewer,
     )
-    .await
+    .await?;
+    plan.all_or_nothing = all_or_nothing;
+
+    if let Some(plan_path) = plan_path {
+        plan.save(&plan_path)?;
+        return Ok(None);
+    }
+
+    let notifier = profile_notifiers(config, profile, dry_run)?;
+
+    equailizer::commands::create_batch::execute_plan(plan, &api, &persistence, &notifier, plugins)
+        .await
+        .map(Some)
+}
+
+async fn handle_apply(
+    plan_path: &Path,
+    all_or_nothing: bool,
+    config: &equailizer::config::Config,
+    profile: &str,
+    dry_run: bool,
+    plugins: &mut PluginManager,
+) -> equailizer::error::Result<equailizer::report::BatchReport> {
+    let mut plan = equailizer::commands::create_batch::plan::BatchPlan::load(plan_path)?;
+    plan.all_or_nothing |= all_or_nothing;
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+    let notifier = profile_notifiers(config, profile, dry_run)?;
+
+    equailizer::commands::apply::apply_plan(plan, &api, &persistence, &notifier, plugins).await
+}
+
+async fn handle_resume(
+    rollback: bool,
+    output: Option<cli::OutputFormat>,
+    config: &equailizer::config::Config,
+    profile: &str,
+    dry_run: bool,
+    plugins: &mut PluginManager,
+) -> equailizer::error::Result<()> {
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+
+    if rollback {
+        if let Some(report) = equailizer::commands::resume::rollback_batch(&api, &persistence).await? {
+            print_report(output, &report);
+        }
+        return Ok(());
+    }
+
+    let notifier = profile_notifiers(config, profile, dry_run)?;
+    if let Some(report) =
+        equailizer::commands::resume::resume_batch(&api, &persistence, &notifier, plugins).await?
+    {
+        print_report(output, &report);
+    }
+    Ok(())
+}
+
+/// Print a command's report to stdout in the requested format, if any.
+fn print_report<T: serde::Serialize + std::fmt::Display>(output: Option<cli::OutputFormat>, report: &T) {
+    match output {
+        Some(cli::OutputFormat::Json) => match serde_json::to_string_pretty(report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => tracing::error!(error = %e, "Failed to serialize report"),
+        },
+        Some(cli::OutputFormat::Table) => print!("{}", report),
+        None => {}
+    }
+}
+
+/// The profile's notifiers: `jmap` or `smtp`, then each enabled entry in
+/// `notifiers`.
+fn profile_notifiers(
+    config: &equailizer::config::Config,
+    profile: &str,
+    dry_run: bool,
+) -> equailizer::error::Result<equailizer::email::Notifiers> {
+    use equailizer::config::NotifierKind;
+    use equailizer::email::{
+        BatchEmails, BatchNotifier, EmailNotifier, EmailTemplates, FileTransport, MailTransport,
+        Party, WebhookTransport,
+    };
+
+    let mut templates_dir = equailizer::persist::base_path()?;
+    templates_dir.push(format!("profiles/{}/templates", profile));
+    let templates = EmailTemplates::load(&templates_dir)?;
+
+    let notifier = |transport: Box<dyn MailTransport>, sending_address: &str, recipients: Vec<Party>| {
+        Box::new(EmailNotifier {
+            emails: BatchEmails {
+                sending_address: sending_address.to_string(),
+                creditor_email: config.creditor.email_address.clone(),
+                debtor_email: config.debtor.email_address.clone(),
+                payment_methods: config.debtor.payment_methods(),
+                templates: templates.clone(),
+            },
+            transport,
+            recipients,
+            dry_run,
+        }) as Box<dyn BatchNotifier>
+    };
+    let both = vec![Party::Creditor, Party::Debtor];
+
+    let mut notifiers = vec![];
+    if let Some(jmap) = &config.jmap {
+        let transport = jmap_transport(jmap);
+        notifiers.push(("jmap".to_string(), notifier(transport, &jmap.sending_address, both.clone())));
+    }
+    if let Some(smtp) = &config.smtp {
+        let transport = smtp_transport(smtp);
+        notifiers.push(("smtp".to_string(), notifier(transport, &smtp.sending_address, both)));
+    }
+    for entry in config.notifiers.iter().filter(|n| n.enabled) {
+        // File and webhook emails don't go through a mailbox, so they're from the creditor.
+        let (transport, sending_address): (Box<dyn MailTransport>, &str) = match &entry.kind {
+            NotifierKind::Jmap(jmap) => (jmap_transport(jmap), &jmap.sending_address),
+            NotifierKind::Smtp(smtp) => (smtp_transport(smtp), &smtp.sending_address),
+            NotifierKind::File { dir } => (
+                Box::new(FileTransport { dir: dir.as_ref().map(std::path::PathBuf::from) }),
+                &config.creditor.email_address,
+            ),
+            NotifierKind::Webhook { url, headers } => (
+                Box::new(WebhookTransport { url: url.clone(), headers: headers.clone() }),
+                &config.creditor.email_address,
+            ),
+        };
+        notifiers.push((
+            entry.name().to_string(),
+            notifier(transport, sending_address, entry.recipients.clone()),
+        ));
+    }
+    if notifiers.is_empty() {
+        tracing::warn!("No notifiers are enabled; no emails will be sent");
+    }
+
+    Ok(equailizer::email::Notifiers { notifiers })
+}
+
+fn jmap_transport(jmap: &equailizer::config::JMAP) -> Box<dyn equailizer::email::MailTransport> {
+    Box::new(equailizer::email::JmapTransport {
+        api_session_endpoint: jmap.api_session_endpoint.clone(),
+        api_key: jmap.api_key.clone(),
+        sent_mailbox: jmap.sent_mailbox.clone(),
+        sending_address: jmap.sending_address.clone(),
+    })
+}
+
+fn smtp_transport(smtp: &equailizer::config::Smtp) -> Box<dyn equailizer::email::MailTransport> {
+    Box::new(equailizer::email::SmtpTransport {
+        host: smtp.host.clone(),
+        port: smtp.port(),
+        tls: smtp.tls,
+        username: smtp.username.clone(),
+        password: smtp.password.clone(),
+    })
 }
 
 async fn handle_reconcile(
@@ -197,10 +576,11 @@ async fn handle_reconcile(
     profile: &str,
     dry_run: bool,
     plugins: &mut PluginManager,
-) -> equailizer::error::Result<()> {
+) -> equailizer::error::Result<equailizer::report::ReconcileReport> {
     let creditor_api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
     let debtor_api = LunchMoneyClient::new(config.debtor.api_key.clone(), dry_run);
     let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+    let notifier = profile_notifiers(config, profile, dry_run)?;
 
     equailizer::commands::reconcile::reconcile_batch_name(
         &batch_name,
@@ -208,6 +588,7 @@ async fn handle_reconcile(
         &creditor_api,
         &debtor_api,
         &persistence,
+        &notifier,
         plugins,
     )
     .await
@@ -222,17 +603,300 @@ async fn handle_reconcile_all(
     let creditor_api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
     let debtor_api = LunchMoneyClient::new(config.debtor.api_key.clone(), dry_run);
     let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+    let notifier = profile_notifiers(config, profile, dry_run)?;
 
     equailizer::commands::reconcile::reconcile_all(
         config,
         &creditor_api,
         &debtor_api,
         &persistence,
+        &notifier,
         plugins,
     )
     .await
 }
 
+async fn handle_remind(profile: &str, dry_run: bool) -> equailizer::error::Result<()> {
+    let config = equailizer::config::read_config(profile)?;
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+    let notifier = profile_notifiers(&config, profile, dry_run)?;
+
+    let reminded = equailizer::commands::remind::remind_overdue(
+        equailizer::date_helpers::now_date_naive_eastern(),
+        &config,
+        &api,
+        &persistence,
+        &notifier,
+    )
+    .await?;
+    tracing::info!(count = reminded.len(), "Reminders sent");
+
+    Ok(())
+}
+
+async fn handle_daemon(
+    config: &equailizer::config::Config,
+    profile: &str,
+    dry_run: bool,
+    plugins: &mut PluginManager,
+) -> equailizer::error::Result<()> {
+    let creditor_api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let debtor_api = LunchMoneyClient::new(config.debtor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+    let notifier = profile_notifiers(config, profile, dry_run)?;
+
+    equailizer::commands::daemon::run_daemon(
+        config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        plugins,
+        shutdown_signal(),
+    )
+    .await
+}
+
+/// Resolves on SIGTERM or SIGINT. The handlers are installed before this
+/// returns, so a signal that arrives while a job is running isn't lost.
+fn shutdown_signal() -> impl std::future::Future<Output = ()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    let mut sigint = signal(SignalKind::interrupt()).expect("failed to install SIGINT handler");
+    async move {
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+    }
+}
+
+async fn handle_rules_test(
+    start: StartArgs,
+    end_date: Option<NaiveDate>,
+    profile: &str,
+) -> equailizer::error::Result<()> {
+    let start_date = cli::start_date_from_args(start);
+    let end_date = end_date.or_naive_date_now();
+    let config = equailizer::config::read_config(profile)?;
+    // Rules testing never writes, so the client always runs as a dry run.
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), true);
+
+    let results =
+        equailizer::commands::rules::test_rules(start_date, end_date, &config, &api).await?;
+
+    for result in &results {
+        tracing::info!(
+            rule = %result.rule_name,
+            action = ?result.action,
+            matches = result.matches.len(),
+            "Rule"
+        );
+        for t in &result.matches {
+            tracing::info!(
+                rule = %result.rule_name,
+                id = t.id,
+                date = %t.date,
+                amount = %t.amount,
+                payee = %t.payee,
+                category = ?t.category_name,
+                "Matched transaction"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_repair(
+    profile: &str,
+    dry_run: bool,
+) -> equailizer::error::Result<equailizer::report::RepairReport> {
+    let config = equailizer::config::read_config(profile)?;
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+
+    equailizer::commands::repair::repair_splits(&api, &persistence).await
+}
+
+async fn handle_amortize_add(
+    id: TransactionId,
+    months: u32,
+    profile: &str,
+    dry_run: bool,
+) -> equailizer::error::Result<()> {
+    let config = equailizer::config::read_config(profile)?;
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), dry_run);
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+
+    let schedule = equailizer::commands::amortize::amortize_transaction(
+        id,
+        months,
+        &config,
+        &api,
+        &persistence,
+    )
+    .await?;
+
+    for i in &schedule.installments {
+        tracing::info!(
+            number = i.number,
+            due = %i.due,
+            amount = %i.amount,
+            "Installment"
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_amortize_list(profile: &str) -> equailizer::error::Result<()> {
+    use equailizer::persist::Persistence;
+
+    // Listing never writes, so persistence always runs as a dry run.
+    let persistence = equailizer::persist::FilePersistence::new(profile, true)?;
+
+    for schedule in persistence.all_schedules()? {
+        tracing::info!(
+            schedule_id = %schedule.id,
+            txn_id = schedule.transaction_id,
+            payee = %schedule.payee,
+            total = %schedule.total,
+            billed = schedule.billed_count(),
+            count = schedule.count(),
+            complete = schedule.is_complete(),
+            "Amortization schedule"
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_export(
+    profile: &str,
+    format: cli::ExportFormat,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    status: Option<cli::ReconciledStatus>,
+) -> equailizer::error::Result<()> {
+    use equailizer::export;
+
+    let config = equailizer::config::read_config(profile)?;
+    // Exporting never writes, so the client and persistence always run as a dry run.
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), true);
+    let persistence = equailizer::persist::FilePersistence::new(profile, true)?;
+
+    let filter = equailizer::commands::export::ExportFilter {
+        start_date,
+        end_date,
+        reconciled: status.map(|s| matches!(s, cli::ReconciledStatus::Reconciled)),
+    };
+    let rows = equailizer::commands::export::export_batches(&filter, &api, &persistence).await?;
+
+    let out = match format {
+        cli::ExportFormat::Csv => export::to_csv(&rows),
+        cli::ExportFormat::Ofx => export::to_ofx(&rows),
+        cli::ExportFormat::Ledger => export::to_ledger(&rows, &config.debtor.name),
+        cli::ExportFormat::Beancount => export::to_beancount(&rows, &config.debtor.name),
+    };
+    print!("{}", out);
+
+    Ok(())
+}
+
+async fn handle_report(
+    profile: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    output: cli::OutputFormat,
+) -> equailizer::error::Result<()> {
+    let config = equailizer::config::read_config(profile)?;
+    // Reporting never writes, so the client and persistence always run as a dry run.
+    let api = LunchMoneyClient::new(config.creditor.api_key.clone(), true);
+    let persistence = equailizer::persist::FilePersistence::new(profile, true)?;
+
+    let report =
+        equailizer::commands::spending::spending_report(from, to, &config, &api, &persistence)
+            .await?;
+    print_report(Some(output), &report);
+
+    Ok(())
+}
+
+fn handle_balance(profile: &str, output: cli::OutputFormat) -> equailizer::error::Result<()> {
+    let config = equailizer::config::read_config(profile)?;
+    // The balance never writes, so persistence always runs as a dry run.
+    let persistence = equailizer::persist::FilePersistence::new(profile, true)?;
+
+    let report = equailizer::commands::balance::balance(
+        equailizer::date_helpers::now_date_naive_eastern(),
+        &config,
+        &persistence,
+    )?;
+    print_report(Some(output), &report);
+
+    Ok(())
+}
+
+fn handle_adjust(
+    profile: &str,
+    kind: cli::AdjustKind,
+    amount: &str,
+    date: Option<NaiveDate>,
+    note: Option<String>,
+    batch_name: Option<String>,
+    dry_run: bool,
+) -> equailizer::error::Result<()> {
+    use equailizer::persist::AdjustmentKind;
+
+    let config = equailizer::config::read_config(profile)?;
+    let persistence = equailizer::persist::FilePersistence::new(profile, dry_run)?;
+
+    let amount = equailizer::money::Money::parse(amount, config.creditor.currency)
+        .map_err(equailizer::error::Error::InvalidAdjustment)?;
+    let kind = match kind {
+        cli::AdjustKind::Payment => AdjustmentKind::Payment,
+        cli::AdjustKind::Forgiven => AdjustmentKind::Forgiven,
+        cli::AdjustKind::Charge => AdjustmentKind::Charge,
+    };
+    equailizer::commands::balance::record_adjustment(
+        kind,
+        amount,
+        date.unwrap_or_else(equailizer::date_helpers::now_date_naive_eastern),
+        note,
+        batch_name,
+        &config,
+        &persistence,
+    )?;
+
+    Ok(())
+}
+
+async fn handle_categories_suggest(profile: &str) -> equailizer::error::Result<()> {
+    let config = equailizer::config::read_config(profile)?;
+    // Suggesting never writes, so the clients always run as a dry run.
+    let creditor_api = LunchMoneyClient::new(config.creditor.api_key.clone(), true);
+    let debtor_api = LunchMoneyClient::new(config.debtor.api_key.clone(), true);
+
+    let suggestion = equailizer::commands::categories::suggest_category_map(
+        &config,
+        &creditor_api,
+        &debtor_api,
+    )
+    .await?;
+
+    for category in &suggestion.unmatched {
+        tracing::info!(id = category.id, name = %category.name, "No matching debtor category");
+    }
+    // Printed as a config snippet, ready to paste into config.json.
+    let snippet = serde_json::json!({ "category_map": suggestion.category_map });
+    println!("{}", serde_json::to_string_pretty(&snippet)?);
+
+    Ok(())
+}
+
 fn handle_dev_email() {
     let d = |m: u32, d: u32| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
 
@@ -240,88 +904,99 @@ fn handle_dev_email() {
         // Monday - 2 transactions
         Txn {
             payee: "Food Garden Market".to_string(),
-            amount: USD::new_from_cents(3736),
+            amount: Money::new_from_minor(3736, Currency::USD),
             date: d(2, 23),
             notes: None,
+            installment: None,
         },
         Txn {
             payee: "Bagel Pub Park Slope".to_string(),
-            amount: USD::new_from_cents(1811),
+            amount: Money::new_from_minor(1811, Currency::USD),
             date: d(2, 23),
             notes: None,
+            installment: None,
         },
         // Tuesday - 1 transaction
         Txn {
             payee: "Verizon".to_string(),
-            amount: USD::new_from_cents(6499),
+            amount: Money::new_from_minor(6499, Currency::USD),
             date: d(2, 24),
             notes: Some("monthly bill".to_string()),
+            installment: None,
         },
         // Wednesday - 0 transactions (skipped)
         // Thursday - 4 transactions
         Txn {
             payee: "Ozakaya".to_string(),
-            amount: USD::new_from_cents(9686),
+            amount: Money::new_from_minor(9686, Currency::USD),
             date: d(2, 26),
             notes: None,
+            installment: None,
         },
         Txn {
             payee: "Winner On 5th".to_string(),
-            amount: USD::new_from_cents(3850),
+            amount: Money::new_from_minor(3850, Currency::USD),
             date: d(2, 26),
             notes: None,
+            installment: None,
         },
         Txn {
             payee: "Lemonade Insurance".to_string(),
-            amount: USD::new_from_cents(24817),
+            amount: Money::new_from_minor(24817, Currency::USD),
             date: d(2, 26),
             notes: Some("renters + pet".to_string()),
+            installment: None,
         },
         Txn {
             payee: "Food Garden Market".to_string(),
-            amount: USD::new_from_cents(1553),
+            amount: Money::new_from_minor(1553, Currency::USD),
             date: d(2, 26),
             notes: None,
+            installment: None,
         },
         // Friday - 0 transactions (skipped)
         // Saturday - 3 transactions
         Txn {
             payee: "Con Edison".to_string(),
-            amount: USD::new_from_cents(12521),
+            amount: Money::new_from_minor(12521, Currency::USD),
             date: d(3, 1),
             notes: Some("electric".to_string()),
+            installment: None,
         },
         Txn {
             payee: "National Grid".to_string(),
-            amount: USD::new_from_cents(17413),
+            amount: Money::new_from_minor(17413, Currency::USD),
             date: d(3, 1),
             notes: Some("gas".to_string()),
+            installment: None,
         },
         Txn {
             payee: "King David Tacos".to_string(),
-            amount: USD::new_from_cents(2724),
+            amount: Money::new_from_minor(2724, Currency::USD),
             date: d(3, 1),
             notes: None,
+            installment: None,
         },
         // Sunday - 1 transaction
         Txn {
             payee: "Doggieacademy".to_string(),
-            amount: USD::new_from_cents(13609),
+            amount: Money::new_from_minor(13609, Currency::USD),
             date: d(3, 2),
             notes: Some("daycare".to_string()),
+            installment: None,
         },
     ];
 
     let warnings = vec!["Test warning: could not find something".to_string()];
 
-    let total = USD::new_from_cents(134219);
+    let total = Money::new_from_minor(134219, Currency::USD);
     equailizer::email::dev_print(&uuid::Uuid::new_v4().to_string(), txns, warnings, &total);
 }
 
 async fn handle_dev_txn(id: TransactionId, profile: String) {
     use equailizer::lunch_money::api::LunchMoney;
     let config = equailizer::config::read_config(&profile).expect("failed reading config");
-    let client = LunchMoneyClient::new(config.creditor.api_key.to_owned(), false);
+    let client = LunchMoneyClient::new(config.creditor.api_key.clone(), false);
     let txn = client
         .get_transaction(id)
         .await
@@ -332,7 +1007,7 @@ async fn handle_dev_txn(id: TransactionId, profile: String) {
 async fn handle_dev_split_children(id: TransactionId, profile: String) {
     use equailizer::lunch_money::api::LunchMoney;
     let config = equailizer::config::read_config(&profile).expect("failed reading config");
-    let client = LunchMoneyClient::new(config.creditor.api_key.to_owned(), false);
+    let client = LunchMoneyClient::new(config.creditor.api_key.clone(), false);
 
     let parent = client
         .get_transaction(id)
@@ -393,7 +1068,7 @@ async fn handle_dev_list(
     use equailizer::lunch_money::model::transaction::TransactionStatus;
 
     let config = equailizer::config::read_config(&profile).expect("failed reading config");
-    let client = LunchMoneyClient::new(config.creditor.api_key.to_owned(), false);
+    let client = LunchMoneyClient::new(config.creditor.api_key.clone(), false);
 
     let txns = client
         .get_transactions(start, end)
@@ -0,0 +1,161 @@
+use crate::error::Result;
+use crate::lunch_money::api::update_transaction::TransactionUpdateItem;
+use crate::lunch_money::api::LunchMoney;
+use crate::lunch_money::model::transaction::{Transaction, TransactionId};
+use serde::{Deserialize, Serialize};
+
+/// A reference to its batch written onto each batched transaction, so the
+/// batch can be found from the Lunch Money UI.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchMarker {
+    #[serde(default)]
+    pub target: MarkerTarget,
+    /// `{batch_id}` is replaced with the batch id and `{short_id}` with its
+    /// first 8 characters.
+    #[serde(default = "default_template")]
+    pub template: String,
+}
+
+/// Where the marker goes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerTarget {
+    /// Appended to the transaction's notes.
+    #[default]
+    Notes,
+    /// Added as a tag, one per batch.
+    Tag,
+}
+
+fn default_template() -> String {
+    "eq-batch-{short_id}".to_string()
+}
+
+impl BatchMarker {
+    pub fn render(&self, batch_id: &str) -> String {
+        let short_id: String = batch_id.chars().take(8).collect();
+        self.template
+            .replace("{batch_id}", batch_id)
+            .replace("{short_id}", &short_id)
+    }
+
+    /// The update that adds the marker to `txn`, or `None` if it already has it.
+    pub fn update_for(&self, txn: &Transaction, batch_id: &str) -> Option<TransactionUpdateItem> {
+        let marker = self.render(batch_id);
+        let mut update = TransactionUpdateItem {
+            payee: None,
+            category_id: None,
+            notes: None,
+            tags: None,
+            status: None,
+        };
+        match self.target {
+            MarkerTarget::Notes => {
+                update.notes = Some(match txn.notes.as_deref() {
+                    Some(notes) if notes.contains(&marker) => return None,
+                    Some(notes) if !notes.is_empty() => format!("{notes} {marker}"),
+                    _ => marker,
+                });
+            }
+            MarkerTarget::Tag => {
+                let mut tags: Vec<String> = txn.tag_names().into_iter().cloned().collect();
+                if tags.contains(&marker) {
+                    return None;
+                }
+                tags.push(marker);
+                update.tags = Some(tags);
+            }
+        }
+        Some(update)
+    }
+
+    /// Write the marker onto the transaction as it currently is in Lunch Money.
+    pub async fn mark(
+        &self,
+        id: TransactionId,
+        batch_id: &str,
+        api: &(impl LunchMoney + Sync),
+    ) -> Result<()> {
+        let txn = api.get_transaction(id).await?;
+        match self.update_for(&txn, batch_id) {
+            Some(update) => api.update_transaction((id, update)).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lunch_money::model::transaction::{Tag, TransactionStatus};
+    use crate::money::{Currency, Money};
+    use chrono::NaiveDate;
+
+    const BATCH_ID: &str = "0123456789abcdef";
+
+    fn txn(notes: Option<&str>, tags: Vec<&str>) -> Transaction {
+        Transaction {
+            id: 1,
+            date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            payee: "Grocer".to_string(),
+            amount: Money::new_from_minor(1500, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: None,
+            category_name: None,
+            tags: tags
+                .into_iter()
+                .map(|name| Tag { name: name.to_string(), id: 0 })
+                .collect(),
+            notes: notes.map(str::to_string),
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        }
+    }
+
+    fn marker(target: MarkerTarget) -> BatchMarker {
+        BatchMarker {
+            target,
+            template: default_template(),
+        }
+    }
+
+    #[test]
+    fn render_fills_in_batch_id() {
+        let marker = BatchMarker {
+            target: MarkerTarget::Notes,
+            template: "batch {batch_id} ({short_id})".to_string(),
+        };
+        assert_eq!(marker.render(BATCH_ID), "batch 0123456789abcdef (01234567)");
+    }
+
+    #[test]
+    fn notes_marker_is_appended_once() {
+        let marker = marker(MarkerTarget::Notes);
+
+        let update = marker.update_for(&txn(Some("Dinner"), vec![]), BATCH_ID).unwrap();
+        assert_eq!(update.notes.as_deref(), Some("Dinner eq-batch-01234567"));
+        assert_eq!(update.tags, None);
+
+        let update = marker.update_for(&txn(None, vec![]), BATCH_ID).unwrap();
+        assert_eq!(update.notes.as_deref(), Some("eq-batch-01234567"));
+
+        assert_eq!(marker.update_for(&txn(Some("Dinner eq-batch-01234567"), vec![]), BATCH_ID), None);
+    }
+
+    #[test]
+    fn tag_marker_keeps_existing_tags() {
+        let marker = marker(MarkerTarget::Tag);
+
+        let update = marker.update_for(&txn(None, vec!["food"]), BATCH_ID).unwrap();
+        assert_eq!(
+            update.tags,
+            Some(vec!["food".to_string(), "eq-batch-01234567".to_string()])
+        );
+        assert_eq!(update.notes, None);
+
+        assert_eq!(marker.update_for(&txn(None, vec!["eq-batch-01234567"]), BATCH_ID), None);
+    }
+}
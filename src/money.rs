@@ -0,0 +1,468 @@
+use rand::random_bool;
+use rust_decimal::{Decimal, RoundingStrategy, dec};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An ISO 4217 currency code. Lunch Money sends codes in lower case, which is
+/// how they're stored and serialized; they're displayed in upper case.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const USD: Currency = Currency(*b"usd");
+
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("currency codes are ASCII")
+    }
+
+    /// Number of decimal places used for amounts in this currency.
+    pub fn minor_units(&self) -> u32 {
+        match self.code() {
+            "bif" | "clp" | "djf" | "gnf" | "isk" | "jpy" | "kmf" | "krw" | "pyg" | "rwf"
+            | "ugx" | "vnd" | "vuv" | "xaf" | "xof" | "xpf" => 0,
+            "bhd" | "iqd" | "jod" | "kwd" | "lyd" | "omr" | "tnd" => 3,
+            _ => 2,
+        }
+    }
+
+    fn symbol(&self) -> Option<&'static str> {
+        match self.code() {
+            "usd" => Some("$"),
+            "eur" => Some("€"),
+            "gbp" => Some("£"),
+            "jpy" => Some("¥"),
+            "inr" => Some("₹"),
+            "krw" => Some("₩"),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::USD
+    }
+}
+
+impl FromStr for Currency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 3 || !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return Err(format!("invalid currency code '{s}'"));
+        }
+        Ok(Currency([
+            bytes[0].to_ascii_lowercase(),
+            bytes[1].to_ascii_lowercase(),
+            bytes[2].to_ascii_lowercase(),
+        ]))
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code().to_ascii_uppercase())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// An amount in a specific currency, rounded to that currency's minor units.
+///
+/// Arithmetic between two amounts requires them to be in the same currency
+/// and panics otherwise — batches are restricted to a single currency before
+/// any amounts are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Money {
+    value: Decimal,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(value: Decimal, currency: Currency) -> Self {
+        Self {
+            value: value.round_dp(currency.minor_units()),
+            currency,
+        }
+    }
+
+    /// An amount given in the currency's minor units, e.g. cents.
+    pub fn new_from_minor(units: i64, currency: Currency) -> Self {
+        Self {
+            value: Decimal::new(units, currency.minor_units()),
+            currency,
+        }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    pub fn value(&self) -> Decimal {
+        self.value
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// The bare amount with the currency's number of decimal places, e.g.
+    /// `"12.50"`, as used in API calls and payment links.
+    pub fn amount_string(&self) -> String {
+        format!("{:.*}", self.currency.minor_units() as usize, self.value)
+    }
+
+    /// Parse an amount string for `currency`, rejecting precision finer than
+    /// the currency's minor units (trailing zeros are fine).
+    pub fn parse(s: &str, currency: Currency) -> Result<Self, String> {
+        let decimal = s
+            .parse::<Decimal>()
+            .map_err(|e| format!("invalid decimal format: {}", e))?;
+        if decimal.normalize().scale() > currency.minor_units() {
+            return Err(format!(
+                "{} values cannot have non-zero digits beyond {} decimal places",
+                currency,
+                currency.minor_units()
+            ));
+        }
+        Ok(Money::new(decimal, currency))
+    }
+
+    pub fn random_rounded_even_split(&self) -> (Money, Money) {
+        let dp = self.currency.minor_units();
+        let half1 = (self.value / dec!(2)).round_dp_with_strategy(dp, RoundingStrategy::AwayFromZero);
+        let half2 = (self.value / dec!(2)).round_dp_with_strategy(dp, RoundingStrategy::ToZero);
+        assert_eq!(
+            self.value,
+            (half1 + half2),
+            "rounded splits not equal to starting total"
+        );
+
+        if random_bool(0.5) {
+            (self.with_value(half1), self.with_value(half2))
+        } else {
+            (self.with_value(half2), self.with_value(half1))
+        }
+    }
+
+    /// Split into (creditor, debtor) amounts with the debtor paying `debtor_share`
+    /// of the total. The debtor amount is rounded to the minor unit and the
+    /// creditor takes the remainder, so the two always sum to the original amount.
+    pub fn split_by_ratio(&self, debtor_share: Decimal) -> (Money, Money) {
+        let debtor = (self.value * debtor_share).round_dp_with_strategy(
+            self.currency.minor_units(),
+            RoundingStrategy::MidpointAwayFromZero,
+        );
+        (self.with_value(self.value - debtor), self.with_value(debtor))
+    }
+
+    fn with_value(&self, value: Decimal) -> Money {
+        Money::new(value, self.currency)
+    }
+
+    fn assert_same_currency(&self, other: &Money) {
+        assert_eq!(
+            self.currency, other.currency,
+            "cannot combine amounts in different currencies"
+        );
+    }
+}
+
+/// Formatted for people, e.g. `$12.50`, `€8.00` or `120.00 CHF`.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.value.is_sign_negative() && !self.value.is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        let amount = Money::new(self.value.abs(), self.currency).amount_string();
+        match self.currency.symbol() {
+            Some(symbol) => write!(f, "{sign}{symbol}{amount}"),
+            None => write!(f, "{sign}{amount} {}", self.currency),
+        }
+    }
+}
+
+/// Amounts in different currencies aren't comparable.
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Money) -> Option<Ordering> {
+        (self.currency == other.currency).then(|| self.value.cmp(&other.value))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        self.assert_same_currency(&other);
+        self.with_value(self.value + other.value)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        self.assert_same_currency(&other);
+        self.with_value(self.value - other.value)
+    }
+}
+
+impl Mul<Decimal> for Money {
+    type Output = Money;
+
+    fn mul(self, factor: Decimal) -> Money {
+        self.with_value(self.value * factor)
+    }
+}
+
+impl Div<Decimal> for Money {
+    type Output = Money;
+
+    fn div(self, divisor: Decimal) -> Money {
+        self.with_value(self.value / divisor)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Self::Output {
+        self.with_value(-self.value)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoneyJson {
+    amount: String,
+    currency: Currency,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyRepr {
+    Object(MoneyJson),
+    /// Batches saved before amounts carried a currency were all in dollars.
+    Legacy(String),
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (amount, currency) = match MoneyRepr::deserialize(deserializer)? {
+            MoneyRepr::Object(json) => (json.amount, json.currency),
+            MoneyRepr::Legacy(amount) => (amount, Currency::USD),
+        };
+        Money::parse(&amount, currency).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        MoneyJson {
+            amount: self.amount_string(),
+            currency: self.currency,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn usd(cents: i64) -> Money {
+        Money::new_from_minor(cents, Currency::USD)
+    }
+
+    fn eur(cents: i64) -> Money {
+        Money::new_from_minor(cents, "EUR".parse().unwrap())
+    }
+
+    #[test]
+    fn new_from_minor() {
+        assert_eq!(usd(1234).value(), dec!(12.34));
+        let jpy: Currency = "jpy".parse().unwrap();
+        assert_eq!(Money::new_from_minor(1234, jpy).value(), dec!(1234));
+    }
+
+    #[test]
+    fn new_rounds_to_minor_units() {
+        assert_eq!(Money::new(dec!(1.999), Currency::USD).value(), dec!(2.00));
+        let kwd: Currency = "kwd".parse().unwrap();
+        assert_eq!(Money::new(dec!(1.2346), kwd).value(), dec!(1.235));
+    }
+
+    #[test]
+    fn currency_parse_and_display() {
+        let chf: Currency = "CHF".parse().unwrap();
+        assert_eq!(chf.code(), "chf");
+        assert_eq!(chf.to_string(), "CHF");
+        assert!("us".parse::<Currency>().is_err());
+        assert!("u$d".parse::<Currency>().is_err());
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(usd(100) + usd(250), usd(350));
+    }
+
+    #[test]
+    #[should_panic(expected = "different currencies")]
+    fn add_rejects_mixed_currencies() {
+        let _ = usd(100) + eur(100);
+    }
+
+    #[test]
+    fn sub() {
+        assert_eq!(usd(500) - usd(150), usd(350));
+    }
+
+    #[test]
+    fn neg() {
+        assert_eq!(-usd(500), usd(-500));
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(usd(1000) * dec!(1.5), usd(1500));
+    }
+
+    #[test]
+    fn div() {
+        assert_eq!(usd(1000) / dec!(4), usd(250));
+    }
+
+    #[test]
+    fn compare_only_within_currency() {
+        assert!(usd(100) < usd(200));
+        assert_eq!(usd(100).partial_cmp(&eur(200)), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", usd(1234)), "$12.34");
+        assert_eq!(format!("{}", usd(-4000)), "-$40.00");
+        assert_eq!(format!("{}", eur(800)), "€8.00");
+        let chf: Currency = "chf".parse().unwrap();
+        assert_eq!(format!("{}", Money::new_from_minor(12000, chf)), "120.00 CHF");
+        let jpy: Currency = "jpy".parse().unwrap();
+        assert_eq!(format!("{}", Money::new(dec!(1500), jpy)), "¥1500");
+    }
+
+    #[test]
+    fn amount_string_pads_to_minor_units() {
+        assert_eq!(Money::new(dec!(40), Currency::USD).amount_string(), "40.00");
+        assert_eq!(usd(-1050).amount_string(), "-10.50");
+    }
+
+    #[test]
+    fn random_even_split_sums_to_original() {
+        // Test with even amount
+        let even = usd(1000);
+        let (a, b) = even.random_rounded_even_split();
+        assert_eq!(a + b, even);
+
+        // Test with odd cent amount
+        let odd = usd(1001);
+        let (a, b) = odd.random_rounded_even_split();
+        assert_eq!(a + b, odd);
+    }
+
+    #[test]
+    fn random_even_split_halves_are_close() {
+        let amount = usd(1001);
+        let (a, b) = amount.random_rounded_even_split();
+        // One should be 5.01, the other 5.00
+        let max = std::cmp::max(a.value(), b.value());
+        let min = std::cmp::min(a.value(), b.value());
+        assert!(max - min <= dec!(0.01));
+    }
+
+    #[test]
+    fn random_even_split_respects_minor_units() {
+        let jpy: Currency = "jpy".parse().unwrap();
+        let (a, b) = Money::new(dec!(1001), jpy).random_rounded_even_split();
+        assert_eq!(a.value().max(b.value()), dec!(501));
+        assert_eq!(a.value().min(b.value()), dec!(500));
+    }
+
+    #[test]
+    fn split_by_ratio_sums_to_original() {
+        let amount = usd(1001);
+        let (creditor, debtor) = amount.split_by_ratio(dec!(0.6));
+        assert_eq!(debtor, usd(601));
+        assert_eq!(creditor, usd(400));
+        assert_eq!(creditor + debtor, amount);
+    }
+
+    #[test]
+    fn deserialize_object() {
+        let money: Money = serde_json::from_str(r#"{"amount": "12.34", "currency": "eur"}"#).unwrap();
+        assert_eq!(money, eur(1234));
+    }
+
+    #[test]
+    fn deserialize_legacy_string_as_usd() {
+        let money: Money = serde_json::from_str("\"12.34\"").unwrap();
+        assert_eq!(money, usd(1234));
+    }
+
+    #[test]
+    fn deserialize_rejects_sub_minor_precision() {
+        let result: Result<Money, _> = serde_json::from_str("\"12.345\"");
+        assert!(result.is_err());
+        let result: Result<Money, _> =
+            serde_json::from_str(r#"{"amount": "12.5", "currency": "jpy"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_allows_trailing_zeros() {
+        let money: Money = serde_json::from_str("\"12.3400\"").unwrap();
+        assert_eq!(money, usd(1234));
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        let original = eur(4299);
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(json, r#"{"amount":"42.99","currency":"eur"}"#);
+        let deserialized: Money = serde_json::from_str(&json).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
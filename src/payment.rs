@@ -0,0 +1,122 @@
+use crate::money::{Currency, Money};
+use serde::{Deserialize, Serialize};
+
+/// A way the debtor can settle a batch, from `debtor.payment_methods`.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum PaymentMethod {
+    /// The creditor requests the money from the debtor's Venmo account.
+    Venmo { username: String },
+    /// The debtor pays the creditor's PayPal.Me link.
+    Paypal { username: String },
+    /// The debtor pays the creditor's Cash App `$cashtag`, given without the `$`.
+    CashApp { cashtag: String },
+    /// The debtor sends a Zelle payment to `recipient`, the creditor's email
+    /// address or phone number. Zelle has no payment links.
+    Zelle { recipient: String },
+}
+
+/// How to pay one batch with one method.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PaymentRequest {
+    pub provider: &'static str,
+    /// A link that starts the payment, for providers that have them.
+    pub link: Option<String>,
+    /// The recipient, amount and memo, in words.
+    pub instructions: String,
+    /// Whether the creditor starts the payment by sending a request, rather
+    /// than the debtor paying.
+    pub requested_by_creditor: bool,
+}
+
+impl PaymentMethod {
+    pub fn provider(&self) -> &'static str {
+        match self {
+            PaymentMethod::Venmo { .. } => "Venmo",
+            PaymentMethod::Paypal { .. } => "PayPal",
+            PaymentMethod::CashApp { .. } => "Cash App",
+            PaymentMethod::Zelle { .. } => "Zelle",
+        }
+    }
+
+    /// How to pay `amount` with this method, with `memo` as the payment's note.
+    pub fn request(&self, memo: &str, amount: &Money) -> PaymentRequest {
+        let (link, instructions) = match self {
+            PaymentMethod::Venmo { username } => {
+                warn_unless_usd(self, amount);
+                (
+                    Some(format!(
+                        "https://venmo.com/{}?txn=charge&note={}&amount={}",
+                        username,
+                        memo,
+                        amount.amount_string()
+                    )),
+                    format!("Request {} from @{} with the note \"{}\"", amount, username, memo),
+                )
+            }
+            PaymentMethod::Paypal { username } => (
+                Some(format!(
+                    "https://paypal.me/{}/{}{}",
+                    username,
+                    amount.amount_string(),
+                    amount.currency()
+                )),
+                format!("Send {} to paypal.me/{} with the note \"{}\"", amount, username, memo),
+            ),
+            PaymentMethod::CashApp { cashtag } => {
+                warn_unless_usd(self, amount);
+                (
+                    Some(format!("https://cash.app/${}/{}", cashtag, amount.amount_string())),
+                    format!("Send {} to ${} with the note \"{}\"", amount, cashtag, memo),
+                )
+            }
+            PaymentMethod::Zelle { recipient } => (
+                None,
+                format!("Send {} by Zelle to {} with the memo \"{}\"", amount, recipient, memo),
+            ),
+        };
+        PaymentRequest {
+            provider: self.provider(),
+            link,
+            instructions,
+            requested_by_creditor: matches!(self, PaymentMethod::Venmo { .. }),
+        }
+    }
+}
+
+fn warn_unless_usd(method: &PaymentMethod, amount: &Money) {
+    if amount.currency() != Currency::USD {
+        tracing::warn!(provider = method.provider(), currency = %amount.currency(), "Provider only supports USD — the payment link amount will be off");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_have_links_and_instructions() {
+        let amount = Money::new_from_minor(4000, Currency::USD);
+
+        let venmo = PaymentMethod::Venmo { username: "debtor".to_string() }.request("eq_1", &amount);
+        assert_eq!(
+            venmo.link.as_deref(),
+            Some("https://venmo.com/debtor?txn=charge&note=eq_1&amount=40.00")
+        );
+        assert!(venmo.requested_by_creditor);
+
+        let paypal = PaymentMethod::Paypal { username: "creditor".to_string() }.request("eq_1", &amount);
+        assert_eq!(paypal.link.as_deref(), Some("https://paypal.me/creditor/40.00USD"));
+        assert!(!paypal.requested_by_creditor);
+
+        let cash_app = PaymentMethod::CashApp { cashtag: "creditor".to_string() }.request("eq_1", &amount);
+        assert_eq!(cash_app.link.as_deref(), Some("https://cash.app/$creditor/40.00"));
+
+        let zelle = PaymentMethod::Zelle { recipient: "creditor@test.com".to_string() }.request("eq_1", &amount);
+        assert_eq!(zelle.link, None);
+        assert_eq!(
+            zelle.instructions,
+            "Send $40.00 by Zelle to creditor@test.com with the memo \"eq_1\""
+        );
+    }
+}
@@ -1,34 +1,168 @@
+use crate::amortization::AmortizationSchedule;
+use crate::commands::create_batch::journal::Journal;
 use crate::error::Result;
-use crate::lunch_money::model::transaction::TransactionId;
-use crate::usd::USD;
+use crate::lunch_money::model::transaction::{Transaction, TransactionId};
+use crate::money::Money;
 use display_json::DebugAsJson;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
 use std::{fs, path::PathBuf};
 
-#[derive(DebugAsJson, Deserialize, Serialize, Clone)]
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, Default)]
 pub struct Batch {
     pub id: String,
-    pub amount: USD,
+    pub amount: Money,
     pub transaction_ids: Vec<TransactionId>,
     pub reconciliation: Option<Settlement>,
+    /// Amortization installments billed in this batch. Their amounts are part
+    /// of `amount`, but their transactions are not in `transaction_ids`.
+    #[serde(default)]
+    pub installments: Vec<BatchInstallment>,
+    /// The creditor's category for each transaction, from before it was moved
+    /// to the proxy category, for mapping to the debtor's categories when the
+    /// batch is reconciled.
+    #[serde(default)]
+    pub categories: BTreeMap<TransactionId, u32>,
+    /// The day the batch was made. Not recorded for older batches.
+    #[serde(default)]
+    pub created_on: Option<chrono::NaiveDate>,
+    /// The days the debtor was reminded about this batch, oldest first.
+    #[serde(default)]
+    pub reminders: Vec<chrono::NaiveDate>,
+    /// The adjustment that settled this batch outside Lunch Money, e.g. in
+    /// cash. A closed batch is never reconciled.
+    #[serde(default)]
+    pub closed_by: Option<String>,
+    /// The creditor's halves of the splits made for this batch. They aren't
+    /// billed, but are left out of later batches like the debtor's halves.
+    /// Not recorded for older batches.
+    #[serde(default)]
+    pub split_ids: Vec<TransactionId>,
+}
+
+impl Batch {
+    /// Whether the batch is still waiting to be settled.
+    pub fn is_open(&self) -> bool {
+        self.reconciliation.is_none() && self.closed_by.is_none()
+    }
+}
+
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BatchInstallment {
+    pub schedule_id: String,
+    pub transaction_id: TransactionId,
+    pub number: u32,
+    pub count: u32,
+    pub due: chrono::NaiveDate,
+    pub amount: Money,
 }
 
 #[derive(DebugAsJson, Deserialize, Serialize, Clone)]
 pub struct Settlement {
     pub settlement_credit_id: TransactionId,
     pub settlement_debit_id: TransactionId,
+    /// The date of the settlement transaction. Not recorded for older batches.
+    #[serde(default)]
+    pub settled_on: Option<chrono::NaiveDate>,
+}
+
+/// What `equailizer daemon` remembers between runs.
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct DaemonState {
+    /// End date of the range covered by the last batch run, so the next run
+    /// can start the day after.
+    pub last_batch_end: Option<chrono::NaiveDate>,
+    pub last_batch_run: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_reconcile_run: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// The children of a split transaction, saved before it's unsplit for a
+/// re-split so the original split can be put back if the re-split fails.
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SplitSnapshot {
+    pub parent_id: TransactionId,
+    pub children: Vec<Transaction>,
+}
+
+/// Money that changed hands outside a batch settlement, recorded with
+/// `equailizer adjust`. `amount` is always positive; `kind` says which way it
+/// moves the balance.
+#[derive(DebugAsJson, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Adjustment {
+    pub id: String,
+    pub date: chrono::NaiveDate,
+    pub kind: AdjustmentKind,
+    pub amount: Money,
+    pub note: Option<String>,
+    /// The batch a payment or forgiveness settles, which closes it.
+    #[serde(default)]
+    pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentKind {
+    /// The debtor paid some back, e.g. in cash.
+    Payment,
+    /// The creditor let the debtor off some of what they owe.
+    Forgiven,
+    /// The debtor owes something that was never batched.
+    Charge,
+}
+
+impl AdjustmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentKind::Payment => "payment",
+            AdjustmentKind::Forgiven => "forgiven",
+            AdjustmentKind::Charge => "charge",
+        }
+    }
+}
+
+impl Adjustment {
+    /// The change to what the debtor owes.
+    pub fn signed_amount(&self) -> Money {
+        match self.kind {
+            AdjustmentKind::Payment | AdjustmentKind::Forgiven => -self.amount,
+            AdjustmentKind::Charge => self.amount,
+        }
+    }
 }
 
 pub trait Persistence {
     fn save_batch(&self, batch: &Batch) -> Result<()>;
     fn get_batch(&self, batch_name: &str) -> Result<Batch>;
     fn all_batches(&self) -> Result<Vec<Batch>>;
+    /// Batches that are neither reconciled nor closed by an adjustment.
     fn unreconciled_batches(&self) -> Result<Vec<Batch>>;
+    fn save_schedule(&self, schedule: &AmortizationSchedule) -> Result<()>;
+    fn all_schedules(&self) -> Result<Vec<AmortizationSchedule>>;
+    fn daemon_state(&self) -> Result<DaemonState>;
+    fn save_daemon_state(&self, state: &DaemonState) -> Result<()>;
+    fn pending_transaction_ids(&self) -> Result<Vec<TransactionId>>;
+    fn save_pending_transaction_ids(&self, ids: &[TransactionId]) -> Result<()>;
+    fn save_journal(&self, journal: &Journal) -> Result<()>;
+    /// The journal of a `create-batch` run that didn't finish, if any.
+    fn unfinished_journal(&self) -> Result<Option<Journal>>;
+    fn remove_journal(&self, batch_id: &str) -> Result<()>;
+    fn save_split_snapshot(&self, snapshot: &SplitSnapshot) -> Result<()>;
+    /// Snapshots of splits that were never put back or re-split.
+    fn split_snapshots(&self) -> Result<Vec<SplitSnapshot>>;
+    fn remove_split_snapshot(&self, parent_id: TransactionId) -> Result<()>;
+    /// Add to the adjustment history. Adjustments are never changed or removed.
+    fn append_adjustment(&self, adjustment: &Adjustment) -> Result<()>;
+    /// Every adjustment, oldest first.
+    fn adjustments(&self) -> Result<Vec<Adjustment>>;
 }
 
 pub struct FilePersistence {
     data_path: PathBuf,
     dry_run: bool,
+    /// On a dry run the daemon state is kept here instead of on disk, so a
+    /// dry-run daemon doesn't rerun a job on every poll.
+    dry_run_daemon_state: Mutex<Option<DaemonState>>,
 }
 
 impl FilePersistence {
@@ -48,7 +182,11 @@ impl FilePersistence {
             }
         }
 
-        Ok(Self { data_path, dry_run })
+        Ok(Self {
+            data_path,
+            dry_run,
+            dry_run_daemon_state: Mutex::new(None),
+        })
     }
 }
 
@@ -103,11 +241,240 @@ impl Persistence for FilePersistence {
         Ok(self
             .all_batches()?
             .into_iter()
-            .filter(|m| m.reconciliation.is_none())
+            .filter(|m| m.is_open())
             .collect())
     }
+
+    fn save_schedule(&self, schedule: &AmortizationSchedule) -> Result<()> {
+        let dir = self.data_path.join(SCHEDULES_DIR);
+        let file_path = dir.join(format!("{}.json", schedule.id));
+
+        if self.dry_run {
+            tracing::info!(schedule_id = %schedule.id, path = %file_path.display(), "Dry run — skipping schedule save");
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        let json = serde_json::to_string_pretty(schedule)?;
+        fs::write(&file_path, json)?;
+        tracing::info!(schedule_id = %schedule.id, path = %file_path.display(), "Amortization schedule saved");
+        Ok(())
+    }
+
+    fn all_schedules(&self) -> Result<Vec<AmortizationSchedule>> {
+        let dir = self.data_path.join(SCHEDULES_DIR);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut schedules = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let file = fs::read_to_string(&path)?;
+            schedules.push(serde_json::from_str(&file)?);
+        }
+        Ok(schedules)
+    }
+
+    fn daemon_state(&self) -> Result<DaemonState> {
+        if let Some(state) = self.dry_run_daemon_state.lock().unwrap().clone() {
+            return Ok(state);
+        }
+        let file_path = self.data_path.join(STATE_DIR).join(DAEMON_STATE_FILE);
+        if !file_path.is_file() {
+            return Ok(DaemonState::default());
+        }
+        let file = fs::read_to_string(&file_path)?;
+        Ok(serde_json::from_str(&file)?)
+    }
+
+    fn save_daemon_state(&self, state: &DaemonState) -> Result<()> {
+        let dir = self.data_path.join(STATE_DIR);
+        let file_path = dir.join(DAEMON_STATE_FILE);
+
+        if self.dry_run {
+            tracing::info!(path = %file_path.display(), "Dry run — keeping daemon state in memory");
+            *self.dry_run_daemon_state.lock().unwrap() = Some(state.clone());
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        fs::write(&file_path, json)?;
+        tracing::debug!(?state, path = %file_path.display(), "Daemon state saved");
+        Ok(())
+    }
+
+    fn pending_transaction_ids(&self) -> Result<Vec<TransactionId>> {
+        let file_path = self.data_path.join(STATE_DIR).join(PENDING_FILE);
+        if !file_path.is_file() {
+            return Ok(vec![]);
+        }
+        let file = fs::read_to_string(&file_path)?;
+        Ok(serde_json::from_str(&file)?)
+    }
+
+    fn save_pending_transaction_ids(&self, ids: &[TransactionId]) -> Result<()> {
+        let dir = self.data_path.join(STATE_DIR);
+        let file_path = dir.join(PENDING_FILE);
+
+        if self.dry_run {
+            tracing::info!(path = %file_path.display(), "Dry run — skipping pending transactions save");
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        let json = serde_json::to_string_pretty(ids)?;
+        fs::write(&file_path, json)?;
+        tracing::debug!(?ids, path = %file_path.display(), "Pending transactions saved");
+        Ok(())
+    }
+
+    fn save_journal(&self, journal: &Journal) -> Result<()> {
+        let dir = self.data_path.join(JOURNALS_DIR);
+        let file_path = dir.join(format!("{}.json", journal.batch_id));
+
+        if self.dry_run {
+            tracing::debug!(batch_id = %journal.batch_id, path = %file_path.display(), "Dry run — skipping journal save");
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        // Write then rename, so a crash mid-write can't leave a truncated journal.
+        let tmp_path = file_path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(journal)?)?;
+        fs::rename(&tmp_path, &file_path)?;
+        Ok(())
+    }
+
+    fn unfinished_journal(&self) -> Result<Option<Journal>> {
+        let dir = self.data_path.join(JOURNALS_DIR);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let file = fs::read_to_string(&path)?;
+            return Ok(Some(serde_json::from_str(&file)?));
+        }
+        Ok(None)
+    }
+
+    fn remove_journal(&self, batch_id: &str) -> Result<()> {
+        let file_path = self.data_path.join(JOURNALS_DIR).join(format!("{}.json", batch_id));
+
+        if self.dry_run {
+            tracing::debug!(batch_id, path = %file_path.display(), "Dry run — skipping journal removal");
+            return Ok(());
+        }
+
+        match fs::remove_file(&file_path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_split_snapshot(&self, snapshot: &SplitSnapshot) -> Result<()> {
+        let dir = self.data_path.join(SNAPSHOTS_DIR);
+        let file_path = dir.join(format!("{}.json", snapshot.parent_id));
+
+        if self.dry_run {
+            tracing::debug!(parent_id = snapshot.parent_id, path = %file_path.display(), "Dry run — skipping split snapshot save");
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        fs::write(&file_path, serde_json::to_string_pretty(snapshot)?)?;
+        tracing::debug!(parent_id = snapshot.parent_id, path = %file_path.display(), "Split snapshot saved");
+        Ok(())
+    }
+
+    fn split_snapshots(&self) -> Result<Vec<SplitSnapshot>> {
+        let dir = self.data_path.join(SNAPSHOTS_DIR);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let file = fs::read_to_string(&path)?;
+            snapshots.push(serde_json::from_str(&file)?);
+        }
+        Ok(snapshots)
+    }
+
+    fn remove_split_snapshot(&self, parent_id: TransactionId) -> Result<()> {
+        let file_path = self.data_path.join(SNAPSHOTS_DIR).join(format!("{}.json", parent_id));
+
+        if self.dry_run {
+            tracing::debug!(parent_id, path = %file_path.display(), "Dry run — skipping split snapshot removal");
+            return Ok(());
+        }
+
+        match fs::remove_file(&file_path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn append_adjustment(&self, adjustment: &Adjustment) -> Result<()> {
+        use std::io::Write;
+
+        let dir = self.data_path.join(STATE_DIR);
+        let file_path = dir.join(ADJUSTMENTS_FILE);
+
+        if self.dry_run {
+            tracing::info!(adjustment_id = %adjustment.id, path = %file_path.display(), "Dry run — skipping adjustment save");
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir)?;
+        // One adjustment per line, only ever appended to.
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        writeln!(file, "{}", serde_json::to_string(adjustment)?)?;
+        tracing::info!(adjustment_id = %adjustment.id, path = %file_path.display(), "Adjustment saved");
+        Ok(())
+    }
+
+    fn adjustments(&self) -> Result<Vec<Adjustment>> {
+        let file_path = self.data_path.join(STATE_DIR).join(ADJUSTMENTS_FILE);
+        if !file_path.is_file() {
+            return Ok(vec![]);
+        }
+        let file = fs::read_to_string(&file_path)?;
+        let mut adjustments = Vec::new();
+        for line in file.lines().filter(|l| !l.trim().is_empty()) {
+            adjustments.push(serde_json::from_str(line)?);
+        }
+        Ok(adjustments)
+    }
 }
 
+// Kept in subdirectories so `all_batches` doesn't try to read them as batches.
+const SCHEDULES_DIR: &str = "schedules";
+const JOURNALS_DIR: &str = "journals";
+const SNAPSHOTS_DIR: &str = "snapshots";
+const STATE_DIR: &str = "state";
+const DAEMON_STATE_FILE: &str = "daemon.json";
+const PENDING_FILE: &str = "pending.json";
+const ADJUSTMENTS_FILE: &str = "adjustments.jsonl";
+
 pub fn base_path() -> Result<PathBuf> {
     let mut base_path = std::env::current_exe()?
         .parent()
@@ -5,8 +5,8 @@ use equailizer_plugin::protocol::{
 use crate::config::PluginEntry;
 use crate::email::Txn;
 use crate::error::Error;
+use crate::money::Money;
 use crate::persist::Batch;
-use crate::usd::USD;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
 use tokio::process::{Child, ChildStdin, ChildStdout};
 
@@ -31,18 +31,19 @@ pub struct PluginManager {
 
 pub fn batch_created_message(
     batch_id: &str,
-    total: &USD,
+    total: &Money,
     txns: &[Txn],
     warnings: &[String],
 ) -> PluginMessage {
     PluginMessage::BatchCreated {
         batch_id: batch_id.to_string(),
-        total: total.to_string(),
+        total: total.amount_string(),
+        currency: total.currency().code().to_string(),
         transactions: txns
             .iter()
             .map(|t| Transaction {
                 payee: t.payee.clone(),
-                amount: t.amount.to_string(),
+                amount: t.amount.amount_string(),
                 date: t.date,
                 notes: t.notes.clone(),
             })
@@ -58,7 +59,8 @@ pub fn batch_reconciled_message(
 ) -> PluginMessage {
     PluginMessage::BatchReconciled {
         batch_id: batch.id.clone(),
-        amount: batch.amount.to_string(),
+        amount: batch.amount.amount_string(),
+        currency: batch.amount.currency().code().to_string(),
         settlement_credit_id,
         settlement_debit_id,
     }
@@ -0,0 +1,558 @@
+use crate::lunch_money::model::transaction::TransactionId;
+use crate::money::Money;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fmt;
+
+/// What a `create-batch` run did, for `--output`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BatchReport {
+    /// `None` when there was nothing to batch.
+    pub batch_id: Option<String>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub total: Option<Money>,
+    pub transactions: Vec<BatchedTransaction>,
+    pub issues: Vec<String>,
+    /// Set when an all-or-nothing run hit a failure and undid its changes
+    /// instead of saving a batch. The failures are in `issues`.
+    pub rollback: Option<RollbackReport>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BatchedTransaction {
+    pub id: TransactionId,
+    pub date: NaiveDate,
+    pub payee: String,
+    pub amount: Money,
+    pub handling: Handling,
+}
+
+/// How a transaction ended up in the batch.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Handling {
+    Added,
+    Split,
+    Resplit,
+    Installment { number: u32, count: u32 },
+}
+
+impl fmt::Display for Handling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Handling::Added => write!(f, "added"),
+            Handling::Split => write!(f, "split"),
+            Handling::Resplit => write!(f, "resplit"),
+            Handling::Installment { number, count } => {
+                write!(f, "installment {} of {}", number, count)
+            }
+        }
+    }
+}
+
+/// The settlement found for one reconciled batch.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReconcileReport {
+    pub batch_id: String,
+    pub amount: Money,
+    pub settlement_credit_id: TransactionId,
+    pub settlement_debit_id: TransactionId,
+}
+
+/// What a `reconcile-all` run did, for `--output`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReconcileAllReport {
+    pub reconciled: Vec<ReconcileReport>,
+    pub errors: Vec<BatchError>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BatchError {
+    pub batch_id: String,
+    pub error: String,
+}
+
+/// What rolling back an unfinished `create-batch` run undid, for `--output`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RollbackReport {
+    pub batch_id: String,
+    /// Transactions put back the way they were before the run.
+    pub reverted: Vec<TransactionId>,
+    pub errors: Vec<RevertError>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RevertError {
+    pub transaction_id: TransactionId,
+    pub error: String,
+}
+
+/// What `repair` put back, for `--output`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RepairReport {
+    /// Parents whose original split was restored.
+    pub repaired: Vec<TransactionId>,
+    pub errors: Vec<RevertError>,
+}
+
+/// What was shared over a date range, for `report`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SpendingReport {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub total: Money,
+    pub transaction_count: usize,
+    /// Oldest month first, named like `2025-03`.
+    pub by_month: Vec<SpendingTotal>,
+    /// By the creditor's category from before batching, largest first.
+    pub by_category: Vec<SpendingTotal>,
+    /// Largest first.
+    pub by_payee: Vec<SpendingTotal>,
+    pub reconcile_times: ReconcileTimes,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SpendingTotal {
+    pub name: String,
+    pub count: usize,
+    pub total: Money,
+}
+
+/// Days from making a batch to its settlement, over the reconciled batches.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReconcileTimes {
+    pub reconciled: usize,
+    pub unreconciled: usize,
+    pub min_days: Option<i64>,
+    pub median_days: Option<Decimal>,
+    pub mean_days: Option<Decimal>,
+    pub max_days: Option<i64>,
+}
+
+/// What the debtor owes, for `balance`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    pub as_of: NaiveDate,
+    /// Every batch less the settled ones, plus adjustments.
+    pub balance: Money,
+    /// Oldest first; batches that don't know when they were made come first.
+    pub open_batches: Vec<OpenBatch>,
+    pub by_age: Vec<AgeBucket>,
+    /// Batches, settlements and adjustments in date order, each with the
+    /// balance after it.
+    pub ledger: Vec<LedgerEntry>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct OpenBatch {
+    pub batch_id: String,
+    pub created_on: Option<NaiveDate>,
+    pub age_days: Option<i64>,
+    pub amount: Money,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AgeBucket {
+    pub label: String,
+    pub count: usize,
+    pub total: Money,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    pub date: Option<NaiveDate>,
+    pub description: String,
+    /// Positive when the debtor owes more.
+    pub change: Money,
+    pub balance: Money,
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.batch_id, &self.total) {
+            (Some(batch_id), Some(total)) => {
+                writeln!(f, "Batch {} ({} to {}): {}", batch_id, self.start_date, self.end_date, total)?
+            }
+            _ => writeln!(f, "No batch created ({} to {})", self.start_date, self.end_date)?,
+        }
+        if let Some(rollback) = &self.rollback {
+            writeln!(f)?;
+            write!(f, "{}", rollback)?;
+        }
+        if !self.transactions.is_empty() {
+            writeln!(f)?;
+            let rows = self
+                .transactions
+                .iter()
+                .map(|t| {
+                    vec![
+                        t.id.to_string(),
+                        t.date.to_string(),
+                        t.payee.clone(),
+                        t.amount.to_string(),
+                        t.handling.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            write_table(f, &["ID", "DATE", "PAYEE", "AMOUNT", "HANDLING"], &rows)?;
+        }
+        write_issues(f, "Issues", &self.issues)
+    }
+}
+
+impl fmt::Display for ReconcileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_table(f, &RECONCILE_HEADERS, &[reconcile_row(self)])
+    }
+}
+
+impl fmt::Display for ReconcileAllReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Reconciled {} batch(es), {} failed",
+            self.reconciled.len(),
+            self.errors.len()
+        )?;
+        if !self.reconciled.is_empty() {
+            writeln!(f)?;
+            let rows = self.reconciled.iter().map(reconcile_row).collect::<Vec<_>>();
+            write_table(f, &RECONCILE_HEADERS, &rows)?;
+        }
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.batch_id, e.error))
+            .collect::<Vec<_>>();
+        write_issues(f, "Errors", &errors)
+    }
+}
+
+impl fmt::Display for RollbackReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Rolled back run {}: {} transaction(s) restored, {} failed",
+            self.batch_id,
+            self.reverted.len(),
+            self.errors.len()
+        )?;
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.transaction_id, e.error))
+            .collect::<Vec<_>>();
+        write_issues(f, "Errors", &errors)
+    }
+}
+
+impl fmt::Display for RepairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Restored {} split(s), {} failed",
+            self.repaired.len(),
+            self.errors.len()
+        )?;
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.transaction_id, e.error))
+            .collect::<Vec<_>>();
+        write_issues(f, "Errors", &errors)
+    }
+}
+
+impl fmt::Display for SpendingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let day = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_else(|| "...".to_string());
+        writeln!(
+            f,
+            "Shared {} in {} transaction(s) ({} to {})",
+            self.total,
+            self.transaction_count,
+            day(self.from),
+            day(self.to)
+        )?;
+        for (title, totals) in [
+            ("MONTH", &self.by_month),
+            ("CATEGORY", &self.by_category),
+            ("PAYEE", &self.by_payee),
+        ] {
+            if totals.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            let rows = totals
+                .iter()
+                .map(|t| vec![t.name.clone(), t.count.to_string(), t.total.to_string()])
+                .collect::<Vec<_>>();
+            write_table(f, &[title, "COUNT", "TOTAL"], &rows)?;
+        }
+
+        let times = &self.reconcile_times;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Reconciled {} batch(es), {} not yet",
+            times.reconciled, times.unreconciled
+        )?;
+        if let (Some(min), Some(median), Some(mean), Some(max)) =
+            (times.min_days, times.median_days, times.mean_days, times.max_days)
+        {
+            writeln!(
+                f,
+                "Days to reconcile: min {}, median {}, mean {}, max {}",
+                min, median, mean, max
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let day = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_else(|| "unknown".to_string());
+        writeln!(f, "Owed {} as of {}", self.balance, self.as_of)?;
+
+        if !self.by_age.is_empty() {
+            writeln!(f)?;
+            let rows = self
+                .by_age
+                .iter()
+                .map(|b| vec![b.label.clone(), b.count.to_string(), b.total.to_string()])
+                .collect::<Vec<_>>();
+            write_table(f, &["AGE", "BATCHES", "TOTAL"], &rows)?;
+        }
+
+        if !self.ledger.is_empty() {
+            writeln!(f)?;
+            let rows = self
+                .ledger
+                .iter()
+                .map(|e| {
+                    vec![
+                        day(e.date),
+                        e.description.clone(),
+                        e.change.to_string(),
+                        e.balance.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            write_table(f, &["DATE", "ENTRY", "CHANGE", "BALANCE"], &rows)?;
+        }
+        Ok(())
+    }
+}
+
+const RECONCILE_HEADERS: [&str; 4] = ["BATCH", "AMOUNT", "CREDIT ID", "DEBIT ID"];
+
+fn reconcile_row(report: &ReconcileReport) -> Vec<String> {
+    vec![
+        report.batch_id.clone(),
+        report.amount.to_string(),
+        report.settlement_credit_id.to_string(),
+        report.settlement_debit_id.to_string(),
+    ]
+}
+
+fn write_issues(f: &mut fmt::Formatter<'_>, title: &str, issues: &[String]) -> fmt::Result {
+    if issues.is_empty() {
+        return Ok(());
+    }
+    writeln!(f)?;
+    writeln!(f, "{}:", title)?;
+    for issue in issues {
+        writeln!(f, "  - {}", issue)?;
+    }
+    Ok(())
+}
+
+/// Left-aligned columns, each as wide as its widest cell.
+fn write_table(f: &mut fmt::Formatter<'_>, headers: &[&str], rows: &[Vec<String>]) -> fmt::Result {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .map(|r| r[i].chars().count())
+                .chain(std::iter::once(h.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let header_row: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header_row).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(f, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+
+    fn report() -> BatchReport {
+        BatchReport {
+            batch_id: Some("abc".to_string()),
+            start_date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+            total: Some(Money::new_from_minor(2500, Currency::USD)),
+            transactions: vec![
+                BatchedTransaction {
+                    id: 1,
+                    date: NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
+                    payee: "Grocer".to_string(),
+                    amount: Money::new_from_minor(1500, Currency::USD),
+                    handling: Handling::Added,
+                },
+                BatchedTransaction {
+                    id: 22,
+                    date: NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+                    payee: "Couch".to_string(),
+                    amount: Money::new_from_minor(1000, Currency::USD),
+                    handling: Handling::Installment { number: 2, count: 12 },
+                },
+            ],
+            issues: vec!["something went wrong".to_string()],
+            rollback: None,
+        }
+    }
+
+    #[test]
+    fn batch_report_json() {
+        let json = serde_json::to_value(report()).unwrap();
+        assert_eq!(json["batch_id"], "abc");
+        assert_eq!(json["total"]["amount"], "25.00");
+        assert_eq!(json["transactions"][0]["handling"]["type"], "added");
+        assert_eq!(json["transactions"][1]["handling"]["number"], 2);
+        assert_eq!(json["issues"][0], "something went wrong");
+    }
+
+    #[test]
+    fn batch_report_table() {
+        let table = report().to_string();
+        let expected = "\
+Batch abc (2025-03-01 to 2025-03-31): $25.00
+
+ID  DATE        PAYEE   AMOUNT  HANDLING
+1   2025-03-02  Grocer  $15.00  added
+22  2025-03-05  Couch   $10.00  installment 2 of 12
+
+Issues:
+  - something went wrong
+";
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn spending_report_table() {
+        let total = |cents| Money::new_from_minor(cents, Currency::USD);
+        let report = SpendingReport {
+            from: NaiveDate::from_ymd_opt(2025, 3, 1),
+            to: None,
+            total: total(2500),
+            transaction_count: 2,
+            by_month: vec![SpendingTotal { name: "2025-03".to_string(), count: 2, total: total(2500) }],
+            by_category: vec![
+                SpendingTotal { name: "Groceries".to_string(), count: 1, total: total(1500) },
+                SpendingTotal { name: "Uncategorized".to_string(), count: 1, total: total(1000) },
+            ],
+            by_payee: vec![],
+            reconcile_times: ReconcileTimes {
+                reconciled: 1,
+                unreconciled: 0,
+                min_days: Some(4),
+                median_days: Some(Decimal::from(4)),
+                mean_days: Some(Decimal::from(4)),
+                max_days: Some(4),
+            },
+        };
+        let expected = "\
+Shared $25.00 in 2 transaction(s) (2025-03-01 to ...)
+
+MONTH    COUNT  TOTAL
+2025-03  2      $25.00
+
+CATEGORY       COUNT  TOTAL
+Groceries      1      $15.00
+Uncategorized  1      $10.00
+
+Reconciled 1 batch(es), 0 not yet
+Days to reconcile: min 4, median 4, mean 4, max 4
+";
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn balance_report_table() {
+        let usd = |cents| Money::new_from_minor(cents, Currency::USD);
+        let report = BalanceReport {
+            as_of: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            balance: usd(2000),
+            open_batches: vec![],
+            by_age: vec![AgeBucket { label: "0-30 days".to_string(), count: 1, total: usd(2500) }],
+            ledger: vec![
+                LedgerEntry {
+                    date: NaiveDate::from_ymd_opt(2025, 4, 20),
+                    description: "batch abc".to_string(),
+                    change: usd(2500),
+                    balance: usd(2500),
+                },
+                LedgerEntry {
+                    date: NaiveDate::from_ymd_opt(2025, 4, 25),
+                    description: "payment: cash".to_string(),
+                    change: usd(-500),
+                    balance: usd(2000),
+                },
+            ],
+        };
+        let expected = "\
+Owed $20.00 as of 2025-05-01
+
+AGE        BATCHES  TOTAL
+0-30 days  1        $25.00
+
+DATE        ENTRY          CHANGE  BALANCE
+2025-04-20  batch abc      $25.00  $25.00
+2025-04-25  payment: cash  -$5.00  $20.00
+";
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn reconcile_all_report_table() {
+        let report = ReconcileAllReport {
+            reconciled: vec![ReconcileReport {
+                batch_id: "abc".to_string(),
+                amount: Money::new_from_minor(2500, Currency::USD),
+                settlement_credit_id: 500,
+                settlement_debit_id: 600,
+            }],
+            errors: vec![BatchError {
+                batch_id: "def".to_string(),
+                error: "no settlement".to_string(),
+            }],
+        };
+        let expected = "\
+Reconciled 1 batch(es), 1 failed
+
+BATCH  AMOUNT  CREDIT ID  DEBIT ID
+abc    $25.00  500        600
+
+Errors:
+  - def: no settlement
+";
+        assert_eq!(report.to_string(), expected);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::lunch_money::model::transaction::Transaction;
+use crate::rules;
+use rust_decimal::Decimal;
+use std::fmt;
+use std::io::{BufRead, Write};
+
+/// What `create-batch` is about to do with a transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProposedAction {
+    Add,
+    Split,
+    /// Split with the debtor paying this share.
+    RatioSplit(Decimal),
+    /// Split a child of an already-split transaction evenly.
+    Resplit,
+    Amortize(u32),
+}
+
+impl ProposedAction {
+    /// Whether the split can be changed to another kind. Re-splits and
+    /// amortizations can only be accepted or skipped.
+    pub fn is_changeable(&self) -> bool {
+        matches!(
+            self,
+            ProposedAction::Add | ProposedAction::Split | ProposedAction::RatioSplit(_)
+        )
+    }
+}
+
+impl fmt::Display for ProposedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProposedAction::Add => write!(f, "add in full"),
+            ProposedAction::Split => write!(f, "split evenly"),
+            ProposedAction::RatioSplit(share) => {
+                write!(f, "split, debtor pays {}%", (share * Decimal::from(100)).normalize())
+            }
+            ProposedAction::Resplit => write!(f, "split evenly (re-split of parent)"),
+            ProposedAction::Amortize(months) => write!(f, "amortize over {} months", months),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewDecision {
+    Accept,
+    /// Leave the transaction out of this batch. Its tag is left alone, so a
+    /// later run will find it again.
+    Skip,
+    /// Split evenly instead.
+    Split,
+    /// Split with the debtor paying this share instead.
+    Ratio(Decimal),
+}
+
+/// Decides, one transaction at a time, whether `create-batch --interactive`
+/// goes ahead with what it found.
+pub trait Reviewer {
+    fn review(&mut self, txn: &Transaction, action: ProposedAction) -> ReviewDecision;
+}
+
+/// Asks on the terminal. Prompts go to `output`, answers come from `input`.
+pub struct TerminalReviewer<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> TerminalReviewer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    fn prompt(&mut self, txn: &Transaction, action: ProposedAction) -> std::io::Result<ReviewDecision> {
+        writeln!(self.output)?;
+        writeln!(
+            self.output,
+            "{}  {}  {}  (id {})",
+            txn.date, txn.payee, txn.amount, txn.id
+        )?;
+        if let Some(notes) = &txn.notes {
+            writeln!(self.output, "  notes: {}", notes)?;
+        }
+        writeln!(self.output, "  action: {}", action)?;
+
+        loop {
+            if action.is_changeable() {
+                write!(
+                    self.output,
+                    "[a]ccept, [s]kip, split [e]venly, or [r]atio <debtor share, e.g. r 0.3>: "
+                )?;
+            } else {
+                write!(self.output, "[a]ccept or [s]kip: ")?;
+            }
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                // Input closed: don't batch anything that wasn't confirmed.
+                return Ok(ReviewDecision::Skip);
+            }
+            match parse_decision(line.trim(), action.is_changeable()) {
+                Some(decision) => return Ok(decision),
+                None => writeln!(self.output, "Didn't understand '{}'", line.trim())?,
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Reviewer for TerminalReviewer<R, W> {
+    fn review(&mut self, txn: &Transaction, action: ProposedAction) -> ReviewDecision {
+        self.prompt(txn, action).unwrap_or_else(|e| {
+            tracing::warn!(txn_id = txn.id, error = %e, "Review prompt failed; skipping transaction");
+            ReviewDecision::Skip
+        })
+    }
+}
+
+fn parse_decision(answer: &str, changeable: bool) -> Option<ReviewDecision> {
+    let mut parts = answer.split_whitespace();
+    let decision = match parts.next()?.to_lowercase().as_str() {
+        "a" | "accept" => ReviewDecision::Accept,
+        "s" | "skip" => ReviewDecision::Skip,
+        "e" | "even" if changeable => ReviewDecision::Split,
+        "r" | "ratio" if changeable => {
+            let share: Decimal = parts.next()?.parse().ok()?;
+            if !rules::is_valid_share(share) {
+                return None;
+            }
+            ReviewDecision::Ratio(share)
+        }
+        _ => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lunch_money::model::transaction::TransactionStatus;
+    use crate::money::{Currency, Money};
+    use chrono::NaiveDate;
+    use rust_decimal::dec;
+
+    fn txn() -> Transaction {
+        Transaction {
+            id: 7,
+            date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            payee: "Grocer".to_string(),
+            amount: Money::new_from_minor(2000, Currency::USD),
+            to_base: None,
+            plaid_account_id: None,
+            category_id: None,
+            category_name: None,
+            tags: vec![],
+            notes: Some("weekly shop".to_string()),
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        }
+    }
+
+    fn answer(input: &str, action: ProposedAction) -> (ReviewDecision, String) {
+        let mut output = vec![];
+        let decision = TerminalReviewer::new(input.as_bytes(), &mut output).review(&txn(), action);
+        (decision, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parses_decisions() {
+        assert_eq!(parse_decision("a", true), Some(ReviewDecision::Accept));
+        assert_eq!(parse_decision("Skip", true), Some(ReviewDecision::Skip));
+        assert_eq!(parse_decision("e", true), Some(ReviewDecision::Split));
+        assert_eq!(parse_decision("r 0.3", true), Some(ReviewDecision::Ratio(dec!(0.3))));
+        assert_eq!(parse_decision("r 1.5", true), None);
+        assert_eq!(parse_decision("r", true), None);
+        assert_eq!(parse_decision("e", false), None);
+        assert_eq!(parse_decision("a b", true), None);
+    }
+
+    #[test]
+    fn terminal_reviewer_shows_transaction_and_reprompts() {
+        let (decision, output) = answer("x\nr 0.25\n", ProposedAction::Add);
+        assert_eq!(decision, ReviewDecision::Ratio(dec!(0.25)));
+        assert!(output.contains("2025-03-01  Grocer  $20.00  (id 7)"));
+        assert!(output.contains("notes: weekly shop"));
+        assert!(output.contains("action: add in full"));
+        assert!(output.contains("Didn't understand 'x'"));
+    }
+
+    #[test]
+    fn terminal_reviewer_skips_when_input_ends() {
+        let (decision, _) = answer("", ProposedAction::Amortize(6));
+        assert_eq!(decision, ReviewDecision::Skip);
+    }
+
+    #[test]
+    fn proposed_action_display() {
+        assert_eq!(ProposedAction::RatioSplit(dec!(0.30)).to_string(), "split, debtor pays 30%");
+        assert_eq!(ProposedAction::Amortize(12).to_string(), "amortize over 12 months");
+    }
+}
@@ -0,0 +1,335 @@
+use crate::config::Tags;
+use crate::error::{Error, Result};
+use crate::lunch_money::model::transaction::Transaction;
+use chrono::Datelike;
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// A rule that batches matching transactions without them being tagged by hand.
+/// Every condition that is set must match; unset conditions match anything.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub payee: Option<PayeeMatch>,
+    pub category_id: Option<u32>,
+    pub account_id: Option<u32>,
+    /// Amount bounds are in the transaction's own currency.
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    pub day_of_month: Option<DayRange>,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum PayeeMatch {
+    /// Case-insensitive substring match.
+    Contains(String),
+    Regex(PayeeRegex),
+}
+
+#[derive(Debug, Clone)]
+pub struct PayeeRegex(Regex);
+
+impl<'de> Deserialize<'de> for PayeeRegex {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Regex::new(&s)
+            .map(PayeeRegex)
+            .map_err(|e| serde::de::Error::custom(format!("invalid payee regex: {e}")))
+    }
+}
+
+/// Inclusive range of days of the month. `from` may be greater than `to` to
+/// wrap around the end of the month, e.g. 28 to 3.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DayRange {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    Add,
+    Split,
+    /// Split with the debtor paying this share of the transaction, strictly
+    /// between 0 and 1. A share of 1 is the `add` action.
+    Ratio(Decimal),
+}
+
+/// Whether `share` works as the debtor's share of a split. Both halves of a
+/// split must be non-zero, so 0 and 1 are out.
+pub fn is_valid_share(share: Decimal) -> bool {
+    Decimal::ZERO < share && share < Decimal::ONE
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct RuleMatches {
+    pub txns_to_add: Vec<Transaction>,
+    pub txns_to_split: Vec<Transaction>,
+    pub txns_to_ratio_split: Vec<(Transaction, Decimal)>,
+}
+
+impl Rule {
+    pub fn matches(&self, txn: &Transaction) -> bool {
+        if let Some(payee) = &self.payee {
+            let payee_matches = match payee {
+                PayeeMatch::Contains(s) => txn.payee.to_lowercase().contains(&s.to_lowercase()),
+                PayeeMatch::Regex(PayeeRegex(re)) => re.is_match(&txn.payee),
+            };
+            if !payee_matches {
+                return false;
+            }
+        }
+
+        if self.category_id.is_some() && txn.category_id != self.category_id {
+            return false;
+        }
+
+        if self.account_id.is_some() && txn.plaid_account_id != self.account_id {
+            return false;
+        }
+
+        if self.min_amount.is_some_and(|min| txn.amount.value() < min)
+            || self.max_amount.is_some_and(|max| txn.amount.value() > max)
+        {
+            return false;
+        }
+
+        if let Some(days) = &self.day_of_month {
+            let day = txn.date.day();
+            let in_range = if days.from <= days.to {
+                days.from <= day && day <= days.to
+            } else {
+                day >= days.from || day <= days.to
+            };
+            if !in_range {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: &str| Err(Error::Config(format!("rule '{}': {}", self.name, msg)));
+
+        if let RuleAction::Ratio(share) = self.action
+            && !is_valid_share(share)
+        {
+            return invalid("ratio must be greater than 0 and less than 1; use the add action for 1");
+        }
+        if let Some(days) = &self.day_of_month
+            && !((1..=31).contains(&days.from) && (1..=31).contains(&days.to))
+        {
+            return invalid("day_of_month must be between 1 and 31");
+        }
+        if let (Some(min), Some(max)) = (self.min_amount, self.max_amount)
+            && min > max
+        {
+            return invalid("min_amount is greater than max_amount");
+        }
+
+        Ok(())
+    }
+}
+
+pub fn validate_rules(rules: &[Rule]) -> Result<()> {
+    rules.iter().try_for_each(Rule::validate)
+}
+
+/// Whether a transaction can be picked up by a rule at all. Tagged transactions
+/// are left to `process_tags`, and split parents/children and transactions
+/// already in the proxy category have been handled by an earlier run.
+pub fn is_eligible(txn: &Transaction, tags: &Tags, proxy_category_id: u32) -> bool {
+    !txn.is_pending
+        && !txn.has_children
+        && txn.parent_id.is_none()
+        && txn.category_id != Some(proxy_category_id)
+        && !txn.tag_names().iter().any(|name| tags.is_trigger_tag(name))
+}
+
+/// Apply rules to the fetched transactions. The first matching rule decides
+/// what happens to a transaction.
+pub fn apply_rules(
+    txns: &[Transaction],
+    rules: &[Rule],
+    tags: &Tags,
+    proxy_category_id: u32,
+) -> RuleMatches {
+    let mut matches = RuleMatches::default();
+    if rules.is_empty() {
+        return matches;
+    }
+
+    for txn in txns
+        .iter()
+        .filter(|t| is_eligible(t, tags, proxy_category_id))
+    {
+        let Some(rule) = rules.iter().find(|r| r.matches(txn)) else {
+            continue;
+        };
+
+        tracing::debug!(txn_id = txn.id, rule = %rule.name, action = ?rule.action, "Rule matched");
+
+        match rule.action {
+            RuleAction::Add => matches.txns_to_add.push(txn.clone()),
+            RuleAction::Split => matches.txns_to_split.push(txn.clone()),
+            RuleAction::Ratio(share) => matches.txns_to_ratio_split.push((txn.clone(), share)),
+        }
+    }
+
+    tracing::info!(
+        to_add = matches.txns_to_add.len(),
+        to_split = matches.txns_to_split.len(),
+        to_ratio_split = matches.txns_to_ratio_split.len(),
+        "Rule matches"
+    );
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lunch_money::model::transaction::{Tag, TransactionStatus};
+    use crate::money::{Currency, Money};
+    use chrono::NaiveDate;
+    use rust_decimal::dec;
+
+    fn txn(id: u32, payee: &str, cents: i64, day: u32) -> Transaction {
+        Transaction {
+            id,
+            date: NaiveDate::from_ymd_opt(2025, 10, day).expect("NaiveDate creation failed"),
+            payee: payee.to_string(),
+            amount: Money::new_from_minor(cents, Currency::USD),
+            to_base: None,
+            plaid_account_id: Some(7),
+            category_id: Some(41),
+            category_name: Some("Utilities".to_string()),
+            tags: vec![],
+            notes: None,
+            status: TransactionStatus::Uncleared,
+            parent_id: None,
+            has_children: false,
+            is_pending: false,
+        }
+    }
+
+    fn rule(json: &str) -> Rule {
+        serde_json::from_str(json).expect("rule should parse")
+    }
+
+    #[test]
+    fn payee_contains_is_case_insensitive() {
+        let r = rule(r#"{"name": "r", "payee": {"contains": "con ed"}, "action": "split"}"#);
+        assert!(r.matches(&txn(1, "CON EDISON", 1000, 1)));
+        assert!(!r.matches(&txn(2, "National Grid", 1000, 1)));
+    }
+
+    #[test]
+    fn payee_regex() {
+        let r = rule(r#"{"name": "r", "payee": {"regex": "^Food (Garden|Bazaar)"}, "action": "add"}"#);
+        assert!(r.matches(&txn(1, "Food Garden Market", 1000, 1)));
+        assert!(!r.matches(&txn(2, "The Food Garden", 1000, 1)));
+    }
+
+    #[test]
+    fn invalid_regex_fails_to_parse() {
+        let result: std::result::Result<Rule, _> =
+            serde_json::from_str(r#"{"name": "r", "payee": {"regex": "("}, "action": "add"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn category_account_and_amount() {
+        let r = rule(
+            r#"{"name": "r", "category_id": 41, "account_id": 7,
+                "min_amount": "10.00", "max_amount": "20.00", "action": "add"}"#,
+        );
+        assert!(r.matches(&txn(1, "A", 1000, 1)));
+        assert!(r.matches(&txn(2, "A", 2000, 1)));
+        assert!(!r.matches(&txn(3, "A", 999, 1)));
+        assert!(!r.matches(&txn(4, "A", 2001, 1)));
+
+        let mut other_account = txn(5, "A", 1500, 1);
+        other_account.plaid_account_id = Some(8);
+        assert!(!r.matches(&other_account));
+
+        let mut other_category = txn(6, "A", 1500, 1);
+        other_category.category_id = None;
+        assert!(!r.matches(&other_category));
+    }
+
+    #[test]
+    fn day_of_month_range_wraps() {
+        let r = rule(r#"{"name": "r", "day_of_month": {"from": 28, "to": 3}, "action": "add"}"#);
+        assert!(r.matches(&txn(1, "A", 1000, 30)));
+        assert!(r.matches(&txn(2, "A", 1000, 2)));
+        assert!(!r.matches(&txn(3, "A", 1000, 15)));
+    }
+
+    #[test]
+    fn validate_rejects_bad_ratio() {
+        let r = rule(r#"{"name": "r", "action": {"ratio": "1.5"}}"#);
+        assert!(validate_rules(&[r]).is_err());
+        let r = rule(r#"{"name": "r", "action": {"ratio": "1"}}"#);
+        assert!(validate_rules(&[r]).is_err());
+        let r = rule(r#"{"name": "r", "action": {"ratio": "0.6"}}"#);
+        assert!(validate_rules(&[r]).is_ok());
+    }
+
+    #[test]
+    fn apply_rules_first_match_wins_and_skips_ineligible() {
+        let rules = vec![
+            rule(r#"{"name": "rent", "payee": {"contains": "rent"}, "action": {"ratio": "0.6"}}"#),
+            rule(r#"{"name": "groceries", "payee": {"contains": "market"}, "action": "split"}"#),
+            rule(r#"{"name": "anything", "action": "add"}"#),
+        ];
+        let tags = Tags::default();
+
+        let rent = txn(1, "Rent Co", 200000, 1);
+        let market = txn(2, "Food Market", 5000, 2);
+        let other = txn(3, "Other", 1000, 3);
+        let mut tagged = txn(4, "Tagged", 1000, 4);
+        tagged.tags = vec![Tag {
+            name: tags.add.clone(),
+            id: 0,
+        }];
+        let mut child = txn(5, "Rent Co", 1000, 5);
+        child.parent_id = Some(1);
+        let mut already_proxied = txn(6, "Rent Co", 1000, 6);
+        already_proxied.category_id = Some(99);
+        let mut pending = txn(7, "Rent Co", 1000, 7);
+        pending.is_pending = true;
+
+        let matches = apply_rules(
+            &[
+                rent.clone(),
+                market.clone(),
+                other.clone(),
+                tagged,
+                child,
+                already_proxied,
+                pending,
+            ],
+            &rules,
+            &tags,
+            99,
+        );
+
+        assert_eq!(
+            matches,
+            RuleMatches {
+                txns_to_add: vec![other],
+                txns_to_split: vec![market],
+                txns_to_ratio_split: vec![(rent, dec!(0.6))],
+            }
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Deserialize;
+use std::fmt;
+use std::process::Command;
+
+/// A sensitive config value, such as an API key.
+///
+/// The wrapped value is only reachable through [`Secret::expose`], so it never
+/// ends up in `Debug` output, `tracing` fields, or plugin messages by accident.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([redacted])")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Where a secret's value comes from, as written in `config.json`.
+///
+/// ```json
+/// "api_key": "literal value"
+/// "api_key": {"env": "LM_CREDITOR_KEY"}
+/// "api_key": {"file": "/run/secrets/lm_creditor"}
+/// "api_key": {"command": "pass show lm/creditor"}
+/// ```
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SecretSource {
+    Literal(String),
+    Env { env: String },
+    File { file: String },
+    Command { command: String },
+}
+
+impl SecretSource {
+    // Error messages name the source but never include the resolved value.
+    fn resolve(self) -> Result<Secret, String> {
+        match self {
+            SecretSource::Literal(value) => Ok(Secret(value)),
+            SecretSource::Env { env } => std::env::var(&env)
+                .map(Secret)
+                .map_err(|e| format!("secret env var '{env}': {e}")),
+            SecretSource::File { file } => std::fs::read_to_string(&file)
+                .map(|s| Secret(trim_line_ending(s)))
+                .map_err(|e| format!("secret file '{file}': {e}")),
+            SecretSource::Command { command } => {
+                let output = Command::new("sh")
+                    .arg("-c")
+                    .arg(&command)
+                    .output()
+                    .map_err(|e| format!("secret command '{command}': {e}"))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "secret command '{command}' exited with {}",
+                        output.status
+                    ));
+                }
+                String::from_utf8(output.stdout)
+                    .map(|s| Secret(trim_line_ending(s)))
+                    .map_err(|_| format!("secret command '{command}' printed invalid UTF-8"))
+            }
+        }
+    }
+}
+
+// Secret files and `pass`-style commands end with a newline that isn't part of the value.
+fn trim_line_ending(mut s: String) -> String {
+    while s.ends_with('\n') || s.ends_with('\r') {
+        s.pop();
+    }
+    s
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        SecretSource::deserialize(deserializer)?
+            .resolve()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_literal() {
+        let secret: Secret = serde_json::from_str("\"abc123\"").unwrap();
+        assert_eq!(secret.expose(), "abc123");
+    }
+
+    #[test]
+    fn deserialize_env() {
+        // SAFETY: the variable name is unique to this test.
+        unsafe { std::env::set_var("EQUAILIZER_TEST_SECRET_ENV", "from-env") };
+        let secret: Secret =
+            serde_json::from_str(r#"{"env": "EQUAILIZER_TEST_SECRET_ENV"}"#).unwrap();
+        assert_eq!(secret.expose(), "from-env");
+    }
+
+    #[test]
+    fn deserialize_missing_env_errors() {
+        let result: Result<Secret, _> =
+            serde_json::from_str(r#"{"env": "EQUAILIZER_TEST_SECRET_MISSING"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_file_trims_trailing_newline() {
+        let path = std::env::temp_dir().join(format!("eq-secret-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        let json = serde_json::json!({ "file": path.to_str().unwrap() }).to_string();
+        let secret: Secret = serde_json::from_str(&json).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(secret.expose(), "from-file");
+    }
+
+    #[test]
+    fn deserialize_command() {
+        let secret: Secret = serde_json::from_str(r#"{"command": "echo from-command"}"#).unwrap();
+        assert_eq!(secret.expose(), "from-command");
+    }
+
+    #[test]
+    fn deserialize_failing_command_errors() {
+        let result: Result<Secret, _> = serde_json::from_str(r#"{"command": "exit 3"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn debug_and_display_are_redacted() {
+        let secret = Secret::new("hunter2");
+        assert!(!format!("{:?}", secret).contains("hunter2"));
+        assert!(!format!("{}", secret).contains("hunter2"));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+/// A template parsed at runtime, for profile overrides of the built-in emails.
+///
+/// The syntax is a small subset of askama's:
+/// - `{{ name }}` or `{{ name.field }}` inserts a value,
+/// - `{% for item in list %}...{% endfor %}` repeats for each element,
+/// - `{% if name %}...{% else %}...{% endif %}` checks that a value is present
+///   and not empty, `false` or `null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeTemplate {
+    nodes: Vec<Node>,
+    escape: Escape,
+}
+
+/// How inserted values are escaped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escape {
+    Html,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value(String),
+    For {
+        item: String,
+        list: String,
+        body: Vec<Node>,
+    },
+    If {
+        condition: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+enum Tag {
+    For(String, String),
+    If(String),
+    Else,
+    EndFor,
+    EndIf,
+}
+
+impl RuntimeTemplate {
+    pub fn parse(source: &str, escape: Escape) -> Result<Self> {
+        let mut rest = source;
+        let (nodes, end) = parse_nodes(&mut rest)?;
+        match end {
+            None => Ok(Self { nodes, escape }),
+            Some(_) => Err(invalid("unexpected end tag")),
+        }
+    }
+
+    /// Render against `context`, which should be a JSON object.
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        let mut scope = vec![];
+        render_nodes(&self.nodes, context, &mut scope, self.escape, &mut out);
+        out
+    }
+}
+
+/// Parse until the end of the source or an `else`/`endfor`/`endif` tag, which
+/// is returned for the caller to check.
+fn parse_nodes(rest: &mut &str) -> Result<(Vec<Node>, Option<Tag>)> {
+    let mut nodes = vec![];
+    loop {
+        let next = [rest.find("{{"), rest.find("{%")].into_iter().flatten().min();
+        let Some(start) = next else {
+            if !rest.is_empty() {
+                nodes.push(Node::Text(rest.to_string()));
+            }
+            *rest = "";
+            return Ok((nodes, None));
+        };
+        if start > 0 {
+            nodes.push(Node::Text(rest[..start].to_string()));
+        }
+
+        let is_value = rest[start..].starts_with("{{");
+        let close = if is_value { "}}" } else { "%}" };
+        let inner_start = start + 2;
+        let inner_end = rest[inner_start..]
+            .find(close)
+            .map(|i| inner_start + i)
+            .ok_or_else(|| invalid(&format!("unclosed `{}`", &rest[start..inner_start])))?;
+        let inner = rest[inner_start..inner_end].trim().to_string();
+        *rest = &rest[inner_end + 2..];
+
+        if is_value {
+            nodes.push(Node::Value(path(&inner)?));
+            continue;
+        }
+
+        match parse_tag(&inner)? {
+            Tag::For(item, list) => {
+                let (body, end) = parse_nodes(rest)?;
+                if !matches!(end, Some(Tag::EndFor)) {
+                    return Err(invalid("`for` without `endfor`"));
+                }
+                nodes.push(Node::For { item, list, body });
+            }
+            Tag::If(condition) => {
+                let (then, end) = parse_nodes(rest)?;
+                let otherwise = match end {
+                    Some(Tag::EndIf) => vec![],
+                    Some(Tag::Else) => match parse_nodes(rest)? {
+                        (otherwise, Some(Tag::EndIf)) => otherwise,
+                        _ => return Err(invalid("`else` without `endif`")),
+                    },
+                    _ => return Err(invalid("`if` without `endif`")),
+                };
+                nodes.push(Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                });
+            }
+            end => return Ok((nodes, Some(end))),
+        }
+    }
+}
+
+fn parse_tag(inner: &str) -> Result<Tag> {
+    let words: Vec<&str> = inner.split_whitespace().collect();
+    match words.as_slice() {
+        ["for", item, "in", list] => Ok(Tag::For(path(item)?, path(list)?)),
+        ["if", condition] => Ok(Tag::If(path(condition)?)),
+        ["else"] => Ok(Tag::Else),
+        ["endfor"] => Ok(Tag::EndFor),
+        ["endif"] => Ok(Tag::EndIf),
+        _ => Err(invalid(&format!("unknown tag `{{% {} %}}`", inner))),
+    }
+}
+
+fn path(s: &str) -> Result<String> {
+    let valid = !s.is_empty()
+        && s.split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    if valid {
+        Ok(s.to_string())
+    } else {
+        Err(invalid(&format!("`{}` isn't a value name", s)))
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::Template(reason.to_string())
+}
+
+fn render_nodes<'a>(
+    nodes: &'a [Node],
+    context: &'a Value,
+    scope: &mut Vec<(&'a str, &'a Value)>,
+    escape: Escape,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(path) => {
+                let text = display(lookup(path, context, scope));
+                match escape {
+                    Escape::Html => out.push_str(&html_escape(&text)),
+                    Escape::None => out.push_str(&text),
+                }
+            }
+            Node::For { item, list, body } => {
+                if let Some(Value::Array(items)) = lookup(list, context, scope) {
+                    for value in items {
+                        scope.push((item, value));
+                        render_nodes(body, context, scope, escape, out);
+                        scope.pop();
+                    }
+                }
+            }
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if truthy(lookup(condition, context, scope)) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, context, scope, escape, out);
+            }
+        }
+    }
+}
+
+/// Loop variables first, innermost first, then the context.
+fn lookup<'a>(path: &str, context: &'a Value, scope: &[(&str, &'a Value)]) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let root = scope
+        .iter()
+        .rev()
+        .find(|(name, _)| *name == first)
+        .map(|(_, value)| *value)
+        .or_else(|| context.get(first))?;
+    parts.try_fold(root, |value, part| value.get(part))
+}
+
+fn display(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(Value::Object(o)) => !o.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, escape: Escape, context: Value) -> String {
+        RuntimeTemplate::parse(source, escape).unwrap().render(&context)
+    }
+
+    #[test]
+    fn inserts_values_and_fields() {
+        let context = json!({"batch_id": "abc", "total": "$25.00", "day": {"date": "Mar 01"}});
+        let source = "Batch {{ batch_id }}: {{total}} on {{ day.date }}{{ missing }}";
+        assert_eq!(render(source, Escape::None, context), "Batch abc: $25.00 on Mar 01");
+    }
+
+    #[test]
+    fn loops_and_conditions() {
+        let context = json!({
+            "txns": [
+                {"payee": "Grocer", "notes": "Dinner"},
+                {"payee": "Diner", "notes": null},
+            ],
+            "warnings": [],
+        });
+        let source = "{% for txn in txns %}{{ txn.payee }}{% if txn.notes %} ({{ txn.notes }}){% endif %};{% endfor %}\
+                      {% if warnings %}warned{% else %}fine{% endif %}";
+        assert_eq!(render(source, Escape::None, context), "Grocer (Dinner);Diner;fine");
+    }
+
+    #[test]
+    fn escapes_html_values_only() {
+        let context = json!({"payee": "Couch & <Co>"});
+        assert_eq!(
+            render("<b>{{ payee }}</b>", Escape::Html, context.clone()),
+            "<b>Couch &amp; &lt;Co&gt;</b>"
+        );
+        assert_eq!(render("{{ payee }}", Escape::None, context), "Couch & <Co>");
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        let sources = ["{{ total", "{% for x in xs %}", "{% endif %}", "{% while x %}", "{{ a b }}"];
+        for source in sources {
+            assert!(
+                matches!(RuntimeTemplate::parse(source, Escape::None), Err(Error::Template(_))),
+                "{source} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+mod support;
+
+use equailizer::commands::amortize::amortize_transaction;
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::error::Error;
+use equailizer::money::{Currency, Money};
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+#[tokio::test]
+async fn amortize_transaction_saves_schedule_and_moves_to_proxy() {
+    let config = test_config();
+    let api = MockLunchMoney::new(vec![
+        test_transaction(1, 60000)
+            .with_payee("Couch Store")
+            .with_date(2025, 4, 10),
+    ]);
+    let persistence = InMemoryPersistence::new();
+
+    let schedule = amortize_transaction(1, 6, &config, &api, &persistence)
+        .await
+        .expect("amortize should succeed");
+
+    assert_eq!(schedule.count(), 6);
+    assert_eq!(schedule.installments[0].amount, Money::new_from_minor(10000, Currency::USD));
+    assert_eq!(schedule.billed_count(), 0);
+    assert_eq!(persistence.saved_schedules(), vec![schedule]);
+
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0, 1);
+    assert_eq!(updates[0].1.category_id, Some(99));
+}
+
+#[tokio::test]
+async fn amortize_transaction_rejects_already_scheduled() {
+    let config = test_config();
+    let api = MockLunchMoney::new(vec![test_transaction(1, 60000).with_date(2025, 4, 10)]);
+    let persistence = InMemoryPersistence::new();
+
+    amortize_transaction(1, 6, &config, &api, &persistence)
+        .await
+        .expect("first amortize should succeed");
+
+    // The mock doesn't apply updates, so only the existing schedule stops this.
+    let result = amortize_transaction(1, 3, &config, &api, &persistence).await;
+    assert!(matches!(result, Err(Error::NotAmortizable(1, _))));
+    assert_eq!(persistence.saved_schedules().len(), 1);
+}
+
+#[tokio::test]
+async fn amortize_transaction_rejects_split_transaction() {
+    let config = test_config();
+    let api = MockLunchMoney::new(vec![test_transaction(1, 60000).with_children()]);
+    let persistence = InMemoryPersistence::new();
+
+    let result = amortize_transaction(1, 6, &config, &api, &persistence).await;
+    assert!(matches!(result, Err(Error::NotAmortizable(1, _))));
+    assert!(api.updates_received.lock().unwrap().is_empty());
+    assert!(persistence.saved_schedules().is_empty());
+}
@@ -0,0 +1,150 @@
+mod support;
+
+use chrono::NaiveDate;
+use equailizer::commands::apply::apply_plan;
+use equailizer::commands::create_batch::plan::BatchPlan;
+use equailizer::commands::create_batch::plan_batch;
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::error::Error;
+use equailizer::money::{Currency, Money};
+use equailizer::plugin::PluginManager;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+fn range() -> (NaiveDate, NaiveDate) {
+    (
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+    )
+}
+
+fn txns() -> Vec<equailizer::lunch_money::model::transaction::Transaction> {
+    vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_payee("Store"),
+        test_transaction(2, 2001) // odd amount, so the split is random
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_payee("Dinner"),
+    ]
+}
+
+/// Write the plan out and read it back, as `create-batch --plan` and `apply` do.
+fn round_trip(plan: &BatchPlan) -> BatchPlan {
+    serde_json::from_str(&serde_json::to_string(plan).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn plan_batch_changes_nothing() {
+    let config = test_config();
+    let api = MockLunchMoney::new(txns());
+    let persistence = InMemoryPersistence::new();
+    let (start, end) = range();
+
+    let plan = plan_batch(start, end, &config, &api, &persistence, None)
+        .await
+        .expect("plan_batch should succeed");
+
+    assert_eq!(plan.adds.len(), 1);
+    assert_eq!(plan.splits.len(), 1);
+    assert!(api.updates_received.lock().unwrap().is_empty());
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+    assert!(persistence.saved_batches().is_empty());
+}
+
+#[tokio::test]
+async fn apply_executes_exactly_the_planned_updates() {
+    let config = test_config();
+    let api = MockLunchMoney::new(txns()).with_split_ids(vec![vec![200, 201]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+    let (start, end) = range();
+
+    let plan = plan_batch(start, end, &config, &api, &persistence, None)
+        .await
+        .expect("plan_batch should succeed");
+    let planned_splits = plan.splits[0].splits.clone();
+    let planned_debtor_amount = planned_splits[1].amount;
+
+    let report = apply_plan(round_trip(&plan), &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("apply_plan should succeed");
+
+    let splits = api.update_and_splits_received.lock().unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].2, planned_splits);
+    assert_eq!(api.updates_received.lock().unwrap()[0].1, plan.adds[0].update);
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![1, 201]);
+    assert_eq!(
+        batches[0].amount,
+        Money::new_from_minor(1500, Currency::USD) + planned_debtor_amount
+    );
+    assert_eq!(report.batch_id, Some(batches[0].id.clone()));
+    assert_eq!(notifier.call_count(), 1);
+}
+
+#[tokio::test]
+async fn apply_rejects_plan_when_a_transaction_changed() {
+    let config = test_config();
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+    let (start, end) = range();
+
+    let plan = plan_batch(start, end, &config, &MockLunchMoney::new(txns()), &persistence, None)
+        .await
+        .expect("plan_batch should succeed");
+
+    // The split transaction's amount was edited after planning.
+    let mut changed = txns();
+    changed[1] = test_transaction(2, 2500)
+        .with_tags(vec![("eq-to-split", 11)])
+        .with_payee("Dinner");
+    let api = MockLunchMoney::new(changed);
+
+    let result = apply_plan(round_trip(&plan), &api, &persistence, &notifier, &mut PluginManager::empty()).await;
+
+    assert!(matches!(result, Err(Error::StalePlan(ref ids)) if ids == &vec![2]));
+    assert!(api.updates_received.lock().unwrap().is_empty());
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+    assert!(persistence.saved_batches().is_empty());
+    assert_eq!(notifier.call_count(), 0);
+}
@@ -0,0 +1,291 @@
+mod support;
+
+use equailizer::category_map::CategoryMap;
+use equailizer::commands::balance::{balance, record_adjustment};
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::error::Error;
+use equailizer::money::{Currency, Money};
+use equailizer::persist::{Adjustment, AdjustmentKind, Batch, Persistence, Settlement};
+use std::collections::BTreeMap;
+use support::mocks::InMemoryPersistence;
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+fn date(m: u32, d: u32) -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+}
+
+fn usd(cents: i64) -> Money {
+    Money::new_from_minor(cents, Currency::USD)
+}
+
+fn batch(id: &str, cents: i64, created_on: Option<chrono::NaiveDate>, reconciled: bool) -> Batch {
+    Batch {
+        id: id.to_string(),
+        amount: usd(cents),
+        transaction_ids: vec![],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: reconciled.then_some(Settlement {
+            settlement_credit_id: 500,
+            settlement_debit_id: 600,
+            settled_on: None,
+        }),
+    }
+}
+
+fn adjustment(
+    kind: AdjustmentKind,
+    cents: i64,
+    on: chrono::NaiveDate,
+    note: Option<&str>,
+) -> Adjustment {
+    Adjustment {
+        id: format!("adj-{cents}"),
+        date: on,
+        kind,
+        amount: usd(cents),
+        note: note.map(str::to_string),
+        batch_id: None,
+    }
+}
+
+#[test]
+fn balance_adds_batches_settlements_and_adjustments() {
+    let persistence = InMemoryPersistence::with_batches(vec![
+        batch("settled", 9900, Some(date(1, 5)), true),
+        batch("old", 4000, Some(date(2, 1)), false),
+        batch("recent", 2500, Some(date(4, 20)), false),
+        batch("legacy", 1000, None, false),
+    ])
+    .with_adjustments(vec![
+        adjustment(AdjustmentKind::Payment, 2000, date(3, 1), Some("cash")),
+        adjustment(AdjustmentKind::Charge, 300, date(4, 25), None),
+        adjustment(AdjustmentKind::Forgiven, 500, date(4, 25), None),
+    ]);
+
+    let report = balance(date(5, 1), &test_config(), &persistence).expect("balance should succeed");
+
+    assert_eq!(report.balance, usd(5300));
+    let open: Vec<_> = report
+        .open_batches
+        .iter()
+        .map(|b| (b.batch_id.as_str(), b.age_days))
+        .collect();
+    assert_eq!(open, vec![("legacy", None), ("old", Some(89)), ("recent", Some(11))]);
+
+    let ages: Vec<_> = report
+        .by_age
+        .iter()
+        .map(|b| (b.label.as_str(), b.count, b.total))
+        .collect();
+    assert_eq!(
+        ages,
+        vec![
+            ("0-30 days", 1, usd(2500)),
+            ("61-90 days", 1, usd(4000)),
+            ("unknown", 1, usd(1000)),
+        ]
+    );
+
+    let ledger: Vec<_> = report
+        .ledger
+        .iter()
+        .map(|e| (e.description.as_str(), e.change, e.balance))
+        .collect();
+    assert_eq!(
+        ledger,
+        vec![
+            ("batch legacy", usd(1000), usd(1000)),
+            ("batch settled", usd(9900), usd(10900)),
+            ("batch settled settled", usd(-9900), usd(1000)),
+            ("batch old", usd(4000), usd(5000)),
+            ("payment: cash", usd(-2000), usd(3000)),
+            ("batch recent", usd(2500), usd(5500)),
+            ("charge", usd(300), usd(5800)),
+            ("forgiven", usd(-500), usd(5300)),
+        ]
+    );
+}
+
+#[test]
+fn record_adjustment_appends_to_history() {
+    let persistence = InMemoryPersistence::new()
+        .with_adjustments(vec![adjustment(AdjustmentKind::Charge, 300, date(4, 1), None)]);
+
+    let recorded = record_adjustment(
+        AdjustmentKind::Payment,
+        usd(2000),
+        date(4, 2),
+        Some("cash".to_string()),
+        None,
+        &test_config(),
+        &persistence,
+    )
+    .expect("adjustment should be recorded");
+
+    let history = persistence.adjustments().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1], recorded);
+    assert_eq!(recorded.signed_amount(), usd(-2000));
+}
+
+#[test]
+fn record_adjustment_rejects_non_positive_amounts() {
+    let persistence = InMemoryPersistence::new();
+
+    let result = record_adjustment(
+        AdjustmentKind::Payment,
+        usd(0),
+        date(4, 2),
+        None,
+        None,
+        &test_config(),
+        &persistence,
+    );
+
+    assert!(matches!(result, Err(Error::InvalidAdjustment(_))));
+    assert!(persistence.adjustments().unwrap().is_empty());
+}
+
+#[test]
+fn record_adjustment_against_a_batch_closes_it() {
+    let persistence = InMemoryPersistence::with_batches(vec![
+        batch("cash", 4000, Some(date(3, 1)), false),
+        batch("open", 2500, Some(date(3, 15)), false),
+    ]);
+
+    let recorded = record_adjustment(
+        AdjustmentKind::Payment,
+        usd(4000),
+        date(4, 2),
+        Some("paid in cash".to_string()),
+        Some("cash".to_string()),
+        &test_config(),
+        &persistence,
+    )
+    .expect("adjustment should be recorded");
+
+    let closed = persistence.get_batch("cash").unwrap();
+    assert_eq!(closed.closed_by, Some(recorded.id));
+    let open: Vec<_> = persistence
+        .unreconciled_batches()
+        .unwrap()
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
+    assert_eq!(open, vec!["open"]);
+
+    let report = balance(date(5, 1), &test_config(), &persistence).expect("balance should succeed");
+    assert_eq!(report.balance, usd(2500));
+    assert_eq!(report.open_batches.len(), 1);
+    let ledger: Vec<_> = report.ledger.iter().map(|e| e.description.as_str()).collect();
+    assert_eq!(
+        ledger,
+        vec!["batch cash", "batch open", "payment for batch cash: paid in cash"]
+    );
+}
+
+#[test]
+fn record_adjustment_only_closes_a_batch_once_it_is_paid_in_full() {
+    let persistence =
+        InMemoryPersistence::with_batches(vec![batch("costco", 20000, Some(date(3, 1)), false)]);
+    let record = |kind, cents| {
+        record_adjustment(
+            kind,
+            usd(cents),
+            date(4, 2),
+            None,
+            Some("costco".to_string()),
+            &test_config(),
+            &persistence,
+        )
+    };
+
+    record(AdjustmentKind::Payment, 500).expect("partial payment should be recorded");
+    assert!(persistence.get_batch("costco").unwrap().closed_by.is_none());
+    assert_eq!(persistence.unreconciled_batches().unwrap().len(), 1);
+
+    assert!(matches!(
+        record(AdjustmentKind::Payment, 19600),
+        Err(Error::InvalidAdjustment(_))
+    ));
+    assert_eq!(persistence.adjustments().unwrap().len(), 1);
+
+    record(AdjustmentKind::Payment, 15000).expect("partial payment should be recorded");
+    assert!(persistence.get_batch("costco").unwrap().closed_by.is_none());
+
+    let last = record(AdjustmentKind::Forgiven, 4500).expect("forgiveness should be recorded");
+    assert_eq!(persistence.get_batch("costco").unwrap().closed_by, Some(last.id));
+    assert!(persistence.unreconciled_batches().unwrap().is_empty());
+
+    let report = balance(date(5, 1), &test_config(), &persistence).expect("balance should succeed");
+    assert_eq!(report.balance, usd(0));
+}
+
+#[test]
+fn record_adjustment_rejects_closing_settled_batches_and_charges() {
+    let persistence = InMemoryPersistence::with_batches(vec![
+        batch("settled", 4000, Some(date(3, 1)), true),
+        batch("open", 2500, Some(date(3, 15)), false),
+    ]);
+    let record = |kind, batch_id: &str| {
+        record_adjustment(
+            kind,
+            usd(2500),
+            date(4, 2),
+            None,
+            Some(batch_id.to_string()),
+            &test_config(),
+            &persistence,
+        )
+    };
+
+    assert!(matches!(
+        record(AdjustmentKind::Payment, "settled"),
+        Err(Error::BatchAlreadyReconciled(_))
+    ));
+    assert!(matches!(
+        record(AdjustmentKind::Charge, "open"),
+        Err(Error::InvalidAdjustment(_))
+    ));
+    assert!(persistence.adjustments().unwrap().is_empty());
+    assert!(persistence.get_batch("open").unwrap().closed_by.is_none());
+}
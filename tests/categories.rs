@@ -0,0 +1,84 @@
+mod support;
+
+use equailizer::category_map::CategoryMap;
+use equailizer::commands::categories::suggest_category_map;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::lunch_money::model::category::Category;
+use equailizer::money::Currency;
+use std::collections::BTreeMap;
+use support::mocks::MockLunchMoney;
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap {
+            categories: BTreeMap::from([(1, 10)]),
+            fallback: None,
+        },
+        reminders: Reminders::default(),
+    }
+}
+
+fn category(id: u32, name: &str) -> Category {
+    Category {
+        id,
+        name: name.to_string(),
+        is_group: false,
+        archived: false,
+    }
+}
+
+#[tokio::test]
+async fn suggest_category_map_matches_names_and_keeps_existing_mappings() {
+    let config = test_config();
+    let creditor_api = MockLunchMoney::new(vec![]).with_categories(vec![
+        category(1, "Rent"),
+        category(2, "Groceries"),
+        category(3, "Gadgets"),
+        category(99, "Proxy"),
+    ]);
+    let debtor_api = MockLunchMoney::new(vec![]).with_categories(vec![
+        category(20, "Housing"),
+        category(21, "groceries"),
+        category(22, "Proxy"),
+    ]);
+
+    let suggestion = suggest_category_map(&config, &creditor_api, &debtor_api)
+        .await
+        .expect("suggest should succeed");
+
+    // The configured mapping stays, Groceries is matched by name, and the
+    // proxy category is never mapped
+    assert_eq!(
+        suggestion.category_map.categories,
+        BTreeMap::from([(1, 10), (2, 21)])
+    );
+    assert_eq!(suggestion.unmatched, vec![category(3, "Gadgets")]);
+}
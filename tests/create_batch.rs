@@ -1,35 +1,56 @@
 mod support;
 
-use equailizer::commands::create_batch::create_batch;
-use equailizer::config::{Config, Creditor, Debtor, JMAP};
-use equailizer::lunch_money::model::transaction::TransactionStatus;
-use equailizer::usd::USD;
+use equailizer::commands::create_batch::{create_batch, execute_plan, plan_batch};
+use equailizer::category_map::CategoryMap;
+use equailizer::email::Notifiers;
+use equailizer::persist::Persistence;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::lunch_money::model::transaction::{TransactionId, TransactionStatus};
+use equailizer::marker::{BatchMarker, MarkerTarget};
+use equailizer::money::{Currency, Money};
+use equailizer::persist::Batch;
+use equailizer::report::Handling;
+use equailizer::review::{ProposedAction, ReviewDecision};
+use rust_decimal::dec;
 use support::builders::{test_transaction, TransactionBuilder};
 use equailizer::plugin::PluginManager;
-use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+use support::mocks::{
+    FailingBatchNotifier, InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier, ScriptedReviewer,
+};
+use std::collections::BTreeMap;
 
 fn test_config() -> Config {
     Config {
         creditor: Creditor {
-            api_key: "test-creditor-key".to_string(),
+            api_key: "test-creditor-key".into(),
             proxy_category_id: 99,
             settlement_account_id: 1000,
             email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
         },
         debtor: Debtor {
-            api_key: "test-debtor-key".to_string(),
+            api_key: "test-debtor-key".into(),
             name: "TestDebtor".to_string(),
             settlement_account_id: 2000,
             email_address: "debtor@test.com".to_string(),
-            venmo_username: "testdebtor".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
         },
-        jmap: JMAP {
+        jmap: Some(JMAP {
             api_session_endpoint: "https://example.com".to_string(),
-            api_key: "test-jmap-key".to_string(),
+            api_key: "test-jmap-key".into(),
             sent_mailbox: "sent".to_string(),
             sending_address: "sender@test.com".to_string(),
-        },
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
         plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
     }
 }
 
@@ -64,7 +85,7 @@ async fn create_batch_with_add_tagged_transactions() {
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
     let batch = &batches[0];
-    assert_eq!(batch.amount, USD::new_from_cents(4000)); // 1500 + 2500
+    assert_eq!(batch.amount, Money::new_from_minor(4000, Currency::USD)); // 1500 + 2500
     assert_eq!(batch.transaction_ids.len(), 2);
     assert!(batch.reconciliation.is_none());
 
@@ -83,10 +104,45 @@ async fn create_batch_with_add_tagged_transactions() {
     // Verify notification was sent
     assert_eq!(notifier.call_count(), 1);
     let calls = notifier.calls.lock().unwrap();
-    assert_eq!(calls[0].total, USD::new_from_cents(4000));
+    assert_eq!(calls[0].total, Money::new_from_minor(4000, Currency::USD));
     assert_eq!(calls[0].txn_count, 2);
 }
 
+#[tokio::test]
+async fn create_batch_reports_each_failed_notifier_without_failing() {
+    let config = test_config();
+    let txns = vec![test_transaction(1, 1500)
+        .with_tags(vec![("eq-to-batch", 10)])
+        .with_date(2025, 3, 1)];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::new();
+    let notifier = Notifiers {
+        notifiers: vec![
+            ("smtp".to_string(), Box::new(FailingBatchNotifier { reason: "connection refused".to_string() })),
+            ("webhook".to_string(), Box::new(FailingBatchNotifier { reason: "404 Not Found".to_string() })),
+        ],
+    };
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    let report = create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("a failed notifier shouldn't fail the run");
+
+    // The batch is saved and finished, and each failure is reported on its own.
+    assert_eq!(persistence.saved_batches().len(), 1);
+    assert!(persistence.unfinished_journal().unwrap().is_none());
+    assert_eq!(
+        report.issues,
+        vec![
+            "Batch notification failed: smtp: connection refused".to_string(),
+            "Batch notification failed: webhook: 404 Not Found".to_string(),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn create_batch_with_split_tagged_transactions() {
     let config = test_config();
@@ -114,8 +170,12 @@ async fn create_batch_with_split_tagged_transactions() {
     assert_eq!(batches.len(), 1);
     let batch = &batches[0];
     // 2000 split evenly = 1000 each, batch gets debtor's half
-    assert_eq!(batch.amount, USD::new_from_cents(1000));
+    assert_eq!(batch.amount, Money::new_from_minor(1000, Currency::USD));
     assert_eq!(batch.transaction_ids, vec![201]);
+    // The creditor's half is remembered so it isn't billed later
+    assert_eq!(batch.split_ids, vec![200]);
+    // The original category is kept for mapping to the debtor's at reconciliation
+    assert_eq!(batch.categories, BTreeMap::from([(201, 42)]));
 
     // Verify an update_and_split call was made
     let splits = api.update_and_splits_received.lock().unwrap();
@@ -196,6 +256,70 @@ async fn create_batch_skips_pending_transactions() {
     // Pending txns should be filtered out, resulting in no batch
     assert_eq!(persistence.saved_batches().len(), 0);
     assert_eq!(notifier.call_count(), 0);
+    // ...but remembered for a later run
+    assert_eq!(persistence.saved_pending_ids(), vec![1]);
+}
+
+#[tokio::test]
+async fn create_batch_reports_tagged_pending_transactions() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500).with_tags(vec![("eq-to-batch", 10)]),
+        test_transaction(2, 800)
+            .with_tags(vec![("eq-to-split", 11)])
+            .pending(),
+        test_transaction(3, 999).pending(), // untagged, not remembered
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    assert_eq!(persistence.saved_batches()[0].transaction_ids, vec![1]);
+    assert_eq!(persistence.saved_pending_ids(), vec![2]);
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 1);
+    assert!(calls[0].warnings[0].contains("Transaction 2 is tagged but still pending"));
+}
+
+#[tokio::test]
+async fn create_batch_picks_up_previously_pending_transactions_outside_range() {
+    let config = test_config();
+    let txns = vec![
+        // Posted since the last run, dated before this run's range
+        test_transaction(5, 1200)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 6, 28),
+        // Still pending
+        test_transaction(6, 300)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 6, 29)
+            .pending(),
+    ];
+
+    let api = MockLunchMoney::new(txns).with_date_filtering();
+    let persistence = InMemoryPersistence::new().with_pending_ids(vec![5, 6, 7]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 7, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![5]);
+    // 7 no longer exists and is forgotten; 6 is carried again
+    assert_eq!(persistence.saved_pending_ids(), vec![6]);
 }
 
 #[tokio::test]
@@ -225,7 +349,7 @@ async fn create_batch_issues_warning_for_add_with_children() {
     // Only the valid txn should be in the batch
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
-    assert_eq!(batches[0].amount, USD::new_from_cents(1500));
+    assert_eq!(batches[0].amount, Money::new_from_minor(1500, Currency::USD));
 
     // Warning should be included in notification
     let calls = notifier.calls.lock().unwrap();
@@ -260,7 +384,7 @@ async fn create_batch_issues_warning_for_split_with_children() {
     // Only the valid txn should be in the batch
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
-    assert_eq!(batches[0].amount, USD::new_from_cents(1500));
+    assert_eq!(batches[0].amount, Money::new_from_minor(1500, Currency::USD));
 
     // Warning should be included in notification
     let calls = notifier.calls.lock().unwrap();
@@ -296,7 +420,7 @@ async fn create_batch_issues_warning_for_update_error() {
     // Only the successful txn should be in the batch
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
-    assert_eq!(batches[0].amount, USD::new_from_cents(1500));
+    assert_eq!(batches[0].amount, Money::new_from_minor(1500, Currency::USD));
 
     // Warning about the update error should be included in notification
     let calls = notifier.calls.lock().unwrap();
@@ -334,7 +458,7 @@ async fn create_batch_mixed_add_and_split() {
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
     // 3000 (full add) + 1000 (half of 2000 split) = 4000
-    assert_eq!(batches[0].amount, USD::new_from_cents(4000));
+    assert_eq!(batches[0].amount, Money::new_from_minor(4000, Currency::USD));
 
     // Should have 1 update (add) + 1 update_and_split
     assert_eq!(api.updates_received.lock().unwrap().len(), 1);
@@ -345,6 +469,44 @@ async fn create_batch_mixed_add_and_split() {
     assert_eq!(calls[0].txn_count, 2);
 }
 
+#[tokio::test]
+async fn create_batch_reports_how_each_transaction_was_handled() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 3000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_payee("Full charge"),
+        test_transaction(2, 2000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_payee("Shared meal"),
+        test_transaction(3, 500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_children(),
+    ];
+
+    let api = MockLunchMoney::new(txns).with_split_ids(vec![vec![300, 301]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+
+    let report = create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    assert_eq!(report.batch_id, Some(persistence.saved_batches()[0].id.clone()));
+    assert_eq!(report.total, Some(Money::new_from_minor(4000, Currency::USD)));
+    let handled: Vec<(u32, Handling)> = report
+        .transactions
+        .iter()
+        .map(|t| (t.id, t.handling))
+        .collect();
+    assert_eq!(handled, vec![(1, Handling::Added), (301, Handling::Split)]);
+    assert_eq!(report.issues.len(), 1);
+    assert!(report.issues[0].contains("has children"));
+}
+
 #[tokio::test]
 async fn create_batch_resplits_child_transaction() {
     let config = test_config();
@@ -387,13 +549,16 @@ async fn create_batch_resplits_child_transaction() {
     let batches = persistence.saved_batches();
     assert_eq!(batches.len(), 1);
     let batch = &batches[0];
-    assert_eq!(batch.amount, USD::new_from_cents(1000)); // half of 2000
+    assert_eq!(batch.amount, Money::new_from_minor(1000, Currency::USD)); // half of 2000
     assert_eq!(batch.transaction_ids, vec![301]); // debtor half ID
+    assert_eq!(batch.split_ids, vec![300]); // creditor half ID, not the sibling
 
     // Verify the parent was unsplit before resplit
     let unsplits = api.unsplits_received.lock().unwrap();
     assert_eq!(unsplits.len(), 1);
     assert_eq!(unsplits[0], 100);
+    // The snapshot taken before unsplitting is gone once the re-split succeeds
+    assert!(persistence.saved_split_snapshots().is_empty());
 
     // Verify update_transaction_and_split was called on the parent — the parent
     // update marks it Cleared (children inherit) and leaves tags untouched.
@@ -409,17 +574,17 @@ async fn create_batch_resplits_child_transaction() {
     assert_eq!(split_items.len(), 3);
 
     // Creditor half: half of $20, original category
-    assert_eq!(split_items[0].amount, USD::new_from_cents(1000));
+    assert_eq!(split_items[0].amount, Money::new_from_minor(1000, Currency::USD));
     assert_eq!(split_items[0].payee, Some("Restaurant".to_string()));
     assert_eq!(split_items[0].category_id, Some(42)); // original category
 
     // Debtor half: half of $20, proxy category
-    assert_eq!(split_items[1].amount, USD::new_from_cents(1000));
+    assert_eq!(split_items[1].amount, Money::new_from_minor(1000, Currency::USD));
     assert_eq!(split_items[1].payee, Some("Restaurant".to_string()));
     assert_eq!(split_items[1].category_id, Some(99)); // proxy category
 
     // Sibling: preserved as-is
-    assert_eq!(split_items[2].amount, USD::new_from_cents(1000));
+    assert_eq!(split_items[2].amount, Money::new_from_minor(1000, Currency::USD));
     assert_eq!(split_items[2].payee, Some("Restaurant".to_string()));
     assert_eq!(split_items[2].category_id, Some(42));
     assert_eq!(split_items[2].notes, Some("sibling".to_string()));
@@ -428,7 +593,7 @@ async fn create_batch_resplits_child_transaction() {
     assert_eq!(notifier.call_count(), 1);
     let calls = notifier.calls.lock().unwrap();
     assert_eq!(calls[0].txn_count, 1);
-    assert_eq!(calls[0].total, USD::new_from_cents(1000));
+    assert_eq!(calls[0].total, Money::new_from_minor(1000, Currency::USD));
 }
 
 #[tokio::test]
@@ -529,6 +694,94 @@ async fn create_batch_resplit_logs_failure_when_split_fails_after_unsplit() {
     assert!(calls[0].warnings[0].contains("mock update_and_split failure"));
 }
 
+#[tokio::test]
+async fn create_batch_resplit_restores_original_split_when_split_fails() {
+    let config = test_config();
+
+    let parent = test_transaction(100, 3000)
+        .with_children()
+        .with_date(2025, 10, 1)
+        .with_payee("Restaurant");
+    let tagged_child = test_transaction(20, 2000)
+        .with_parent(100)
+        .with_tags(vec![("eq-to-split", 11)])
+        .with_date(2025, 10, 1)
+        .with_payee("Restaurant")
+        .with_category(42, "Dining");
+    let sibling = test_transaction(21, 1000)
+        .with_parent(100)
+        .with_date(2025, 10, 1)
+        .with_payee("Restaurant")
+        .with_category(43, "Drinks")
+        .with_notes("sibling");
+
+    let api = MockLunchMoney::new(vec![parent, tagged_child, sibling])
+        .with_failing_update_and_splits(vec![100])
+        .with_split_ids(vec![vec![400, 401]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 10, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed even if split fails after unsplit");
+
+    // The parent was split back into its original children...
+    let splits = api.splits_received.lock().unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].0, 100);
+    let items = &splits[0].1;
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].amount, Money::new_from_minor(2000, Currency::USD));
+    assert_eq!(items[0].category_id, Some(42));
+    assert_eq!(items[1].amount, Money::new_from_minor(1000, Currency::USD));
+    assert_eq!(items[1].category_id, Some(43));
+    assert_eq!(items[1].notes, Some("sibling".to_string()));
+
+    // ...and the tagged child got its tag back, so a later run tries again.
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0, 400);
+    assert_eq!(updates[0].1.tags, Some(vec!["eq-to-split".to_string()]));
+
+    assert!(persistence.saved_split_snapshots().is_empty());
+    let calls = notifier.calls.lock().unwrap();
+    assert!(calls[0].warnings[0].contains("original split restored"));
+}
+
+#[tokio::test]
+async fn create_batch_resplit_keeps_snapshot_when_restore_fails() {
+    let config = test_config();
+
+    let parent = test_transaction(100, 3000).with_children();
+    let tagged_child = test_transaction(20, 2000)
+        .with_parent(100)
+        .with_tags(vec![("eq-to-split", 11)]);
+    let sibling = test_transaction(21, 1000).with_parent(100);
+
+    let api = MockLunchMoney::new(vec![parent, tagged_child.clone(), sibling.clone()])
+        .with_failing_update_and_splits(vec![100])
+        .with_failing_splits(vec![100]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed even if the restore fails");
+
+    let snapshots = persistence.saved_split_snapshots();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].parent_id, 100);
+    assert_eq!(snapshots[0].children, vec![tagged_child, sibling]);
+    let calls = notifier.calls.lock().unwrap();
+    assert!(calls[0].warnings[0].contains("equailizer repair"));
+}
+
 #[tokio::test]
 async fn create_batch_notifies_with_saved_batch_id() {
     let config = test_config();
@@ -554,3 +807,657 @@ async fn create_batch_notifies_with_saved_batch_id() {
     let calls = notifier.calls.lock().unwrap();
     assert_eq!(calls[0].batch_id, batches[0].id);
 }
+
+#[tokio::test]
+async fn create_batch_uses_profile_tags() {
+    let mut config = test_config();
+    config.tags = Tags {
+        add: "partner-batch".to_string(),
+        split: "partner-split".to_string(),
+        add_aliases: vec!["pb".to_string()],
+        split_aliases: vec![],
+        ..Tags::default()
+    };
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("partner-batch", 10)])
+            .with_date(2025, 3, 1),
+        test_transaction(2, 2500)
+            .with_tags(vec![("pb", 12), ("external-tag", 13)])
+            .with_date(2025, 3, 2),
+        test_transaction(3, 999) // default tag belongs to another profile
+            .with_tags(vec![("eq-to-batch", 14)])
+            .with_date(2025, 3, 3),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![1, 2]);
+
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates[0].1.tags, Some(vec![]));
+    assert_eq!(updates[1].1.tags, Some(vec!["external-tag".to_string()]));
+}
+
+#[tokio::test]
+async fn create_batch_applies_rules_alongside_tags() {
+    let mut config = test_config();
+    config.rules = serde_json::from_str(
+        r#"[
+            {"name": "rent", "payee": {"contains": "rent co"}, "action": {"ratio": "0.6"}},
+            {"name": "streaming", "payee": {"regex": "^Netflix"}, "action": "add"}
+        ]"#,
+    )
+    .unwrap();
+    let txns = vec![
+        test_transaction(1, 200000)
+            .with_payee("Rent Co")
+            .with_date(2025, 3, 1)
+            .with_category(42, "Rent"),
+        test_transaction(2, 1599)
+            .with_payee("Netflix.com")
+            .with_date(2025, 3, 2),
+        test_transaction(3, 1000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_payee("Tagged")
+            .with_date(2025, 3, 3),
+        test_transaction(4, 999) // matches no rule
+            .with_payee("Coffee")
+            .with_date(2025, 3, 4),
+    ];
+
+    let api = MockLunchMoney::new(txns).with_split_ids(vec![vec![200, 201]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    // Tagged add, rule add, and the debtor's 60% of the rent.
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![3, 2, 201]);
+    assert_eq!(batches[0].amount, Money::new_from_minor(1000 + 1599 + 120000, Currency::USD));
+
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[1].0, 2);
+    assert_eq!(updates[1].1.category_id, Some(99));
+
+    let splits = api.update_and_splits_received.lock().unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].0, 1);
+    assert_eq!(splits[0].2[0].amount, Money::new_from_minor(80000, Currency::USD));
+    assert_eq!(splits[0].2[0].category_id, Some(42));
+    assert_eq!(splits[0].2[1].amount, Money::new_from_minor(120000, Currency::USD));
+    assert_eq!(splits[0].2[1].category_id, Some(99));
+}
+
+#[tokio::test]
+async fn create_batch_amortize_tag_starts_schedule_and_bills_first_installment() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 120000)
+            .with_tags(vec![("eq-to-amortize-12", 10), ("insurance", 11)])
+            .with_payee("Lemonade")
+            .with_date(2025, 3, 5),
+        test_transaction(2, 1500)
+            .with_tags(vec![("eq-to-batch", 12)])
+            .with_date(2025, 3, 6),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    // The amortized transaction moves to the proxy category with its tag stripped.
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 2);
+    let amortize_update = updates.iter().find(|u| u.0 == 1).unwrap();
+    assert_eq!(amortize_update.1.category_id, Some(99));
+    assert_eq!(amortize_update.1.tags, Some(vec!["insurance".to_string()]));
+
+    let schedules = persistence.saved_schedules();
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].transaction_id, 1);
+    assert_eq!(schedules[0].count(), 12);
+    assert_eq!(schedules[0].billed_count(), 1);
+
+    // Only the first installment is billed, not the full amount.
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![2]);
+    assert_eq!(batches[0].installments.len(), 1);
+    assert_eq!(batches[0].installments[0].number, 1);
+    assert_eq!(batches[0].installments[0].count, 12);
+    assert_eq!(batches[0].amount, Money::new_from_minor(1500 + 10000, Currency::USD));
+    assert_eq!(
+        schedules[0].installments[0].batch_id.as_deref(),
+        Some(batches[0].id.as_str())
+    );
+
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].txn_count, 2);
+}
+
+#[tokio::test]
+async fn create_batch_bills_due_installments_without_new_transactions() {
+    use equailizer::amortization::AmortizationSchedule;
+
+    let config = test_config();
+    let original = test_transaction(1, 30000)
+        .with_payee("Furniture Co")
+        .with_date(2025, 1, 15);
+    let mut schedule = AmortizationSchedule::new(&original, 3);
+    schedule.mark_billed(1, "earlier-batch");
+
+    let api = MockLunchMoney::new(vec![]);
+    let persistence = InMemoryPersistence::new().with_schedules(vec![schedule]);
+    let notifier = RecordingBatchNotifier::new();
+
+    // Installment 2 is due 2025-02-15; installment 3 not until 2025-03-15.
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 2, 28).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert!(batches[0].transaction_ids.is_empty());
+    assert_eq!(batches[0].installments.len(), 1);
+    assert_eq!(batches[0].installments[0].number, 2);
+    assert_eq!(batches[0].amount, Money::new_from_minor(10000, Currency::USD));
+
+    let schedules = persistence.saved_schedules();
+    assert_eq!(schedules[0].billed_count(), 2);
+    assert!(!schedules[0].is_complete());
+}
+
+#[tokio::test]
+async fn create_batch_leaves_out_foreign_currency_transactions() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+        test_transaction(2, 2000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 2)
+            .with_currency("eur"),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].amount, Money::new_from_minor(1500, Currency::USD));
+    assert_eq!(batches[0].transaction_ids, vec![1]);
+
+    // The EUR transaction is untouched and reported
+    let updates = api.updates_received.lock().unwrap();
+    assert!(updates.iter().all(|(id, _)| *id != 2));
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 1);
+    assert!(calls[0].warnings[0].contains("EUR"));
+}
+
+#[tokio::test]
+async fn create_batch_leaves_out_already_batched_transactions() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+        // Re-tagged after it was batched
+        test_transaction(2, 2000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 2),
+        // Child of a transaction that was batched whole before being split
+        test_transaction(3, 500)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_date(2025, 3, 3)
+            .with_parent(50),
+        // In the proxy category without being in any batch
+        test_transaction(4, 700)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 4)
+            .with_category(99, "Proxy"),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "earlier".to_string(),
+        amount: Money::new_from_minor(2000, Currency::USD),
+        transaction_ids: vec![2, 50],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: None,
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].transaction_ids, vec![1]);
+    assert_eq!(batches[1].amount, Money::new_from_minor(1500, Currency::USD));
+
+    // Nothing but the new transaction was touched
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1]);
+    assert!(api.splits_received.lock().unwrap().is_empty());
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 3);
+    assert!(calls[0].warnings[0].contains("Transaction 2 is already in batch earlier"));
+    assert!(calls[0].warnings[1].contains("Transaction 4 is already in the proxy category"));
+    assert!(calls[0].warnings[2].contains("Transaction 3 is already in batch earlier"));
+}
+
+#[tokio::test]
+async fn create_batch_leaves_out_retagged_creditor_half_of_batched_split() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+        // The creditor's half of an earlier split, re-tagged
+        test_transaction(300, 1000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_date(2025, 3, 2)
+            .with_parent(60),
+        // The debtor's half, billed in the earlier batch
+        test_transaction(301, 1000)
+            .with_date(2025, 3, 2)
+            .with_parent(60)
+            .with_category(99, "Proxy"),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "earlier".to_string(),
+        amount: Money::new_from_minor(1000, Currency::USD),
+        transaction_ids: vec![301],
+        split_ids: vec![300],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        reconciliation: None,
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].transaction_ids, vec![1]);
+
+    // The creditor's half is neither re-split nor billed again
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1]);
+    assert!(api.splits_received.lock().unwrap().is_empty());
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 1);
+    assert!(calls[0].warnings[0].contains("Transaction 300 is already in batch earlier"));
+}
+
+#[tokio::test]
+async fn create_batch_picks_up_untouched_sibling_of_batched_split_child() {
+    let config = test_config();
+    let txns = vec![
+        // Resplit and billed on an earlier run
+        test_transaction(401, 1000)
+            .with_date(2025, 3, 2)
+            .with_parent(70)
+            .with_category(99, "Proxy"),
+        // Another child of the same parent, tagged since
+        test_transaction(402, 2500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 2)
+            .with_parent(70),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "earlier".to_string(),
+        amount: Money::new_from_minor(1000, Currency::USD),
+        transaction_ids: vec![401],
+        split_ids: vec![400],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        reconciliation: None,
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].transaction_ids, vec![402]);
+    let calls = notifier.calls.lock().unwrap();
+    assert!(calls[0].warnings.is_empty());
+}
+
+#[tokio::test]
+async fn create_batch_quietly_leaves_out_batched_transactions_when_tags_are_kept() {
+    let mut config = test_config();
+    config.tags.remove_after_processing = false;
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+        // Batched on an earlier run, and still tagged
+        test_transaction(2, 2000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 2)
+            .with_category(99, "Proxy"),
+        // In the proxy category without being in any batch
+        test_transaction(3, 700)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 3)
+            .with_category(99, "Proxy"),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "earlier".to_string(),
+        amount: Money::new_from_minor(2000, Currency::USD),
+        transaction_ids: vec![2],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: None,
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].transaction_ids, vec![1]);
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 1);
+    assert!(calls[0].warnings[0].contains("Transaction 3 is already in the proxy category"));
+}
+
+#[tokio::test]
+async fn create_batch_quietly_skips_split_parents_when_tags_are_kept() {
+    let mut config = test_config();
+    config.tags.remove_after_processing = false;
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+        // Split on an earlier run, and still tagged
+        test_transaction(10, 2000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_date(2025, 3, 2)
+            .with_children(),
+        test_transaction(200, 1000).with_date(2025, 3, 2).with_parent(10),
+        test_transaction(201, 1000)
+            .with_date(2025, 3, 2)
+            .with_parent(10)
+            .with_category(99, "Proxy"),
+        // Split by hand, then tagged by mistake
+        test_transaction(20, 3000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_date(2025, 3, 3)
+            .with_children(),
+        test_transaction(300, 3000).with_date(2025, 3, 3).with_parent(20),
+    ];
+
+    let api = MockLunchMoney::new(txns);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "earlier".to_string(),
+        amount: Money::new_from_minor(1000, Currency::USD),
+        transaction_ids: vec![201],
+        split_ids: vec![200],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        reconciliation: None,
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].transaction_ids, vec![1]);
+    let calls = notifier.calls.lock().unwrap();
+    assert_eq!(calls[0].warnings.len(), 1);
+    assert!(calls[0].warnings[0].contains("tagged to split, but it already has children: 20"));
+}
+
+#[tokio::test]
+async fn create_batch_marks_batched_transactions_with_batch_id() {
+    let mut config = test_config();
+    config.batch_marker = Some(BatchMarker {
+        target: MarkerTarget::Notes,
+        template: "eq:{batch_id}".to_string(),
+    });
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1)
+            .with_notes("Dinner"),
+        test_transaction(10, 2000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_date(2025, 3, 2),
+        // The debtor's half, as Lunch Money returns it after the split
+        test_transaction(201, 1000)
+            .with_date(2025, 3, 2)
+            .with_parent(10),
+    ];
+
+    let api = MockLunchMoney::new(txns).with_split_ids(vec![vec![200, 201]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+    let report = create_batch(start, end, &config, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let batch_id = report.batch_id.expect("a batch should be saved");
+    assert_eq!(persistence.saved_batches()[0].transaction_ids, vec![1, 201]);
+    assert!(report.issues.is_empty());
+
+    let notes: Vec<(TransactionId, String)> = api
+        .updates_received
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, u)| u.notes.as_deref().is_some_and(|n| n.contains("eq:")))
+        .map(|(id, u)| (*id, u.notes.clone().unwrap()))
+        .collect();
+    assert_eq!(
+        notes,
+        vec![
+            (1, format!("Dinner eq:{}", batch_id)),
+            (201, format!("eq:{}", batch_id)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn create_batch_interactive_review_skips_and_changes_splits() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500).with_tags(vec![("eq-to-batch", 10)]),
+        test_transaction(2, 2000).with_tags(vec![("eq-to-batch", 10)]),
+        test_transaction(3, 1000).with_tags(vec![("eq-to-split", 11)]),
+    ];
+
+    let api = MockLunchMoney::new(txns).with_split_ids(vec![vec![200, 201], vec![300, 301]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+    let mut reviewer = ScriptedReviewer::new(vec![
+        (1, ReviewDecision::Skip),
+        (2, ReviewDecision::Split),
+        (3, ReviewDecision::Ratio(dec!(0.3))),
+    ]);
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+
+    let plan = plan_batch(start, end, &config, &api, &persistence, Some(&mut reviewer))
+        .await
+        .expect("plan_batch should succeed");
+    assert_eq!(
+        reviewer.asked,
+        vec![
+            (1, ProposedAction::Add),
+            (2, ProposedAction::Add),
+            (3, ProposedAction::Split),
+        ]
+    );
+
+    execute_plan(plan, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("execute_plan should succeed");
+
+    // The skipped transaction is left alone, tag and all
+    assert!(api.updates_received.lock().unwrap().is_empty());
+
+    let splits = api.update_and_splits_received.lock().unwrap();
+    assert_eq!(splits.len(), 2);
+    // Add tag stripped from the transaction split instead of added
+    assert_eq!(splits[0].0, 2);
+    assert_eq!(splits[0].1.tags, Some(vec![]));
+    assert_eq!(splits[0].2[1].amount, Money::new_from_minor(1000, Currency::USD));
+    assert_eq!(splits[1].0, 3);
+    assert_eq!(splits[1].2[1].amount, Money::new_from_minor(300, Currency::USD));
+
+    let batch = &persistence.saved_batches()[0];
+    assert_eq!(batch.amount, Money::new_from_minor(1300, Currency::USD));
+}
+
+#[tokio::test]
+async fn create_batch_all_or_nothing_rolls_back_after_a_failure() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_category(42, "Dining"),
+        test_transaction(2, 2500).with_tags(vec![("eq-to-batch", 10)]),
+        test_transaction(3, 2000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_category(42, "Dining"),
+    ];
+
+    let api = MockLunchMoney::new(txns)
+        .with_failing_updates(vec![2])
+        .with_split_ids(vec![vec![200, 201]]);
+    // 1 was still pending on the last run
+    let persistence = InMemoryPersistence::new().with_pending_ids(vec![1]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+
+    let mut plan = plan_batch(start, end, &config, &api, &persistence, None)
+        .await
+        .expect("plan_batch should succeed");
+    plan.all_or_nothing = true;
+    let report = execute_plan(plan, &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("execute_plan should succeed");
+
+    // Transactions 1 and 3 were changed, then put back; 2 never changed.
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.iter().map(|u| u.0).collect::<Vec<_>>(), vec![1, 2, 3, 1]);
+    assert_eq!(updates[3].1.category_id, Some(42));
+    assert_eq!(updates[3].1.tags, Some(vec!["eq-to-batch".to_string()]));
+
+    // No batch, no email, and the report says what failed.
+    assert!(persistence.saved_batches().is_empty());
+    assert_eq!(notifier.call_count(), 0);
+    assert!(persistence.saved_journal().is_none());
+    assert_eq!(report.batch_id, None);
+    assert_eq!(report.issues.len(), 1);
+    assert!(report.issues[0].contains("Error when updating transaction 2"));
+    let rollback = report.rollback.expect("run should have been rolled back");
+    assert_eq!(rollback.reverted, vec![3, 1]);
+    assert!(rollback.errors.is_empty());
+
+    // 1 is reverted, so it's still carried forward to the next run.
+    assert_eq!(persistence.saved_pending_ids(), vec![1]);
+}
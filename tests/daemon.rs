@@ -0,0 +1,248 @@
+mod support;
+
+use chrono::{NaiveDate, TimeZone};
+use chrono_tz::US::Eastern;
+use equailizer::commands::daemon::run_due_jobs;
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::money::{Currency, Money};
+use equailizer::persist::{Batch, DaemonState};
+use equailizer::plugin::PluginManager;
+use std::collections::BTreeMap;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(), // Sundays at 9, reconcile every 4 hours
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+#[tokio::test]
+async fn run_due_jobs_batches_since_previous_batch_and_records_state() {
+    let config = test_config();
+    let creditor_api = MockLunchMoney::new(vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 12),
+    ]);
+    let debtor_api = MockLunchMoney::new(vec![]);
+    let persistence = InMemoryPersistence::new().with_daemon_state(DaemonState {
+        last_batch_end: Some(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()),
+        last_batch_run: Some(Eastern.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap().to_utc()),
+        last_reconcile_run: None,
+    });
+    let notifier = RecordingBatchNotifier::new();
+
+    let now = Eastern.with_ymd_and_hms(2025, 3, 16, 9, 1, 0).unwrap();
+    run_due_jobs(
+        now,
+        now,
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("jobs should run");
+
+    assert_eq!(persistence.saved_batches().len(), 1);
+    assert_eq!(notifier.call_count(), 1);
+
+    let state = persistence.saved_daemon_state();
+    assert_eq!(state.last_batch_end, NaiveDate::from_ymd_opt(2025, 3, 16));
+    assert_eq!(state.last_batch_run, Some(now.to_utc()));
+    assert_eq!(state.last_reconcile_run, Some(now.to_utc()));
+}
+
+#[tokio::test]
+async fn run_due_jobs_picks_up_transactions_tagged_after_the_previous_batch() {
+    let config = test_config();
+    let creditor_api = MockLunchMoney::new(vec![
+        // Dated before the previous batch ended, but tagged since
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 8),
+        // Dated before the overlap
+        test_transaction(2, 2000)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 1),
+    ])
+    .with_date_filtering();
+    let debtor_api = MockLunchMoney::new(vec![]);
+    let persistence = InMemoryPersistence::new().with_daemon_state(DaemonState {
+        last_batch_end: Some(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()),
+        last_batch_run: Some(Eastern.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap().to_utc()),
+        last_reconcile_run: None,
+    });
+    let notifier = RecordingBatchNotifier::new();
+
+    let now = Eastern.with_ymd_and_hms(2025, 3, 16, 9, 1, 0).unwrap();
+    run_due_jobs(
+        now,
+        now,
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("jobs should run");
+
+    let batches = persistence.saved_batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_ids, vec![1]);
+}
+
+#[tokio::test]
+async fn run_due_jobs_does_nothing_between_scheduled_times() {
+    let config = test_config();
+    let creditor_api = MockLunchMoney::new(vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 12),
+    ]);
+    let debtor_api = MockLunchMoney::new(vec![]);
+    let state = DaemonState {
+        last_batch_end: Some(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()),
+        last_batch_run: Some(Eastern.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap().to_utc()),
+        last_reconcile_run: Some(Eastern.with_ymd_and_hms(2025, 3, 12, 8, 0, 0).unwrap().to_utc()),
+    };
+    let persistence = InMemoryPersistence::new().with_daemon_state(state.clone());
+    let notifier = RecordingBatchNotifier::new();
+
+    let now = Eastern.with_ymd_and_hms(2025, 3, 12, 10, 0, 0).unwrap();
+    run_due_jobs(
+        now,
+        now,
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("jobs should run");
+
+    assert!(persistence.saved_batches().is_empty());
+    assert_eq!(notifier.call_count(), 0);
+    assert_eq!(persistence.saved_daemon_state(), state);
+}
+
+#[tokio::test]
+async fn run_due_jobs_retries_range_of_rolled_back_batch() {
+    let mut config = test_config();
+    config.daemon.all_or_nothing = true;
+    let creditor_api = MockLunchMoney::new(vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_date(2025, 3, 12),
+    ])
+    .with_failing_updates(vec![1]);
+    let debtor_api = MockLunchMoney::new(vec![]);
+    let persistence = InMemoryPersistence::new().with_daemon_state(DaemonState {
+        last_batch_end: Some(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()),
+        last_batch_run: Some(Eastern.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap().to_utc()),
+        last_reconcile_run: None,
+    });
+    let notifier = RecordingBatchNotifier::new();
+
+    let now = Eastern.with_ymd_and_hms(2025, 3, 16, 9, 1, 0).unwrap();
+    run_due_jobs(
+        now,
+        now,
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("jobs should run");
+
+    assert!(persistence.saved_batches().is_empty());
+    let state = persistence.saved_daemon_state();
+    assert_eq!(state.last_batch_end, NaiveDate::from_ymd_opt(2025, 3, 9));
+    assert_eq!(state.last_batch_run, Some(now.to_utc()));
+}
+
+#[tokio::test]
+async fn run_due_jobs_sends_reminders_after_reconciling_when_enabled() {
+    let mut config = test_config();
+    config.daemon.remind = true;
+    let creditor_api = MockLunchMoney::new(vec![test_transaction(1, 1500).with_date(2025, 2, 20)]);
+    let debtor_api = MockLunchMoney::new(vec![]);
+    let state = DaemonState {
+        last_batch_run: Some(Eastern.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap().to_utc()),
+        ..DaemonState::default()
+    };
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "overdue".to_string(),
+        amount: Money::new_from_minor(1500, Currency::USD),
+        transaction_ids: vec![1],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: NaiveDate::from_ymd_opt(2025, 2, 21),
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: None,
+    }])
+    .with_daemon_state(state);
+    let notifier = RecordingBatchNotifier::new();
+
+    let now = Eastern.with_ymd_and_hms(2025, 3, 12, 10, 0, 0).unwrap();
+    run_due_jobs(
+        now,
+        now,
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &notifier,
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("jobs should run");
+
+    assert_eq!(notifier.reminders.lock().unwrap().len(), 1);
+    assert_eq!(
+        persistence.saved_batches()[0].reminders,
+        vec![NaiveDate::from_ymd_opt(2025, 3, 12).unwrap()]
+    );
+}
@@ -1,29 +1,35 @@
-use equailizer::email::{make_creditor_email_html_string, make_debtor_email_html_string, Txn};
-use equailizer::usd::USD;
+use equailizer::email::{
+    email_context, make_creditor_email_html_string, make_debtor_email_html_string,
+    make_reminder_email_html_string, make_settled_email_html_string, EmailContent, EmailTemplates, InstallmentInfo, Txn,
+};
+use equailizer::error::Error;
+use equailizer::payment::{PaymentMethod, PaymentRequest};
+use equailizer::money::{Currency, Money};
 
 #[test]
 fn creditor_email_html_contains_key_elements() {
     let txns = vec![
         Txn {
             payee: "Store A".to_string(),
-            amount: USD::new_from_cents(1500),
+            amount: Money::new_from_minor(1500, Currency::USD),
             date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
             notes: Some("groceries".to_string()),
+            installment: None,
         },
         Txn {
             payee: "Store B".to_string(),
-            amount: USD::new_from_cents(2500),
+            amount: Money::new_from_minor(2500, Currency::USD),
             date: chrono::NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
             notes: None,
+            installment: None,
         },
     ];
-    let venmo_link = "https://venmo.com/test?txn=charge&amount=40.00".to_string();
     let batch_id = "test-batch-123".to_string();
-    let total = USD::new_from_cents(4000);
+    let total = Money::new_from_minor(4000, Currency::USD);
 
     let html = make_creditor_email_html_string(
         &txns,
-        &venmo_link,
+        &venmo("test", &total),
         vec!["Warning: something happened".to_string()],
         &batch_id,
         &total,
@@ -45,15 +51,16 @@ fn creditor_email_html_contains_key_elements() {
 fn creditor_email_html_no_warnings() {
     let txns = vec![Txn {
         payee: "Store".to_string(),
-        amount: USD::new_from_cents(1000),
+        amount: Money::new_from_minor(1000, Currency::USD),
         date: chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
         notes: None,
+        installment: None,
     }];
-    let venmo_link = "https://venmo.com/test".to_string();
     let batch_id = "batch-1".to_string();
-    let total = USD::new_from_cents(1000);
+    let total = Money::new_from_minor(1000, Currency::USD);
 
-    let html = make_creditor_email_html_string(&txns, &venmo_link, vec![], &batch_id, &total);
+    let html =
+        make_creditor_email_html_string(&txns, &venmo("test", &total), vec![], &batch_id, &total);
 
     assert!(html.contains("Store"));
     assert!(html.contains("10.00"));
@@ -64,21 +71,23 @@ fn debtor_email_html_contains_key_elements() {
     let txns = vec![
         Txn {
             payee: "Store A".to_string(),
-            amount: USD::new_from_cents(1500),
+            amount: Money::new_from_minor(1500, Currency::USD),
             date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
             notes: Some("weekly groceries".to_string()),
+            installment: None,
         },
         Txn {
             payee: "Store B".to_string(),
-            amount: USD::new_from_cents(2500),
+            amount: Money::new_from_minor(2500, Currency::USD),
             date: chrono::NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
             notes: None,
+            installment: None,
         },
     ];
     let batch_id = "test-batch-456".to_string();
-    let total = USD::new_from_cents(4000);
+    let total = Money::new_from_minor(4000, Currency::USD);
 
-    let html = make_debtor_email_html_string(&txns, &batch_id, &total);
+    let html = make_debtor_email_html_string(&txns, &[], &batch_id, &total);
 
     assert!(html.contains("Store A"));
     assert!(html.contains("Store B"));
@@ -93,15 +102,198 @@ fn debtor_email_html_contains_key_elements() {
 fn debtor_email_html_single_transaction() {
     let txns = vec![Txn {
         payee: "Single Store".to_string(),
-        amount: USD::new_from_cents(999),
+        amount: Money::new_from_minor(999, Currency::USD),
         date: chrono::NaiveDate::from_ymd_opt(2025, 6, 15).unwrap(),
         notes: None,
+        installment: None,
     }];
     let batch_id = "single-batch".to_string();
-    let total = USD::new_from_cents(999);
+    let total = Money::new_from_minor(999, Currency::USD);
 
-    let html = make_debtor_email_html_string(&txns, &batch_id, &total);
+    let html = make_debtor_email_html_string(&txns, &[], &batch_id, &total);
 
     assert!(html.contains("Single Store"));
     assert!(html.contains("9.99"));
 }
+
+#[test]
+fn email_html_shows_installment_progress() {
+    use equailizer::email::InstallmentInfo;
+
+    let txns = vec![Txn {
+        payee: "Lemonade".to_string(),
+        amount: Money::new_from_minor(10000, Currency::USD),
+        date: chrono::NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+        notes: Some("annual premium".to_string()),
+        installment: Some(InstallmentInfo {
+            number: 3,
+            count: 12,
+        }),
+    }];
+    let total = Money::new_from_minor(10000, Currency::USD);
+
+    let creditor_html = make_creditor_email_html_string(
+        &txns,
+        &venmo("test", &total),
+        vec![],
+        &"batch-1".to_string(),
+        &total,
+    );
+    let debtor_html = make_debtor_email_html_string(&txns, &[], &"batch-1".to_string(), &total);
+
+    assert!(creditor_html.contains("installment 3 of 12"));
+    assert!(debtor_html.contains("installment 3 of 12"));
+}
+
+fn venmo(username: &str, total: &Money) -> Vec<PaymentRequest> {
+    let method = PaymentMethod::Venmo { username: username.to_string() };
+    vec![method.request("equailizer_abc", total)]
+}
+
+fn override_txns() -> Vec<Txn> {
+    vec![
+        Txn {
+            payee: "Couch & Co".to_string(),
+            amount: Money::new_from_minor(10000, Currency::USD),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            notes: None,
+            installment: Some(InstallmentInfo { number: 2, count: 12 }),
+        },
+        Txn {
+            payee: "Grocer".to_string(),
+            amount: Money::new_from_minor(1500, Currency::USD),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            notes: Some("dinner".to_string()),
+            installment: None,
+        },
+    ]
+}
+
+fn built_in() -> EmailContent {
+    EmailContent {
+        subject: "Built-in subject".to_string(),
+        text: "Built-in text".to_string(),
+        html: "<p>Built-in</p>".to_string(),
+    }
+}
+
+#[test]
+fn email_templates_override_only_the_given_parts() {
+    let templates = EmailTemplates::from_sources([
+        (
+            "creditor_subject.txt".to_string(),
+            "Batch {{ batch_id }}: {{ total }}\n".to_string(),
+        ),
+        (
+            "creditor.html".to_string(),
+            "{% for day in txns_by_date %}<h2>{{ day.date }}</h2>\
+             {% for txn in day.txns %}<p>{{ txn.payee }} {{ txn.amount }}\
+             {% if txn.installment %} ({{ txn.installment }}){% endif %}</p>{% endfor %}{% endfor %}\
+             {% for warning in warnings %}<i>{{ warning }}</i>{% endfor %}\
+             <a href=\"{{ venmo_request_link }}\">Request</a>"
+                .to_string(),
+        ),
+    ])
+    .expect("templates should parse");
+    let total = Money::new_from_minor(11500, Currency::USD);
+    let context = email_context(
+        "abc",
+        &total,
+        &override_txns(),
+        &["Careful".to_string()],
+        &venmo("x", &total),
+        None,
+    );
+
+    let content = templates.content("creditor", &context, built_in());
+
+    assert_eq!(content.subject, "Batch abc: $115.00");
+    assert_eq!(content.text, "Built-in text");
+    assert_eq!(
+        content.html,
+        "<h2>Mar 01, 2025</h2><p>Couch &amp; Co $100.00 (installment 2 of 12)</p><p>Grocer $15.00</p>\
+         <i>Careful</i><a href=\"https://venmo.com/x?txn=charge&amp;note=equailizer_abc&amp;amount=115.00\">Request</a>"
+    );
+    // Other emails are left alone.
+    assert_eq!(templates.content("debtor", &context, built_in()), built_in());
+}
+
+#[test]
+fn email_templates_report_the_file_that_does_not_parse() {
+    let result = EmailTemplates::from_sources([(
+        "debtor.txt".to_string(),
+        "{% for txn in txns %}{{ txn.payee }}".to_string(),
+    )]);
+
+    match result {
+        Err(Error::Template(message)) => assert!(message.starts_with("debtor.txt:")),
+        other => panic!("expected a template error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn reminder_email_html_contains_days_open_and_link() {
+    let total = Money::new_from_minor(11500, Currency::USD);
+    let html = make_reminder_email_html_string(
+        &override_txns(),
+        &venmo("test", &total),
+        &"batch-1".to_string(),
+        &total,
+        21,
+    );
+
+    assert!(html.contains("open for 21 days"));
+    assert!(html.contains("venmo.com/test"));
+    assert!(html.contains("Grocer"));
+    assert!(html.contains("installment 2 of 12"));
+}
+
+#[test]
+fn settled_email_html_contains_date_and_splits() {
+    let total = Money::new_from_minor(11500, Currency::USD);
+    let html = make_settled_email_html_string(
+        &override_txns(),
+        &"batch-1".to_string(),
+        &total,
+        chrono::NaiveDate::from_ymd_opt(2025, 3, 20).unwrap(),
+    );
+
+    assert!(html.contains("settled on Mar 20, 2025"));
+    assert!(html.contains("115.00"));
+    assert!(html.contains("Grocer"));
+    assert!(html.contains("batch-1"));
+}
+
+#[test]
+fn debtor_email_shows_how_to_pay_each_configured_method() {
+    let total = Money::new_from_minor(4000, Currency::USD);
+    let payments: Vec<PaymentRequest> = [
+        PaymentMethod::Venmo { username: "debtor".to_string() },
+        PaymentMethod::Paypal { username: "creditor".to_string() },
+        PaymentMethod::Zelle { recipient: "creditor@test.com".to_string() },
+    ]
+    .iter()
+    .map(|m| m.request("equailizer_abc", &total))
+    .collect();
+
+    let html = make_debtor_email_html_string(&override_txns(), &payments, &"abc".to_string(), &total);
+
+    assert!(html.contains("You'll see a Venmo request for it soon."));
+    assert!(!html.contains("venmo.com"));
+    assert!(html.contains("https://paypal.me/creditor/40.00USD"));
+    assert!(html.contains("Pay with PayPal"));
+    assert!(html.contains("by Zelle to creditor@test.com"));
+
+    let templates = EmailTemplates::from_sources([(
+        "debtor.txt".to_string(),
+        "{% for method in payment_methods %}{{ method.provider }}: {{ method.instructions }}\n{% endfor %}"
+            .to_string(),
+    )])
+    .unwrap();
+    let context = email_context("abc", &total, &override_txns(), &[], &payments[1..], None);
+    assert_eq!(
+        templates.content("debtor", &context, built_in()).text,
+        "PayPal: Send $40.00 to paypal.me/creditor with the note \"equailizer_abc\"\n\
+         Zelle: Send $40.00 by Zelle to creditor@test.com with the memo \"equailizer_abc\"\n"
+    );
+}
@@ -0,0 +1,107 @@
+mod support;
+
+use equailizer::commands::export::{export_batches, ExportFilter};
+use equailizer::money::{Currency, Money};
+use equailizer::persist::{Batch, BatchInstallment, Settlement};
+use std::collections::BTreeMap;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney};
+
+fn batches() -> Vec<Batch> {
+    vec![
+        Batch {
+            id: "reconciled".to_string(),
+            amount: Money::new_from_minor(2500, Currency::USD),
+            transaction_ids: vec![1, 2],
+            installments: vec![],
+            categories: BTreeMap::new(),
+            created_on: None,
+            reminders: vec![],
+            closed_by: None,
+            split_ids: vec![],
+            reconciliation: Some(Settlement {
+                settlement_credit_id: 500,
+                settlement_debit_id: 600,
+                settled_on: None,
+            }),
+        },
+        Batch {
+            id: "open".to_string(),
+            amount: Money::new_from_minor(10700, Currency::USD),
+            transaction_ids: vec![3],
+            installments: vec![BatchInstallment {
+                schedule_id: "schedule-1".to_string(),
+                transaction_id: 20,
+                number: 2,
+                count: 12,
+                due: chrono::NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+                amount: Money::new_from_minor(10000, Currency::USD),
+            }],
+            categories: BTreeMap::new(),
+            created_on: None,
+            reminders: vec![],
+            closed_by: None,
+            split_ids: vec![],
+            reconciliation: None,
+        },
+    ]
+}
+
+fn api() -> MockLunchMoney {
+    MockLunchMoney::new(vec![
+        test_transaction(1, 1500).with_date(2025, 3, 1).with_notes("Dinner"),
+        test_transaction(2, 1000).with_date(2025, 3, 10),
+        test_transaction(3, 700).with_date(2025, 4, 2),
+        test_transaction(20, 120000).with_date(2025, 3, 1).with_payee("Couch"),
+    ])
+}
+
+#[tokio::test]
+async fn export_lists_batched_transactions_and_installments_by_date() {
+    let persistence = InMemoryPersistence::with_batches(batches());
+
+    let rows = export_batches(&ExportFilter::default(), &api(), &persistence)
+        .await
+        .expect("export should succeed");
+
+    let summary: Vec<_> = rows
+        .iter()
+        .map(|r| (r.batch_id.as_str(), r.transaction_id, r.date.to_string(), r.amount))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("reconciled", 1, "2025-03-01".to_string(), Money::new_from_minor(1500, Currency::USD)),
+            ("reconciled", 2, "2025-03-10".to_string(), Money::new_from_minor(1000, Currency::USD)),
+            ("open", 20, "2025-04-01".to_string(), Money::new_from_minor(10000, Currency::USD)),
+            ("open", 3, "2025-04-02".to_string(), Money::new_from_minor(700, Currency::USD)),
+        ]
+    );
+    assert_eq!(rows[0].notes.as_deref(), Some("Dinner"));
+    assert_eq!(rows[0].settlement_credit_id, Some(500));
+    assert_eq!(rows[0].settlement_debit_id, Some(600));
+    assert_eq!(rows[2].payee, "Couch");
+    assert_eq!(rows[2].notes.as_deref(), Some("installment 2 of 12"));
+    assert_eq!(rows[3].settlement_credit_id, None);
+}
+
+#[tokio::test]
+async fn export_filters_by_date_and_reconciliation() {
+    let persistence = InMemoryPersistence::with_batches(batches());
+
+    let filter = ExportFilter {
+        start_date: chrono::NaiveDate::from_ymd_opt(2025, 3, 5),
+        end_date: chrono::NaiveDate::from_ymd_opt(2025, 4, 1),
+        reconciled: None,
+    };
+    let rows = export_batches(&filter, &api(), &persistence).await.unwrap();
+    assert_eq!(rows.iter().map(|r| r.transaction_id).collect::<Vec<_>>(), vec![2, 20]);
+
+    let filter = ExportFilter {
+        reconciled: Some(false),
+        ..ExportFilter::default()
+    };
+    let rows = export_batches(&filter, &api(), &persistence).await.unwrap();
+    assert!(rows.iter().all(|r| r.batch_id == "open"));
+    assert_eq!(rows.len(), 2);
+}
@@ -0,0 +1,74 @@
+use equailizer::config::{NotifierEntry, NotifierKind};
+use equailizer::email::{
+    BatchEmails, BatchNotifier, EmailNotifier, EmailTemplates, FileTransport, Party, Txn,
+};
+use equailizer::money::{Currency, Money};
+use equailizer::payment::PaymentMethod;
+
+#[test]
+fn notifier_entries_parse_with_defaults() {
+    let entries: Vec<NotifierEntry> = serde_json::from_str(
+        r#"[
+            {"type": "file", "dir": "outbox"},
+            {"type": "webhook", "name": "chat", "url": "https://example.com/hook",
+             "headers": {"Authorization": "Bearer abc"}, "recipients": ["creditor"], "enabled": false}
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(entries[0].name(), "file");
+    assert!(entries[0].enabled);
+    assert_eq!(entries[0].recipients, vec![Party::Creditor, Party::Debtor]);
+    assert!(matches!(&entries[0].kind, NotifierKind::File { dir: Some(dir) } if dir == "outbox"));
+
+    assert_eq!(entries[1].name(), "chat");
+    assert!(!entries[1].enabled);
+    assert_eq!(entries[1].recipients, vec![Party::Creditor]);
+    let NotifierKind::Webhook { url, headers } = &entries[1].kind else {
+        panic!("expected a webhook");
+    };
+    assert_eq!(url, "https://example.com/hook");
+    assert_eq!(headers["Authorization"].expose(), "Bearer abc");
+}
+
+#[tokio::test]
+async fn file_notifier_writes_emails_for_its_recipients_only() {
+    let dir = std::env::temp_dir().join(format!("eq-outbox-{}", uuid::Uuid::new_v4()));
+    let notifier = EmailNotifier {
+        emails: BatchEmails {
+            sending_address: "creditor@test.com".to_string(),
+            creditor_email: "creditor@test.com".to_string(),
+            debtor_email: "debtor@test.com".to_string(),
+            payment_methods: vec![PaymentMethod::Venmo {
+                username: "testdebtor".to_string(),
+            }],
+            templates: EmailTemplates::default(),
+        },
+        transport: FileTransport { dir: Some(dir.clone()) },
+        recipients: vec![Party::Debtor],
+        dry_run: false,
+    };
+    let txns = vec![Txn {
+        payee: "Grocer".to_string(),
+        amount: Money::new_from_minor(4000, Currency::USD),
+        date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+        notes: None,
+        installment: None,
+    }];
+
+    notifier
+        .send_batch_notification("batch-1", &Money::new_from_minor(4000, Currency::USD), &txns, vec![])
+        .await
+        .unwrap();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(files, vec!["equailizer.batch-1.debtor@test.com.eml"]);
+    let eml = std::fs::read_to_string(dir.join(&files[0])).unwrap();
+    assert!(eml.contains("To: debtor@test.com"));
+    assert!(eml.contains("Subject: Quail alert! Batch incoming from equailizer"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -4,37 +4,50 @@ use equailizer::commands::reconcile::{
     build_creditor_splits, build_debtor_splits, find_existing_split_children,
     find_settlement_transaction,
 };
-use equailizer::config::{Config, Creditor, Debtor, JMAP};
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
 use equailizer::lunch_money::api::update_transaction::TransactionUpdateItem;
-use equailizer::lunch_money::model::transaction::TransactionStatus;
+use equailizer::lunch_money::model::transaction::{TransactionId, TransactionStatus};
+use equailizer::marker::{BatchMarker, MarkerTarget};
+use equailizer::money::{Currency, Money};
 use equailizer::persist::{Batch, Settlement};
-use equailizer::usd::USD;
 use support::builders::{test_transaction, TransactionBuilder};
 use equailizer::plugin::PluginManager;
-use support::mocks::{InMemoryPersistence, MockLunchMoney};
+use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+use std::collections::BTreeMap;
 
 fn test_config() -> Config {
     Config {
         creditor: Creditor {
-            api_key: "test-creditor-key".to_string(),
+            api_key: "test-creditor-key".into(),
             proxy_category_id: 99,
             settlement_account_id: 1000,
             email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
         },
         debtor: Debtor {
-            api_key: "test-debtor-key".to_string(),
+            api_key: "test-debtor-key".into(),
             name: "TestDebtor".to_string(),
             settlement_account_id: 2000,
             email_address: "debtor@test.com".to_string(),
-            venmo_username: "testdebtor".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
         },
-        jmap: JMAP {
+        jmap: Some(JMAP {
             api_session_endpoint: "https://example.com".to_string(),
-            api_key: "test-jmap-key".to_string(),
+            api_key: "test-jmap-key".into(),
             sent_mailbox: "sent".to_string(),
             sending_address: "sender@test.com".to_string(),
-        },
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
         plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
     }
 }
 
@@ -48,7 +61,7 @@ fn find_settlement_exact_match() {
         test_transaction(3, -3000).with_account(9999), // wrong account
     ];
 
-    let result = find_settlement_transaction(&txns, USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&txns, Money::new_from_minor(-3000, Currency::USD), 1000);
     assert!(result.is_some());
     assert_eq!(result.unwrap().id, 2);
 }
@@ -57,7 +70,7 @@ fn find_settlement_exact_match() {
 fn find_settlement_no_match_wrong_amount() {
     let txns = vec![test_transaction(1, -5000).with_account(1000)];
 
-    let result = find_settlement_transaction(&txns, USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&txns, Money::new_from_minor(-3000, Currency::USD), 1000);
     assert!(result.is_none());
 }
 
@@ -65,7 +78,7 @@ fn find_settlement_no_match_wrong_amount() {
 fn find_settlement_no_match_wrong_account() {
     let txns = vec![test_transaction(1, -3000).with_account(9999)];
 
-    let result = find_settlement_transaction(&txns, USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&txns, Money::new_from_minor(-3000, Currency::USD), 1000);
     assert!(result.is_none());
 }
 
@@ -74,13 +87,13 @@ fn find_settlement_no_match_no_account() {
     // Transaction without plaid_account_id
     let txns = vec![test_transaction(1, -3000)];
 
-    let result = find_settlement_transaction(&txns, USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&txns, Money::new_from_minor(-3000, Currency::USD), 1000);
     assert!(result.is_none());
 }
 
 #[test]
 fn find_settlement_empty_candidates() {
-    let result = find_settlement_transaction(&[], USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&[], Money::new_from_minor(-3000, Currency::USD), 1000);
     assert!(result.is_none());
 }
 
@@ -91,7 +104,7 @@ fn find_settlement_returns_first_match() {
         test_transaction(2, -3000).with_account(1000), // also matches
     ];
 
-    let result = find_settlement_transaction(&txns, USD::new_from_cents(-3000), 1000);
+    let result = find_settlement_transaction(&txns, Money::new_from_minor(-3000, Currency::USD), 1000);
     assert_eq!(result.unwrap().id, 1);
 }
 
@@ -111,7 +124,7 @@ fn build_creditor_splits_correct_structure() {
     assert_eq!(splits.len(), 2);
 
     // Creditor splits have negative amounts (credits back)
-    assert_eq!(splits[0].amount, USD::new_from_cents(-1500));
+    assert_eq!(splits[0].amount, Money::new_from_minor(-1500, Currency::USD));
     assert_eq!(splits[0].payee, Some("Alice".to_string()));
     assert_eq!(splits[0].category_id, Some(99));
     assert_eq!(splits[0].notes, Some("Store A".to_string()));
@@ -120,7 +133,7 @@ fn build_creditor_splits_correct_structure() {
         Some(chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())
     );
 
-    assert_eq!(splits[1].amount, USD::new_from_cents(-2500));
+    assert_eq!(splits[1].amount, Money::new_from_minor(-2500, Currency::USD));
     assert_eq!(splits[1].payee, Some("Alice".to_string()));
     assert_eq!(splits[1].notes, Some("Store B".to_string()));
 }
@@ -137,21 +150,42 @@ fn build_debtor_splits_correct_structure() {
             .with_date(2025, 3, 2),
     ];
 
-    let splits = build_debtor_splits(&batch_txns);
+    let splits = build_debtor_splits(&batch_txns, &BTreeMap::new(), &CategoryMap::default());
 
     assert_eq!(splits.len(), 2);
 
     // Debtor splits have positive amounts and pass through payee/notes
-    assert_eq!(splits[0].amount, USD::new_from_cents(1500));
+    assert_eq!(splits[0].amount, Money::new_from_minor(1500, Currency::USD));
     assert_eq!(splits[0].payee, Some("Store A".to_string()));
     assert_eq!(splits[0].category_id, None);
     assert_eq!(splits[0].notes, Some("groceries".to_string()));
 
-    assert_eq!(splits[1].amount, USD::new_from_cents(2500));
+    assert_eq!(splits[1].amount, Money::new_from_minor(2500, Currency::USD));
     assert_eq!(splits[1].payee, Some("Store B".to_string()));
     assert_eq!(splits[1].notes, None);
 }
 
+#[test]
+fn build_debtor_splits_maps_categories() {
+    let batch_txns = vec![
+        test_transaction(1, 1500),
+        test_transaction(2, 2500),
+        test_transaction(3, 500),
+    ];
+    let creditor_categories = BTreeMap::from([(1, 41), (2, 42)]);
+    let category_map = CategoryMap {
+        categories: BTreeMap::from([(41, 7)]),
+        fallback: Some(8),
+    };
+
+    let splits = build_debtor_splits(&batch_txns, &creditor_categories, &category_map);
+
+    // Mapped, unmapped, and no creditor category on record
+    assert_eq!(splits[0].category_id, Some(7));
+    assert_eq!(splits[1].category_id, Some(8));
+    assert_eq!(splits[2].category_id, Some(8));
+}
+
 #[test]
 fn build_creditor_splits_empty() {
     let splits = build_creditor_splits(&[], "Alice", 99);
@@ -160,7 +194,7 @@ fn build_creditor_splits_empty() {
 
 #[test]
 fn build_debtor_splits_empty() {
-    let splits = build_debtor_splits(&[]);
+    let splits = build_debtor_splits(&[], &BTreeMap::new(), &CategoryMap::default());
     assert!(splits.is_empty());
 }
 
@@ -196,11 +230,18 @@ async fn reconcile_batch_end_to_end() {
 
     let batch = Batch {
         id: "test-batch-1".to_string(),
-        amount: USD::new_from_cents(4000),
+        amount: Money::new_from_minor(4000, Currency::USD),
         transaction_ids: vec![10, 11],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
+    let notifier = RecordingBatchNotifier::new();
 
     equailizer::commands::reconcile::reconcile_batch_name(
         "test-batch-1",
@@ -208,6 +249,7 @@ async fn reconcile_batch_end_to_end() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &notifier,
         &mut PluginManager::empty(),
     )
     .await
@@ -259,6 +301,15 @@ async fn reconcile_batch_end_to_end() {
     let settlement = reconciled.reconciliation.as_ref().unwrap();
     assert_eq!(settlement.settlement_credit_id, 50);
     assert_eq!(settlement.settlement_debit_id, 60);
+
+    // Both parties hear about it, with the debtor's splits itemised.
+    let settled = notifier.settled.lock().unwrap();
+    assert_eq!(settled.len(), 1);
+    assert_eq!(settled[0].batch_id, "test-batch-1");
+    assert_eq!(settled[0].total, Money::new_from_minor(4000, Currency::USD));
+    assert_eq!(settled[0].settled_on, chrono::NaiveDate::from_ymd_opt(2025, 3, 5).unwrap());
+    let payees: Vec<_> = settled[0].splits.iter().map(|t| t.payee.as_str()).collect();
+    assert_eq!(payees, vec!["Store A", "Store B"]);
 }
 
 #[tokio::test]
@@ -269,11 +320,18 @@ async fn reconcile_fails_when_already_reconciled() {
 
     let batch = Batch {
         id: "already-done".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: Some(Settlement {
             settlement_credit_id: 50,
             settlement_debit_id: 60,
+            settled_on: None,
         }),
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -284,6 +342,7 @@ async fn reconcile_fails_when_already_reconciled() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await;
@@ -310,8 +369,14 @@ async fn reconcile_fails_when_no_settlement_credit() {
 
     let batch = Batch {
         id: "batch-no-credit".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -322,6 +387,7 @@ async fn reconcile_fails_when_no_settlement_credit() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await;
@@ -350,17 +416,30 @@ async fn reconcile_all_processes_unreconciled_batches() {
 
     let unreconciled_batch = Batch {
         id: "unreconciled-1".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let already_reconciled = Batch {
         id: "already-done".to_string(),
-        amount: USD::new_from_cents(500),
+        amount: Money::new_from_minor(500, Currency::USD),
         transaction_ids: vec![99],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: Some(Settlement {
             settlement_credit_id: 200,
             settlement_debit_id: 201,
+            settled_on: None,
         }),
     };
     let persistence =
@@ -371,6 +450,7 @@ async fn reconcile_all_processes_unreconciled_batches() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -414,14 +494,26 @@ async fn reconcile_all_continues_after_batch_failure() {
 
     let failing_batch = Batch {
         id: "will-fail".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let succeeding_batch = Batch {
         id: "will-succeed".to_string(),
-        amount: USD::new_from_cents(2000),
+        amount: Money::new_from_minor(2000, Currency::USD),
         transaction_ids: vec![20],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence =
@@ -432,6 +524,7 @@ async fn reconcile_all_continues_after_batch_failure() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -449,6 +542,15 @@ async fn reconcile_all_continues_after_batch_failure() {
     // The failing batch should remain unreconciled
     let failed = saved.iter().find(|b| b.id == "will-fail").unwrap();
     assert!(failed.reconciliation.is_none());
+
+    // The report has the settlement of one and the error of the other
+    let report = result.report();
+    assert_eq!(report.reconciled.len(), 1);
+    assert_eq!(report.reconciled[0].batch_id, "will-succeed");
+    assert_eq!(report.reconciled[0].settlement_credit_id, 50);
+    assert_eq!(report.reconciled[0].settlement_debit_id, 60);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].batch_id, "will-fail");
 }
 
 #[tokio::test]
@@ -478,8 +580,14 @@ async fn reconcile_does_not_modify_batch_transaction_tags() {
 
     let batch = Batch {
         id: "pending-tag-test".to_string(),
-        amount: USD::new_from_cents(4000),
+        amount: Money::new_from_minor(4000, Currency::USD),
         transaction_ids: vec![10, 11],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -490,6 +598,7 @@ async fn reconcile_does_not_modify_batch_transaction_tags() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -629,8 +738,14 @@ async fn reconcile_skips_split_when_creditor_settlement_already_split() {
 
     let batch = Batch {
         id: "recovery-creditor".to_string(),
-        amount: USD::new_from_cents(4000),
+        amount: Money::new_from_minor(4000, Currency::USD),
         transaction_ids: vec![10, 11],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -641,6 +756,7 @@ async fn reconcile_skips_split_when_creditor_settlement_already_split() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -692,8 +808,14 @@ async fn reconcile_skips_split_when_debtor_settlement_already_split() {
 
     let batch = Batch {
         id: "recovery-debtor".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -704,6 +826,7 @@ async fn reconcile_skips_split_when_debtor_settlement_already_split() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -749,8 +872,14 @@ async fn reconcile_skips_clearing_already_cleared_settlement_parent() {
 
     let batch = Batch {
         id: "skip-already-cleared".to_string(),
-        amount: USD::new_from_cents(1500),
+        amount: Money::new_from_minor(1500, Currency::USD),
         transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -761,6 +890,7 @@ async fn reconcile_skips_clearing_already_cleared_settlement_parent() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -852,8 +982,14 @@ async fn reconcile_skips_already_cleared_split_children() {
 
     let batch = Batch {
         id: "partial-children".to_string(),
-        amount: USD::new_from_cents(5000),
+        amount: Money::new_from_minor(5000, Currency::USD),
         transaction_ids: vec![10, 11, 12, 13, 14],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -864,6 +1000,7 @@ async fn reconcile_skips_already_cleared_split_children() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -928,8 +1065,14 @@ async fn reconcile_full_recovery_after_partial_failure() {
 
     let batch = Batch {
         id: "real-recovery".to_string(),
-        amount: USD::new_from_cents(4000),
+        amount: Money::new_from_minor(4000, Currency::USD),
         transaction_ids: vec![10, 11],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -940,6 +1083,7 @@ async fn reconcile_full_recovery_after_partial_failure() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await
@@ -1016,8 +1160,14 @@ async fn reconcile_errors_on_child_count_mismatch() {
 
     let batch = Batch {
         id: "mismatch".to_string(),
-        amount: USD::new_from_cents(4000),
+        amount: Money::new_from_minor(4000, Currency::USD),
         transaction_ids: vec![10, 11],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
         reconciliation: None,
     };
     let persistence = InMemoryPersistence::with_batches(vec![batch]);
@@ -1028,6 +1178,7 @@ async fn reconcile_errors_on_child_count_mismatch() {
         &creditor_api,
         &debtor_api,
         &persistence,
+        &RecordingBatchNotifier::new(),
         &mut PluginManager::empty(),
     )
     .await;
@@ -1041,3 +1192,136 @@ async fn reconcile_errors_on_child_count_mismatch() {
     assert!(debtor_api.updates_received.lock().unwrap().is_empty());
 }
 
+
+#[tokio::test]
+async fn reconcile_batch_includes_installments() {
+    use equailizer::persist::BatchInstallment;
+
+    let config = test_config();
+
+    let batch_txn = test_transaction(10, 1500)
+        .with_payee("Store A")
+        .with_date(2025, 3, 1);
+    // Amortized over 12 months; only installment 3 is in this batch.
+    let amortized = test_transaction(20, 120000)
+        .with_payee("Lemonade")
+        .with_date(2025, 1, 5);
+
+    let settlement_credit = test_transaction(50, -11500)
+        .with_account(1000)
+        .with_date(2025, 3, 8)
+        .with_status(TransactionStatus::Uncleared);
+    let settlement_debit = test_transaction(60, 11500)
+        .with_account(2000)
+        .with_date(2025, 3, 8)
+        .with_status(TransactionStatus::Uncleared);
+
+    let creditor_api = MockLunchMoney::new(vec![batch_txn, amortized, settlement_credit]);
+    let debtor_api = MockLunchMoney::new(vec![settlement_debit]);
+
+    let batch = Batch {
+        id: "installment-batch".to_string(),
+        amount: Money::new_from_minor(11500, Currency::USD),
+        transaction_ids: vec![10],
+        installments: vec![BatchInstallment {
+            schedule_id: "schedule-1".to_string(),
+            transaction_id: 20,
+            number: 3,
+            count: 12,
+            due: chrono::NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+            amount: Money::new_from_minor(10000, Currency::USD),
+        }],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: None,
+    };
+    let persistence = InMemoryPersistence::with_batches(vec![batch]);
+
+    equailizer::commands::reconcile::reconcile_batch_name(
+        "installment-batch",
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &RecordingBatchNotifier::new(),
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("reconcile should succeed");
+
+    // The installment gets a settlement split for its amount, not the full total.
+    let creditor_splits = creditor_api.splits_received.lock().unwrap();
+    assert_eq!(creditor_splits[0].1.len(), 2);
+    assert_eq!(creditor_splits[0].1[1].amount, Money::new_from_minor(-10000, Currency::USD));
+    assert_eq!(creditor_splits[0].1[1].notes.as_deref(), Some("Lemonade"));
+
+    let debtor_splits = debtor_api.splits_received.lock().unwrap();
+    assert_eq!(debtor_splits[0].1[1].amount, Money::new_from_minor(10000, Currency::USD));
+
+    let saved = persistence.saved_batches();
+    assert!(saved[0].reconciliation.is_some());
+    assert_eq!(saved[0].installments.len(), 1);
+}
+
+#[tokio::test]
+async fn reconcile_marks_settlements_with_batch_id() {
+    let mut config = test_config();
+    config.batch_marker = Some(BatchMarker {
+        target: MarkerTarget::Tag,
+        template: "batch-{short_id}".to_string(),
+    });
+
+    let batch_txn = test_transaction(10, 1500).with_date(2025, 3, 1);
+    let settlement_credit = test_transaction(50, -1500)
+        .with_account(1000)
+        .with_date(2025, 3, 5)
+        .with_tags(vec![("venmo", 7)]);
+    let settlement_debit = test_transaction(60, 1500)
+        .with_account(2000)
+        .with_date(2025, 3, 5);
+
+    let creditor_api = MockLunchMoney::new(vec![batch_txn, settlement_credit]);
+    let debtor_api = MockLunchMoney::new(vec![settlement_debit]);
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        id: "0123456789abcdef".to_string(),
+        amount: Money::new_from_minor(1500, Currency::USD),
+        transaction_ids: vec![10],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on: None,
+        reminders: vec![],
+        closed_by: None,
+        split_ids: vec![],
+        reconciliation: None,
+    }]);
+
+    equailizer::commands::reconcile::reconcile_batch_name(
+        "0123456789abcdef",
+        &config,
+        &creditor_api,
+        &debtor_api,
+        &persistence,
+        &RecordingBatchNotifier::new(),
+        &mut PluginManager::empty(),
+    )
+    .await
+    .expect("reconcile should succeed");
+
+    let tagged = |updates: &[(TransactionId, TransactionUpdateItem)]| -> Vec<(TransactionId, Vec<String>)> {
+        updates
+            .iter()
+            .filter_map(|(id, u)| Some((*id, u.tags.clone()?)))
+            .collect()
+    };
+    assert_eq!(
+        tagged(&creditor_api.updates_received.lock().unwrap()),
+        vec![(50, vec!["venmo".to_string(), "batch-01234567".to_string()])]
+    );
+    assert_eq!(
+        tagged(&debtor_api.updates_received.lock().unwrap()),
+        vec![(60, vec!["batch-01234567".to_string()])]
+    );
+}
@@ -0,0 +1,160 @@
+mod support;
+
+use equailizer::category_map::CategoryMap;
+use equailizer::commands::remind::remind_overdue;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::money::{Currency, Money};
+use equailizer::persist::{Batch, BatchInstallment, Persistence, Settlement};
+use std::collections::BTreeMap;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+fn date(m: u32, d: u32) -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+}
+
+fn batch(
+    id: &str,
+    created_on: Option<chrono::NaiveDate>,
+    reminders: Vec<chrono::NaiveDate>,
+) -> Batch {
+    Batch {
+        id: id.to_string(),
+        amount: Money::new_from_minor(2500, Currency::USD),
+        transaction_ids: vec![1, 2],
+        installments: vec![],
+        categories: BTreeMap::new(),
+        created_on,
+        reminders,
+        reconciliation: None,
+        closed_by: None,
+        split_ids: vec![],
+    }
+}
+
+fn api() -> MockLunchMoney {
+    MockLunchMoney::new(vec![
+        test_transaction(1, 1500).with_date(2025, 3, 1),
+        test_transaction(2, 1000).with_date(2025, 3, 10),
+        test_transaction(20, 120000).with_date(2025, 1, 1).with_payee("Couch"),
+    ])
+}
+
+#[tokio::test]
+async fn remind_overdue_reminds_about_old_batches_and_records_it() {
+    let persistence = InMemoryPersistence::with_batches(vec![
+        batch("overdue", Some(date(3, 11)), vec![]),
+        batch("recent", Some(date(3, 20)), vec![]),
+        batch("reminded-lately", Some(date(3, 1)), vec![date(3, 22)]),
+        batch("reminded-long-ago", Some(date(2, 1)), vec![date(3, 1)]),
+        Batch {
+            reconciliation: Some(Settlement {
+                settlement_credit_id: 500,
+                settlement_debit_id: 600,
+                settled_on: None,
+            }),
+            ..batch("settled", Some(date(2, 1)), vec![])
+        },
+    ]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let reminded = remind_overdue(date(3, 25), &test_config(), &api(), &persistence, &notifier)
+        .await
+        .expect("remind should succeed");
+
+    assert_eq!(reminded, vec!["overdue", "reminded-long-ago"]);
+    let sent = notifier.reminders.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].batch_id, "overdue");
+    assert_eq!(sent[0].days_open, 14);
+    assert_eq!(sent[0].txn_count, 2);
+
+    let saved = persistence.saved_batches();
+    let reminders = |id: &str| saved.iter().find(|b| b.id == id).unwrap().reminders.clone();
+    assert_eq!(reminders("overdue"), vec![date(3, 25)]);
+    assert_eq!(reminders("reminded-long-ago"), vec![date(3, 1), date(3, 25)]);
+    assert_eq!(reminders("recent"), vec![]);
+    assert_eq!(reminders("reminded-lately"), vec![date(3, 22)]);
+}
+
+#[tokio::test]
+async fn remind_overdue_dates_older_batches_by_their_latest_transaction() {
+    let persistence = InMemoryPersistence::with_batches(vec![Batch {
+        installments: vec![BatchInstallment {
+            schedule_id: "schedule-1".to_string(),
+            transaction_id: 20,
+            number: 3,
+            count: 12,
+            due: date(3, 1),
+            amount: Money::new_from_minor(10000, Currency::USD),
+        }],
+        ..batch("legacy", None, vec![])
+    }]);
+    let notifier = RecordingBatchNotifier::new();
+
+    // The latest transaction is from Mar 10, so this is a day too early.
+    let reminded = remind_overdue(date(3, 23), &test_config(), &api(), &persistence, &notifier)
+        .await
+        .expect("remind should succeed");
+    assert!(reminded.is_empty());
+
+    let reminded = remind_overdue(date(3, 24), &test_config(), &api(), &persistence, &notifier)
+        .await
+        .expect("remind should succeed");
+    assert_eq!(reminded, vec!["legacy"]);
+    assert_eq!(notifier.reminders.lock().unwrap()[0].txn_count, 3);
+}
+
+#[tokio::test]
+async fn remind_overdue_skips_batches_whose_transactions_cant_be_fetched() {
+    let persistence = InMemoryPersistence::with_batches(vec![
+        Batch {
+            transaction_ids: vec![404],
+            ..batch("missing", Some(date(3, 1)), vec![])
+        },
+        batch("overdue", Some(date(3, 11)), vec![]),
+    ]);
+    let notifier = RecordingBatchNotifier::new();
+
+    let reminded = remind_overdue(date(3, 25), &test_config(), &api(), &persistence, &notifier)
+        .await
+        .expect("remind should succeed");
+
+    assert_eq!(reminded, vec!["overdue"]);
+    assert!(persistence.get_batch("missing").unwrap().reminders.is_empty());
+}
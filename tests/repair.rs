@@ -0,0 +1,57 @@
+mod support;
+
+use equailizer::commands::repair::repair_splits;
+use equailizer::persist::SplitSnapshot;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney};
+
+#[tokio::test]
+async fn repair_restores_saved_splits() {
+    // Parent 100 was left unsplit; parent 200 has been split again since.
+    let api = MockLunchMoney::new(vec![
+        test_transaction(100, 3000),
+        test_transaction(200, 3000).with_children(),
+    ])
+    .with_split_ids(vec![vec![500, 501]]);
+    let persistence = InMemoryPersistence::new().with_split_snapshots(vec![
+        SplitSnapshot {
+            parent_id: 100,
+            children: vec![
+                test_transaction(20, 2000)
+                    .with_parent(100)
+                    .with_category(42, "Dining")
+                    .with_tags(vec![("shared", 7)]),
+                test_transaction(21, 1000).with_parent(100),
+            ],
+        },
+        SplitSnapshot {
+            parent_id: 200,
+            children: vec![test_transaction(30, 3000).with_parent(200)],
+        },
+    ]);
+
+    let report = repair_splits(&api, &persistence)
+        .await
+        .expect("repair should succeed");
+
+    assert_eq!(report.repaired, vec![100]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].transaction_id, 200);
+
+    let splits = api.splits_received.lock().unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].0, 100);
+    assert_eq!(splits[0].1.len(), 2);
+    assert_eq!(splits[0].1[0].category_id, Some(42));
+
+    // Tags go back on the new child that replaced the tagged one.
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0, 500);
+    assert_eq!(updates[0].1.tags, Some(vec!["shared".to_string()]));
+
+    // Only the restored snapshot is forgotten.
+    let remaining = persistence.saved_split_snapshots();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].parent_id, 200);
+}
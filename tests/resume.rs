@@ -0,0 +1,254 @@
+mod support;
+
+use equailizer::commands::create_batch::journal::{Journal, Step, StepResult, StepState};
+use equailizer::commands::create_batch::{create_batch, plan_batch};
+use equailizer::commands::resume::{resume_batch, rollback_batch};
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::email::Txn;
+use equailizer::lunch_money::model::transaction::Transaction;
+use equailizer::money::{Currency, Money};
+use equailizer::plugin::PluginManager;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney, RecordingBatchNotifier};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+fn txns() -> Vec<Transaction> {
+    vec![
+        test_transaction(1, 1500)
+            .with_tags(vec![("eq-to-batch", 10)])
+            .with_category(42, "Dining"),
+        test_transaction(2, 2500).with_tags(vec![("eq-to-batch", 10)]),
+        test_transaction(3, 2000)
+            .with_tags(vec![("eq-to-split", 11)])
+            .with_category(42, "Dining"),
+    ]
+}
+
+/// A journal for the plan of `txns()`, with the given steps already recorded.
+async fn journal(steps: Vec<(Step, StepState)>) -> Journal {
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+    let plan = plan_batch(
+        start,
+        end,
+        &test_config(),
+        &MockLunchMoney::new(txns()),
+        &InMemoryPersistence::new(),
+        None,
+    )
+    .await
+    .expect("plan_batch should succeed");
+
+    let mut journal = Journal::new(plan);
+    for (step, state) in steps {
+        journal.record(step, state);
+    }
+    journal
+}
+
+fn added(id: u32, cents: i64) -> StepState {
+    StepState::Finished(Box::new(StepResult {
+        batched: vec![(
+            id,
+            Txn {
+                payee: format!("Payee_{}", id),
+                amount: Money::new_from_minor(cents, Currency::USD),
+                date: chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                notes: None,
+                installment: None,
+            },
+        )],
+        ..StepResult::default()
+    }))
+}
+
+#[tokio::test]
+async fn create_batch_journals_each_step_before_running_it() {
+    let api = MockLunchMoney::new(txns()).with_split_ids(vec![vec![200, 201]]);
+    let persistence = InMemoryPersistence::new();
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+    let report = create_batch(start, end, &test_config(), &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("create_batch should succeed");
+
+    let saves = persistence.journal_saves.lock().unwrap();
+    // Saved once before anything changed, then around each of the 3 steps.
+    assert_eq!(saves.len(), 7);
+    assert!(saves[0].steps.is_empty());
+    assert!(matches!(saves[1].state(Step::Add(1)), Some(StepState::Started)));
+    assert!(matches!(saves[2].state(Step::Add(1)), Some(StepState::Finished(_))));
+    assert!(matches!(saves[5].state(Step::Split(3)), Some(StepState::Started)));
+
+    // The batch is saved under the journal's id, and the journal removed.
+    assert_eq!(report.batch_id, Some(saves[0].batch_id.clone()));
+    assert_eq!(persistence.saved_batches()[0].id, saves[0].batch_id);
+    assert!(persistence.saved_journal().is_none());
+}
+
+#[tokio::test]
+async fn create_batch_refuses_while_a_run_is_unfinished() {
+    let api = MockLunchMoney::new(txns());
+    let persistence = InMemoryPersistence::new().with_journal(journal(vec![]).await);
+    let notifier = RecordingBatchNotifier::new();
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+    let err = create_batch(start, end, &test_config(), &api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("equailizer resume"));
+    assert!(api.updates_received.lock().unwrap().is_empty());
+    assert!(persistence.saved_batches().is_empty());
+}
+
+#[tokio::test]
+async fn resume_finishes_an_interrupted_run() {
+    let journal = journal(vec![
+        (Step::Add(1), added(1, 1500)),
+        (Step::Add(2), StepState::Started),
+    ])
+    .await;
+    let batch_id = journal.batch_id.clone();
+
+    let api = MockLunchMoney::new(txns()).with_split_ids(vec![vec![200, 201]]);
+    let persistence = InMemoryPersistence::new().with_journal(journal);
+    let notifier = RecordingBatchNotifier::new();
+
+    let report = resume_batch(&api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("resume should succeed")
+        .expect("there is a run to resume");
+
+    // Transaction 1 was already done; 2 is redone and 3 is split.
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0, 2);
+    assert_eq!(api.update_and_splits_received.lock().unwrap().len(), 1);
+
+    let batch = &persistence.saved_batches()[0];
+    assert_eq!(batch.id, batch_id);
+    assert_eq!(batch.transaction_ids, vec![1, 2, 201]);
+    assert_eq!(batch.amount, Money::new_from_minor(5000, Currency::USD));
+    assert_eq!(report.batch_id, Some(batch_id));
+    assert_eq!(notifier.call_count(), 1);
+    assert!(persistence.saved_journal().is_none());
+}
+
+#[tokio::test]
+async fn resume_finds_the_debtor_half_of_a_split_that_went_through() {
+    let journal = journal(vec![
+        (Step::Add(1), added(1, 1500)),
+        (Step::Add(2), added(2, 2500)),
+        (Step::Split(3), StepState::Started),
+    ])
+    .await;
+
+    // The split went through before the run stopped.
+    let api = MockLunchMoney::new(vec![
+        test_transaction(3, 2000).with_category(42, "Dining").with_children(),
+        test_transaction(300, 1000).with_parent(3).with_category(42, "Dining"),
+        test_transaction(301, 1000).with_parent(3).with_category(99, "Proxy"),
+    ]);
+    let persistence = InMemoryPersistence::new().with_journal(journal);
+    let notifier = RecordingBatchNotifier::new();
+
+    resume_batch(&api, &persistence, &notifier, &mut PluginManager::empty())
+        .await
+        .expect("resume should succeed");
+
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+    assert_eq!(persistence.saved_batches()[0].transaction_ids, vec![1, 2, 301]);
+}
+
+#[tokio::test]
+async fn rollback_restores_every_transaction_the_run_touched() {
+    let journal = journal(vec![
+        (Step::Add(1), added(1, 1500)),
+        (Step::Add(2), added(2, 2500)),
+        (Step::Split(3), StepState::Started),
+    ])
+    .await;
+    let batch_id = journal.batch_id.clone();
+
+    let api = MockLunchMoney::new(vec![
+        test_transaction(1, 1500).with_category(99, "Proxy"),
+        test_transaction(2, 2500).with_category(99, "Proxy"),
+        test_transaction(3, 2000).with_category(42, "Dining").with_children(),
+    ]);
+    let persistence = InMemoryPersistence::new().with_journal(journal);
+
+    let report = rollback_batch(&api, &persistence)
+        .await
+        .expect("rollback should succeed")
+        .expect("there is a run to roll back");
+
+    assert_eq!(report.batch_id, batch_id);
+    assert_eq!(report.reverted, vec![3, 2, 1]);
+    assert!(report.errors.is_empty());
+
+    assert_eq!(*api.unsplits_received.lock().unwrap(), vec![3]);
+    let updates = api.updates_received.lock().unwrap();
+    assert_eq!(updates.iter().map(|u| u.0).collect::<Vec<_>>(), vec![3, 2, 1]);
+    let restore_1 = &updates[2].1;
+    assert_eq!(restore_1.category_id, Some(42));
+    assert_eq!(restore_1.tags, Some(vec!["eq-to-batch".to_string()]));
+
+    assert!(persistence.saved_batches().is_empty());
+    assert!(persistence.saved_journal().is_none());
+}
+
+#[tokio::test]
+async fn rollback_keeps_the_journal_when_a_revert_fails() {
+    let journal = journal(vec![(Step::Add(1), added(1, 1500))]).await;
+
+    let api = MockLunchMoney::new(txns()).with_failing_updates(vec![1]);
+    let persistence = InMemoryPersistence::new().with_journal(journal);
+
+    let report = rollback_batch(&api, &persistence)
+        .await
+        .expect("rollback should succeed")
+        .expect("there is a run to roll back");
+
+    assert!(report.reverted.is_empty());
+    assert_eq!(report.errors[0].transaction_id, 1);
+    assert!(persistence.saved_journal().is_some());
+}
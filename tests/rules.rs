@@ -0,0 +1,94 @@
+mod support;
+
+use equailizer::commands::rules::test_rules;
+use equailizer::category_map::CategoryMap;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::money::Currency;
+use equailizer::rules::RuleAction;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::MockLunchMoney;
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: serde_json::from_str(
+            r#"[
+                {"name": "utilities", "payee": {"contains": "con ed"}, "action": "split"},
+                {"name": "big", "min_amount": "100.00", "action": "add"}
+            ]"#,
+        )
+        .unwrap(),
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_rules_lists_matches_per_rule_without_writing() {
+    let config = test_config();
+    let txns = vec![
+        test_transaction(1, 12500).with_payee("Con Edison"),
+        test_transaction(2, 5000).with_payee("Con Edison"),
+        test_transaction(3, 30000).with_payee("Furniture"),
+        test_transaction(4, 30000)
+            .with_payee("Already batched")
+            .with_category(99, "Proxy"),
+    ];
+    let api = MockLunchMoney::new(txns);
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+    let results = test_rules(start, end, &config, &api)
+        .await
+        .expect("test_rules should succeed");
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].rule_name, "utilities");
+    assert_eq!(results[0].action, RuleAction::Split);
+    let ids: Vec<u32> = results[0].matches.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    // Con Edison also matches here; each rule is shown on its own.
+    assert_eq!(results[1].rule_name, "big");
+    let ids: Vec<u32> = results[1].matches.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![1, 3]);
+
+    assert!(api.updates_received.lock().unwrap().is_empty());
+    assert!(api.update_and_splits_received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rules_rejects_start_after_end() {
+    let config = test_config();
+    let api = MockLunchMoney::new(vec![]);
+
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    assert!(test_rules(start, end, &config, &api).await.is_err());
+}
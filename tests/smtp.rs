@@ -0,0 +1,155 @@
+use equailizer::config::SmtpTls;
+use equailizer::email::{
+    BatchEmails, BatchNotifier, EmailNotifier, EmailTemplates, Party, SmtpBatchNotifier,
+    SmtpTransport, Txn,
+};
+use equailizer::money::{Currency, Money};
+use equailizer::payment::PaymentMethod;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// A message as the sink received it.
+#[derive(Debug, Clone, Default)]
+struct Received {
+    recipients: Vec<String>,
+    data: String,
+}
+
+/// Start a local SMTP server that accepts everything and keeps what it's sent.
+async fn smtp_sink() -> (u16, Arc<Mutex<Vec<Received>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = Arc::new(Mutex::new(vec![]));
+
+    let sink = received.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let sink = sink.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let mut message = Received::default();
+                write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_ascii_uppercase();
+                    let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                        b"250 sink\r\n"
+                    } else if command.starts_with("RCPT TO:") {
+                        message.recipients.push(line[8..].trim().to_string());
+                        b"250 OK\r\n"
+                    } else if command == "DATA" {
+                        write.write_all(b"354 go ahead\r\n").await.unwrap();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            message.data.push_str(&line);
+                            message.data.push('\n');
+                        }
+                        sink.lock().unwrap().push(std::mem::take(&mut message));
+                        b"250 OK\r\n"
+                    } else if command == "QUIT" {
+                        write.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    write.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (port, received)
+}
+
+fn notifier(port: u16, dry_run: bool) -> SmtpBatchNotifier {
+    EmailNotifier {
+        emails: BatchEmails {
+            sending_address: "sender@test.com".to_string(),
+            creditor_email: "creditor@test.com".to_string(),
+            debtor_email: "debtor@test.com".to_string(),
+            payment_methods: vec![PaymentMethod::Venmo {
+                username: "testdebtor".to_string(),
+            }],
+            templates: EmailTemplates::default(),
+        },
+        transport: SmtpTransport {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: SmtpTls::None,
+            username: None,
+            password: None,
+        },
+        recipients: vec![Party::Creditor, Party::Debtor],
+        dry_run,
+    }
+}
+
+fn txns() -> Vec<Txn> {
+    vec![Txn {
+        payee: "Grocer".to_string(),
+        amount: Money::new_from_minor(4000, Currency::USD),
+        date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+        notes: None,
+        installment: None,
+    }]
+}
+
+#[tokio::test]
+async fn smtp_sends_creditor_and_debtor_emails() {
+    let (port, received) = smtp_sink().await;
+    let total = Money::new_from_minor(4000, Currency::USD);
+
+    notifier(port, false)
+        .send_batch_notification("batch-1", &total, &txns(), vec![])
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].recipients, vec!["<creditor@test.com>"]);
+    assert!(received[0].data.contains("Subject: Quail alert! Batch ready from equailizer"));
+    assert!(received[0].data.contains("Message-ID: <equailizer.batch-1.creditor@test.com>"));
+    assert!(received[0].data.contains("From: sender@test.com"));
+    assert!(received[0].data.contains("multipart/alternative"));
+    assert_eq!(received[1].recipients, vec!["<debtor@test.com>"]);
+    assert!(received[1].data.contains("Subject: Quail alert! Batch incoming from equailizer"));
+}
+
+#[tokio::test]
+async fn smtp_reminder_copies_creditor_and_replies_to_debtor_email() {
+    let (port, received) = smtp_sink().await;
+    let total = Money::new_from_minor(4000, Currency::USD);
+
+    notifier(port, false)
+        .send_reminder("batch-1", &total, &txns(), 20)
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(
+        received[0].recipients,
+        vec!["<debtor@test.com>", "<creditor@test.com>"]
+    );
+    assert!(received[0].data.contains("Cc: creditor@test.com"));
+    assert!(received[0].data.contains("Message-ID: <equailizer.batch-1.reminder-20@test.com>"));
+    assert!(received[0].data.contains("In-Reply-To: <equailizer.batch-1.debtor@test.com>"));
+    assert!(received[0].data.contains("References: <equailizer.batch-1.debtor@test.com>"));
+}
+
+#[tokio::test]
+async fn smtp_dry_run_sends_nothing() {
+    let (port, received) = smtp_sink().await;
+    let total = Money::new_from_minor(4000, Currency::USD);
+
+    notifier(port, true)
+        .send_batch_notification("batch-1", &total, &txns(), vec![])
+        .await
+        .unwrap();
+
+    assert!(received.lock().unwrap().is_empty());
+}
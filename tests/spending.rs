@@ -0,0 +1,187 @@
+mod support;
+
+use equailizer::category_map::CategoryMap;
+use equailizer::commands::spending::spending_report;
+use equailizer::config::{Config, Creditor, Daemon, Debtor, Reminders, Tags, JMAP};
+use equailizer::lunch_money::model::category::Category;
+use equailizer::money::{Currency, Money};
+use equailizer::persist::{Adjustment, AdjustmentKind, Batch, Settlement};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use support::builders::{test_transaction, TransactionBuilder};
+use support::mocks::{InMemoryPersistence, MockLunchMoney};
+
+fn test_config() -> Config {
+    Config {
+        creditor: Creditor {
+            api_key: "test-creditor-key".into(),
+            proxy_category_id: 99,
+            settlement_account_id: 1000,
+            email_address: "creditor@test.com".to_string(),
+            currency: Currency::USD,
+        },
+        debtor: Debtor {
+            api_key: "test-debtor-key".into(),
+            name: "TestDebtor".to_string(),
+            settlement_account_id: 2000,
+            email_address: "debtor@test.com".to_string(),
+            venmo_username: Some("testdebtor".to_string()),
+            payment_methods: vec![],
+        },
+        jmap: Some(JMAP {
+            api_session_endpoint: "https://example.com".to_string(),
+            api_key: "test-jmap-key".into(),
+            sent_mailbox: "sent".to_string(),
+            sending_address: "sender@test.com".to_string(),
+        }),
+        smtp: None,
+        notifiers: vec![],
+        tags: Tags::default(),
+        rules: vec![],
+        plugins: vec![],
+        daemon: Daemon::default(),
+        batch_marker: None,
+        category_map: CategoryMap::default(),
+        reminders: Reminders::default(),
+    }
+}
+
+fn date(m: u32, d: u32) -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+}
+
+fn batches() -> Vec<Batch> {
+    vec![
+        Batch {
+            id: "march".to_string(),
+            amount: Money::new_from_minor(2500, Currency::USD),
+            transaction_ids: vec![1, 2],
+            installments: vec![],
+            categories: BTreeMap::from([(1, 7), (2, 8)]),
+            created_on: Some(date(3, 11)),
+            reminders: vec![],
+            closed_by: None,
+            split_ids: vec![],
+            reconciliation: Some(Settlement {
+                settlement_credit_id: 500,
+                settlement_debit_id: 600,
+                settled_on: Some(date(3, 15)),
+            }),
+        },
+        Batch {
+            id: "april".to_string(),
+            amount: Money::new_from_minor(1200, Currency::USD),
+            transaction_ids: vec![3, 4],
+            installments: vec![],
+            categories: BTreeMap::from([(3, 7)]),
+            created_on: None,
+            reminders: vec![],
+            closed_by: None,
+            split_ids: vec![],
+            reconciliation: None,
+        },
+    ]
+}
+
+fn api() -> MockLunchMoney {
+    MockLunchMoney::new(vec![
+        test_transaction(1, 1500).with_date(2025, 3, 1).with_payee("Grocer"),
+        test_transaction(2, 1000).with_date(2025, 3, 10).with_payee("Diner"),
+        test_transaction(3, 700).with_date(2025, 4, 2).with_payee("Grocer"),
+        test_transaction(4, 500).with_date(2025, 4, 3).with_payee("Diner"),
+    ])
+    .with_categories(vec![
+        Category {
+            id: 7,
+            name: "Groceries".to_string(),
+            is_group: false,
+            archived: false,
+        },
+        Category {
+            id: 8,
+            name: "Dining Out".to_string(),
+            is_group: false,
+            archived: false,
+        },
+    ])
+}
+
+fn usd(cents: i64) -> Money {
+    Money::new_from_minor(cents, Currency::USD)
+}
+
+fn summary(totals: &[equailizer::report::SpendingTotal]) -> Vec<(&str, usize, Money)> {
+    totals
+        .iter()
+        .map(|t| (t.name.as_str(), t.count, t.total))
+        .collect()
+}
+
+#[tokio::test]
+async fn spending_report_totals_by_month_category_and_payee() {
+    let persistence = InMemoryPersistence::with_batches(batches());
+
+    let report = spending_report(None, None, &test_config(), &api(), &persistence)
+        .await
+        .expect("report should succeed");
+
+    assert_eq!(report.total, usd(3700));
+    assert_eq!(report.transaction_count, 4);
+    assert_eq!(
+        summary(&report.by_month),
+        vec![("2025-03", 2, usd(2500)), ("2025-04", 2, usd(1200))]
+    );
+    assert_eq!(
+        summary(&report.by_category),
+        vec![
+            ("Groceries", 2, usd(2200)),
+            ("Dining Out", 1, usd(1000)),
+            ("Uncategorized", 1, usd(500)),
+        ]
+    );
+    assert_eq!(
+        summary(&report.by_payee),
+        vec![("Grocer", 2, usd(2200)), ("Diner", 2, usd(1500))]
+    );
+
+    let times = &report.reconcile_times;
+    assert_eq!((times.reconciled, times.unreconciled), (1, 1));
+    assert_eq!(times.min_days, Some(4));
+    assert_eq!(times.median_days, Some(Decimal::from(4)));
+}
+
+#[tokio::test]
+async fn spending_report_only_counts_the_date_range() {
+    let persistence = InMemoryPersistence::with_batches(batches());
+
+    let report = spending_report(Some(date(4, 1)), None, &test_config(), &api(), &persistence)
+        .await
+        .expect("report should succeed");
+
+    assert_eq!(report.total, usd(1200));
+    assert_eq!(summary(&report.by_month), vec![("2025-04", 2, usd(1200))]);
+    assert_eq!((report.reconcile_times.reconciled, report.reconcile_times.unreconciled), (0, 1));
+    assert_eq!(report.reconcile_times.median_days, None);
+}
+
+#[tokio::test]
+async fn spending_report_counts_batches_closed_by_an_adjustment_as_settled() {
+    let mut batches = batches();
+    batches[1].closed_by = Some("cash".to_string());
+    let persistence = InMemoryPersistence::with_batches(batches).with_adjustments(vec![Adjustment {
+        id: "cash".to_string(),
+        date: date(4, 10),
+        kind: AdjustmentKind::Payment,
+        amount: usd(1200),
+        note: None,
+        batch_id: Some("april".to_string()),
+    }]);
+
+    let report = spending_report(None, None, &test_config(), &api(), &persistence)
+        .await
+        .expect("report should succeed");
+
+    let times = &report.reconcile_times;
+    assert_eq!((times.reconciled, times.unreconciled), (2, 0));
+    assert_eq!((times.min_days, times.max_days), (Some(4), Some(7)));
+}
@@ -1,5 +1,5 @@
 use chrono::NaiveDate;
-use equailizer::usd::USD;
+use equailizer::money::{Currency, Money};
 
 use equailizer::lunch_money::model::transaction::{
     Tag, Transaction, TransactionId, TransactionStatus,
@@ -12,7 +12,8 @@ pub fn test_transaction(id: TransactionId, amount_cents: i64) -> Transaction {
         id,
         date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
         payee: format!("Payee_{}", id),
-        amount: USD::new_from_cents(amount_cents),
+        amount: Money::new_from_minor(amount_cents, Currency::USD),
+        to_base: None,
         plaid_account_id: None,
         category_id: None,
         category_name: None,
@@ -35,6 +36,7 @@ pub trait TransactionBuilder {
     fn with_status(self, status: TransactionStatus) -> Self;
     fn with_parent(self, parent_id: u32) -> Self;
     fn with_children(self) -> Self;
+    fn with_currency(self, code: &str) -> Self;
     fn pending(self) -> Self;
 }
 
@@ -91,6 +93,11 @@ impl TransactionBuilder for Transaction {
         self
     }
 
+    fn with_currency(mut self, code: &str) -> Self {
+        self.amount = Money::new(self.amount.value(), code.parse::<Currency>().unwrap());
+        self
+    }
+
     fn pending(mut self) -> Self {
         self.is_pending = true;
         self
@@ -7,10 +7,14 @@ use equailizer::lunch_money::api::update_transaction::{
     SplitResponse, SplitUpdate, TransactionAndSplitUpdate, TransactionUpdate,
 };
 use equailizer::lunch_money::api::LunchMoney;
+use equailizer::lunch_money::model::category::Category;
 use equailizer::lunch_money::model::transaction::{Transaction, TransactionId};
-use equailizer::persist::{Batch, Persistence};
-use equailizer::usd::USD;
+use equailizer::amortization::AmortizationSchedule;
+use equailizer::commands::create_batch::journal::Journal;
+use equailizer::persist::{Adjustment, Batch, DaemonState, Persistence, SplitSnapshot};
+use equailizer::money::Money;
 use equailizer::email::{BatchNotifier, Txn};
+use equailizer::review::{ProposedAction, ReviewDecision, Reviewer};
 
 // ── MockLunchMoney ──────────────────────────────────────────────────────
 
@@ -20,6 +24,7 @@ use equailizer::email::{BatchNotifier, Txn};
 /// All calls are recorded for assertion.
 pub struct MockLunchMoney {
     pub transactions: Vec<Transaction>,
+    pub categories: Vec<Category>,
     pub next_split_ids: Mutex<Vec<Vec<TransactionId>>>,
     pub fail_update_for_ids: Mutex<Vec<TransactionId>>,
     pub fail_split_for_parent_ids: Mutex<Vec<TransactionId>>,
@@ -29,12 +34,14 @@ pub struct MockLunchMoney {
     pub splits_received: Mutex<Vec<SplitUpdate>>,
     pub update_and_splits_received: Mutex<Vec<TransactionAndSplitUpdate>>,
     pub unsplits_received: Mutex<Vec<TransactionId>>,
+    pub filter_by_date: bool,
 }
 
 impl MockLunchMoney {
     pub fn new(transactions: Vec<Transaction>) -> Self {
         Self {
             transactions,
+            categories: vec![],
             next_split_ids: Mutex::new(vec![]),
             fail_update_for_ids: Mutex::new(vec![]),
             fail_split_for_parent_ids: Mutex::new(vec![]),
@@ -44,9 +51,16 @@ impl MockLunchMoney {
             splits_received: Mutex::new(vec![]),
             update_and_splits_received: Mutex::new(vec![]),
             unsplits_received: Mutex::new(vec![]),
+            filter_by_date: false,
         }
     }
 
+    /// Make `get_transactions` only return transactions within the date range.
+    pub fn with_date_filtering(mut self) -> Self {
+        self.filter_by_date = true;
+        self
+    }
+
     /// Set the split IDs that will be returned by successive split/update_and_split calls.
     pub fn with_split_ids(self, split_ids: Vec<Vec<TransactionId>>) -> Self {
         *self.next_split_ids.lock().unwrap() = split_ids;
@@ -76,6 +90,12 @@ impl MockLunchMoney {
         *self.fail_unsplit_for_parent_ids.lock().unwrap() = parent_ids;
         self
     }
+
+    /// Set the categories returned by `get_categories`.
+    pub fn with_categories(mut self, categories: Vec<Category>) -> Self {
+        self.categories = categories;
+        self
+    }
 }
 
 #[async_trait]
@@ -90,10 +110,15 @@ impl LunchMoney for MockLunchMoney {
 
     async fn get_transactions(
         &self,
-        _start: NaiveDate,
-        _end: NaiveDate,
+        start: NaiveDate,
+        end: NaiveDate,
     ) -> Result<Vec<Transaction>> {
-        Ok(self.transactions.clone())
+        Ok(self
+            .transactions
+            .iter()
+            .filter(|t| !self.filter_by_date || (start <= t.date && t.date <= end))
+            .cloned()
+            .collect())
     }
 
     async fn get_transactions_by_id(&self, ids: &[TransactionId]) -> Result<Vec<Transaction>> {
@@ -171,6 +196,10 @@ impl LunchMoney for MockLunchMoney {
         }
         Ok(vec![])
     }
+
+    async fn get_categories(&self) -> Result<Vec<Category>> {
+        Ok(self.categories.clone())
+    }
 }
 
 // ── InMemoryPersistence ─────────────────────────────────────────────────
@@ -178,24 +207,96 @@ impl LunchMoney for MockLunchMoney {
 /// An in-memory persistence implementation for testing.
 pub struct InMemoryPersistence {
     batches: Mutex<Vec<Batch>>,
+    schedules: Mutex<Vec<AmortizationSchedule>>,
+    daemon_state: Mutex<DaemonState>,
+    pending_ids: Mutex<Vec<TransactionId>>,
+    journal: Mutex<Option<Journal>>,
+    /// Every journal saved, in order, to check what was written ahead.
+    pub journal_saves: Mutex<Vec<Journal>>,
+    snapshots: Mutex<Vec<SplitSnapshot>>,
+    adjustments: Mutex<Vec<Adjustment>>,
 }
 
 impl InMemoryPersistence {
     pub fn new() -> Self {
         Self {
             batches: Mutex::new(vec![]),
+            schedules: Mutex::new(vec![]),
+            daemon_state: Mutex::new(DaemonState::default()),
+            pending_ids: Mutex::new(vec![]),
+            journal: Mutex::new(None),
+            journal_saves: Mutex::new(vec![]),
+            snapshots: Mutex::new(vec![]),
+            adjustments: Mutex::new(vec![]),
         }
     }
 
     pub fn with_batches(batches: Vec<Batch>) -> Self {
         Self {
             batches: Mutex::new(batches),
+            schedules: Mutex::new(vec![]),
+            daemon_state: Mutex::new(DaemonState::default()),
+            pending_ids: Mutex::new(vec![]),
+            journal: Mutex::new(None),
+            journal_saves: Mutex::new(vec![]),
+            snapshots: Mutex::new(vec![]),
+            adjustments: Mutex::new(vec![]),
         }
     }
 
+    pub fn with_schedules(self, schedules: Vec<AmortizationSchedule>) -> Self {
+        *self.schedules.lock().unwrap() = schedules;
+        self
+    }
+
     pub fn saved_batches(&self) -> Vec<Batch> {
         self.batches.lock().unwrap().clone()
     }
+
+    pub fn with_daemon_state(self, state: DaemonState) -> Self {
+        *self.daemon_state.lock().unwrap() = state;
+        self
+    }
+
+    pub fn saved_schedules(&self) -> Vec<AmortizationSchedule> {
+        self.schedules.lock().unwrap().clone()
+    }
+
+    pub fn saved_daemon_state(&self) -> DaemonState {
+        self.daemon_state.lock().unwrap().clone()
+    }
+
+    pub fn with_pending_ids(self, ids: Vec<TransactionId>) -> Self {
+        *self.pending_ids.lock().unwrap() = ids;
+        self
+    }
+
+    pub fn saved_pending_ids(&self) -> Vec<TransactionId> {
+        self.pending_ids.lock().unwrap().clone()
+    }
+
+    pub fn with_journal(self, journal: Journal) -> Self {
+        *self.journal.lock().unwrap() = Some(journal);
+        self
+    }
+
+    pub fn saved_journal(&self) -> Option<Journal> {
+        self.journal.lock().unwrap().clone()
+    }
+
+    pub fn with_split_snapshots(self, snapshots: Vec<SplitSnapshot>) -> Self {
+        *self.snapshots.lock().unwrap() = snapshots;
+        self
+    }
+
+    pub fn saved_split_snapshots(&self) -> Vec<SplitSnapshot> {
+        self.snapshots.lock().unwrap().clone()
+    }
+
+    pub fn with_adjustments(self, adjustments: Vec<Adjustment>) -> Self {
+        *self.adjustments.lock().unwrap() = adjustments;
+        self
+    }
 }
 
 impl Persistence for InMemoryPersistence {
@@ -203,29 +304,9 @@ impl Persistence for InMemoryPersistence {
         let mut batches = self.batches.lock().unwrap();
         // Replace if exists, otherwise insert
         if let Some(pos) = batches.iter().position(|b| b.id == batch.id) {
-            batches[pos] = Batch {
-                id: batch.id.clone(),
-                amount: batch.amount,
-                transaction_ids: batch.transaction_ids.clone(),
-                reconciliation: batch.reconciliation.as_ref().map(|r| {
-                    equailizer::persist::Settlement {
-                        settlement_credit_id: r.settlement_credit_id,
-                        settlement_debit_id: r.settlement_debit_id,
-                    }
-                }),
-            };
+            batches[pos] = batch.clone();
         } else {
-            batches.push(Batch {
-                id: batch.id.clone(),
-                amount: batch.amount,
-                transaction_ids: batch.transaction_ids.clone(),
-                reconciliation: batch.reconciliation.as_ref().map(|r| {
-                    equailizer::persist::Settlement {
-                        settlement_credit_id: r.settlement_credit_id,
-                        settlement_debit_id: r.settlement_debit_id,
-                    }
-                }),
-            });
+            batches.push(batch.clone());
         }
         Ok(())
     }
@@ -235,17 +316,7 @@ impl Persistence for InMemoryPersistence {
         batches
             .iter()
             .find(|b| b.id == batch_name)
-            .map(|b| Batch {
-                id: b.id.clone(),
-                amount: b.amount,
-                transaction_ids: b.transaction_ids.clone(),
-                reconciliation: b.reconciliation.as_ref().map(|r| {
-                    equailizer::persist::Settlement {
-                        settlement_credit_id: r.settlement_credit_id,
-                        settlement_debit_id: r.settlement_debit_id,
-                    }
-                }),
-            })
+            .cloned()
             .ok_or_else(|| {
                 Error::Io(std::io::Error::new(
                     std::io::ErrorKind::NotFound,
@@ -255,30 +326,91 @@ impl Persistence for InMemoryPersistence {
     }
 
     fn all_batches(&self) -> Result<Vec<Batch>> {
-        let batches = self.batches.lock().unwrap();
-        Ok(batches
-            .iter()
-            .map(|b| Batch {
-                id: b.id.clone(),
-                amount: b.amount,
-                transaction_ids: b.transaction_ids.clone(),
-                reconciliation: b.reconciliation.as_ref().map(|r| {
-                    equailizer::persist::Settlement {
-                        settlement_credit_id: r.settlement_credit_id,
-                        settlement_debit_id: r.settlement_debit_id,
-                    }
-                }),
-            })
-            .collect())
+        Ok(self.batches.lock().unwrap().clone())
     }
 
     fn unreconciled_batches(&self) -> Result<Vec<Batch>> {
         Ok(self
             .all_batches()?
             .into_iter()
-            .filter(|b| b.reconciliation.is_none())
+            .filter(|b| b.is_open())
             .collect())
     }
+
+    fn save_schedule(&self, schedule: &AmortizationSchedule) -> Result<()> {
+        let mut schedules = self.schedules.lock().unwrap();
+        if let Some(pos) = schedules.iter().position(|s| s.id == schedule.id) {
+            schedules[pos] = schedule.clone();
+        } else {
+            schedules.push(schedule.clone());
+        }
+        Ok(())
+    }
+
+    fn all_schedules(&self) -> Result<Vec<AmortizationSchedule>> {
+        Ok(self.schedules.lock().unwrap().clone())
+    }
+
+    fn daemon_state(&self) -> Result<DaemonState> {
+        Ok(self.daemon_state.lock().unwrap().clone())
+    }
+
+    fn save_daemon_state(&self, state: &DaemonState) -> Result<()> {
+        *self.daemon_state.lock().unwrap() = state.clone();
+        Ok(())
+    }
+
+    fn pending_transaction_ids(&self) -> Result<Vec<TransactionId>> {
+        Ok(self.pending_ids.lock().unwrap().clone())
+    }
+
+    fn save_pending_transaction_ids(&self, ids: &[TransactionId]) -> Result<()> {
+        *self.pending_ids.lock().unwrap() = ids.to_vec();
+        Ok(())
+    }
+
+    fn save_journal(&self, journal: &Journal) -> Result<()> {
+        *self.journal.lock().unwrap() = Some(journal.clone());
+        self.journal_saves.lock().unwrap().push(journal.clone());
+        Ok(())
+    }
+
+    fn unfinished_journal(&self) -> Result<Option<Journal>> {
+        Ok(self.journal.lock().unwrap().clone())
+    }
+
+    fn remove_journal(&self, batch_id: &str) -> Result<()> {
+        let mut journal = self.journal.lock().unwrap();
+        if journal.as_ref().is_some_and(|j| j.batch_id == batch_id) {
+            *journal = None;
+        }
+        Ok(())
+    }
+
+    fn save_split_snapshot(&self, snapshot: &SplitSnapshot) -> Result<()> {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        snapshots.retain(|s| s.parent_id != snapshot.parent_id);
+        snapshots.push(snapshot.clone());
+        Ok(())
+    }
+
+    fn split_snapshots(&self) -> Result<Vec<SplitSnapshot>> {
+        Ok(self.snapshots.lock().unwrap().clone())
+    }
+
+    fn remove_split_snapshot(&self, parent_id: TransactionId) -> Result<()> {
+        self.snapshots.lock().unwrap().retain(|s| s.parent_id != parent_id);
+        Ok(())
+    }
+
+    fn append_adjustment(&self, adjustment: &Adjustment) -> Result<()> {
+        self.adjustments.lock().unwrap().push(adjustment.clone());
+        Ok(())
+    }
+
+    fn adjustments(&self) -> Result<Vec<Adjustment>> {
+        Ok(self.adjustments.lock().unwrap().clone())
+    }
 }
 
 // ── RecordingBatchNotifier ──────────────────────────────────────────────
@@ -286,19 +418,37 @@ impl Persistence for InMemoryPersistence {
 /// Records batch notification calls for assertion. Does not send real emails.
 pub struct RecordingBatchNotifier {
     pub calls: Mutex<Vec<BatchNotification>>,
+    pub reminders: Mutex<Vec<ReminderNotification>>,
+    pub settled: Mutex<Vec<SettledNotification>>,
 }
 
 pub struct BatchNotification {
     pub batch_id: String,
-    pub total: USD,
+    pub total: Money,
     pub txn_count: usize,
     pub warnings: Vec<String>,
 }
 
+pub struct ReminderNotification {
+    pub batch_id: String,
+    pub total: Money,
+    pub txn_count: usize,
+    pub days_open: i64,
+}
+
+pub struct SettledNotification {
+    pub batch_id: String,
+    pub total: Money,
+    pub settled_on: NaiveDate,
+    pub splits: Vec<Txn>,
+}
+
 impl RecordingBatchNotifier {
     pub fn new() -> Self {
         Self {
             calls: Mutex::new(vec![]),
+            reminders: Mutex::new(vec![]),
+            settled: Mutex::new(vec![]),
         }
     }
 
@@ -312,7 +462,7 @@ impl BatchNotifier for RecordingBatchNotifier {
     async fn send_batch_notification(
         &self,
         batch_id: &str,
-        total: &USD,
+        total: &Money,
         txns: &[Txn],
         warnings: Vec<String>,
     ) -> Result<()> {
@@ -324,4 +474,103 @@ impl BatchNotifier for RecordingBatchNotifier {
         });
         Ok(())
     }
+
+    async fn send_settled_notification(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        settled_on: NaiveDate,
+        splits: &[Txn],
+    ) -> Result<()> {
+        self.settled.lock().unwrap().push(SettledNotification {
+            batch_id: batch_id.to_string(),
+            total: *total,
+            settled_on,
+            splits: splits.to_vec(),
+        });
+        Ok(())
+    }
+
+    async fn send_reminder(
+        &self,
+        batch_id: &str,
+        total: &Money,
+        txns: &[Txn],
+        days_open: i64,
+    ) -> Result<()> {
+        self.reminders.lock().unwrap().push(ReminderNotification {
+            batch_id: batch_id.to_string(),
+            total: *total,
+            txn_count: txns.len(),
+            days_open,
+        });
+        Ok(())
+    }
+}
+
+/// Fails every notification with `reason`, like a channel that's down.
+pub struct FailingBatchNotifier {
+    pub reason: String,
+}
+
+#[async_trait]
+impl BatchNotifier for FailingBatchNotifier {
+    async fn send_batch_notification(
+        &self,
+        _batch_id: &str,
+        _total: &Money,
+        _txns: &[Txn],
+        _warnings: Vec<String>,
+    ) -> Result<()> {
+        Err(Error::Notification(self.reason.clone()))
+    }
+
+    async fn send_settled_notification(
+        &self,
+        _batch_id: &str,
+        _total: &Money,
+        _settled_on: NaiveDate,
+        _splits: &[Txn],
+    ) -> Result<()> {
+        Err(Error::Notification(self.reason.clone()))
+    }
+
+    async fn send_reminder(
+        &self,
+        _batch_id: &str,
+        _total: &Money,
+        _txns: &[Txn],
+        _days_open: i64,
+    ) -> Result<()> {
+        Err(Error::Notification(self.reason.clone()))
+    }
+}
+
+// ── ScriptedReviewer ────────────────────────────────────────────────────
+
+/// Answers reviews from a fixed list of decisions keyed by transaction id,
+/// accepting anything not listed. Records what it was asked.
+pub struct ScriptedReviewer {
+    pub decisions: Vec<(TransactionId, ReviewDecision)>,
+    pub asked: Vec<(TransactionId, ProposedAction)>,
+}
+
+impl ScriptedReviewer {
+    pub fn new(decisions: Vec<(TransactionId, ReviewDecision)>) -> Self {
+        Self {
+            decisions,
+            asked: vec![],
+        }
+    }
+}
+
+impl Reviewer for ScriptedReviewer {
+    fn review(&mut self, txn: &Transaction, action: ProposedAction) -> ReviewDecision {
+        self.asked.push((txn.id, action));
+        self.decisions
+            .iter()
+            .find(|(id, _)| *id == txn.id)
+            .map(|(_, decision)| *decision)
+            .unwrap_or(ReviewDecision::Accept)
+    }
 }